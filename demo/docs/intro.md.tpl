@@ -0,0 +1,3 @@
+# Intro
+
+A template with _some_ **emphasized** prose.
@@ -0,0 +1,67 @@
+//! Record applied corrections to an audit log.
+//!
+//! Each applied `BandAid` is appended as a single JSON object per line.
+
+use super::bandaid::BandAid;
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::io::Write;
+use std::path::Path;
+
+#[derive(Debug, Serialize)]
+struct Position {
+    line: usize,
+    column: usize,
+}
+
+/// A single applied correction.
+#[derive(Debug, Serialize)]
+struct Record<'a> {
+    path: &'a Path,
+    start: Position,
+    end: Position,
+    original: &'a str,
+    replacement: &'a str,
+    detector: Option<String>,
+}
+
+/// Append one record per bandaid applied to `path` to the `audit_log`.
+///
+/// `originals` are the replaced texts, in the same order as `bandaids`.
+pub(super) fn append(
+    audit_log: &Path,
+    path: &Path,
+    bandaids: &[BandAid],
+    originals: &[String],
+) -> Result<()> {
+    let mut records = Vec::with_capacity(bandaids.len() * 128);
+    for (bandaid, original) in bandaids.iter().zip(originals.iter()) {
+        let record = Record {
+            path,
+            start: Position {
+                line: bandaid.span.start.line,
+                column: bandaid.span.start.column,
+            },
+            end: Position {
+                line: bandaid.span.end.line,
+                column: bandaid.span.end.column,
+            },
+            original: original.as_str(),
+            replacement: bandaid.replacement.as_str(),
+            detector: bandaid.detector.map(|detector| detector.to_string()),
+        };
+        serde_json::to_writer(&mut records, &record)
+            .map_err(|e| anyhow!("Failed to serialize audit record").context(e))?;
+        records.push(b'\n');
+    }
+
+    // a single write, so records of one file are not interleaved
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(audit_log)
+        .map_err(|e| anyhow!("Failed to open audit log {}", audit_log.display()).context(e))?;
+    file.write_all(&records)
+        .map_err(|e| anyhow!("Failed to append to audit log {}", audit_log.display()).context(e))?;
+    Ok(())
+}
@@ -1,18 +1,36 @@
-use crate::span::Span;
-use crate::suggestion::Suggestion;
+use crate::literalset::CommentVariant;
+use crate::span::{LineColumn, Span};
+use crate::suggestion::{Detector, Suggestion};
 use anyhow::{anyhow, Error, Result};
 use log::trace;
 use std::convert::TryFrom;
+
+/// A replacement of the content covered by a span.
+///
+/// The span and the replacement are independent in their number of lines,
+/// a span within a single line may be replaced by multiple lines and a
+/// span across lines may be replaced by a single one. The span always
+/// refers to the original content, so the line numbers of subsequent
+/// bandaids are not affected by a change in the number of lines.
+///
+/// The replacement is written verbatim besides its line breaks, which
+/// are adjusted to the line ending of the line the span starts in. So
+/// continuation lines within a comment must carry the comment prefix.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct BandAid {
     /// a span, where the first line has index 1, columns are base 1 too
     pub span: Span,
-    /// replacement text for the given span
+    /// replacement text for the given span, may contain line breaks
     pub replacement: String,
+    /// the detector which found the issue, if any
+    pub detector: Option<Detector>,
+    /// insert the replacement right before the start of `span` instead of
+    /// replacing what it covers, the end of `span` is irrelevant
+    pub insert: bool,
 }
 
 impl BandAid {
-    pub fn new(replacement: &str, span: &Span) -> Self {
+    pub fn new(replacement: &str, span: &Span, variant: CommentVariant) -> Self {
         trace!(
             "proc_macro literal span of doc comment: ({},{})..({},{})",
             span.start.line,
@@ -22,15 +40,48 @@ impl BandAid {
         );
 
         let mut span = span.clone();
-        // @todo this is a hack and should be documented better
-        // @todo not sure why the offset of two is necessary
-        // @todo but it works consistently
-        let doc_comment_to_file_offset = 2;
-        span.start.column += doc_comment_to_file_offset;
-        span.end.column += doc_comment_to_file_offset;
+        // the literal span of a doc comment starts at the `///` prefix, whereas
+        // the rendered literal only accounts for a single quote character
+        span.start.column = variant.file_column(span.start.column);
+        span.end.column = variant.file_column(span.end.column);
         Self {
             span,
             replacement: replacement.to_owned(),
+            ..Default::default()
+        }
+    }
+
+    /// Insert `text` right before the character at `column` of `line`, or at
+    /// the end of the line if `column` is past it, without replacing anything.
+    pub fn insertion(text: &str, line: usize, column: usize) -> Self {
+        let at = LineColumn { line, column };
+        Self {
+            span: Span { start: at, end: at },
+            replacement: text.to_owned(),
+            detector: None,
+            insert: true,
+        }
+    }
+
+    /// Remove everything covered by `span`.
+    pub fn deletion(span: Span) -> Self {
+        Self {
+            span,
+            ..Default::default()
+        }
+    }
+}
+
+/// An empty replacement at the very beginning, for filling in the relevant
+/// fields only.
+impl Default for BandAid {
+    fn default() -> Self {
+        let start = LineColumn { line: 1, column: 0 };
+        Self {
+            span: Span { start, end: start },
+            replacement: String::new(),
+            detector: None,
+            insert: false,
         }
     }
 }
@@ -48,7 +99,13 @@ impl<'s> TryFrom<(&Suggestion<'s>, usize)> for BandAid {
         );
 
         if let Some(replacement) = suggestion.replacements.iter().nth(pick_idx) {
-            Ok(Self::new(replacement.as_str(), &suggestion.span))
+            let mut bandaid = Self::new(
+                replacement.as_str(),
+                &suggestion.span,
+                suggestion.literal.variant(),
+            );
+            bandaid.detector = Some(suggestion.detector);
+            Ok(bandaid)
         } else {
             Err(anyhow!("Does not contain any replacements"))
         }
@@ -64,6 +121,10 @@ impl<'s> TryFrom<(Suggestion<'s>, usize)> for BandAid {
 
 impl From<(String, Span)> for BandAid {
     fn from((replacement, span): (String, Span)) -> Self {
-        Self { span, replacement }
+        Self {
+            span,
+            replacement,
+            ..Default::default()
+        }
     }
 }
@@ -10,21 +10,27 @@ use crossterm::{
     cursor,
     event::{Event, KeyCode, KeyEvent, KeyModifiers},
     style::{style, Attribute, Color, ContentStyle, Print, PrintStyledContent, StyledContent},
-    terminal, QueueableCommand,
+    terminal,
+    tty::IsTty,
+    QueueableCommand,
 };
 
 use std::convert::TryFrom;
+use std::fmt;
 use std::io::{stdin, stdout};
 use std::path::Path;
 
 const HELP: &'static str = r##"y - apply this suggestion
 n - do not apply the suggested correction
+a - add the word to the project dictionary, so it is never flagged again
+A - apply this suggestion and the first replacement of all remaining ones in this file
 q - quit; do not stage this hunk or any of the remaining ones
 d - do not apply this suggestion and skip the rest of the file
 g - select a suggestion to go to
-j - leave this hunk undecided, see next undecided hunk
+j - go back to the previous suggestion, a new decision replaces the earlier one
 J - leave this hunk undecided, see next hunk
-e - manually edit the current hunk
+e - manually edit the current hunk, with `$EDITOR` if set
+u - undo the last decision and present that suggestion again
 ? - print help
 
 
@@ -48,11 +54,182 @@ impl Drop for ScopedRaw {
     }
 }
 
-/// In which direction we should progress
-#[derive(Debug, Clone, Copy)]
-enum Direction {
-    Forward,
-    Backward,
+/// Drop the colors of `style` unless colored output is enabled, the
+/// attributes are retained since they convey i.e. the cursor position.
+fn colored(style: ContentStyle) -> ContentStyle {
+    if crate::color::colors_enabled() {
+        style
+    } else {
+        ContentStyle {
+            foreground_color: None,
+            background_color: None,
+            ..style
+        }
+    }
+}
+
+/// Assure both ends of the conversation with the user are a terminal.
+///
+/// Raw mode and cursor movement are meaningless when piped or run in CI,
+/// so bail early with a clear message rather than garbling the output.
+fn ensure_tty(stdin_is_tty: bool, stdout_is_tty: bool) -> Result<()> {
+    match (stdin_is_tty, stdout_is_tty) {
+        (true, true) => Ok(()),
+        (false, _) => Err(anyhow::anyhow!(
+            "Interactive mode requires stdin to be a terminal, use `check` or `fix` instead"
+        )),
+        (_, false) => Err(anyhow::anyhow!(
+            "Interactive mode requires stdout to be a terminal, use `check` or `fix` instead"
+        )),
+    }
+}
+
+/// Number of lines at the end of a printed suggestion, starting with the
+/// inline replacements, which are replaced by the interactive list.
+fn suggestion_tail(suggestion: &Suggestion) -> u16 {
+    // the replacements, a separator, the optional description, another
+    // separator and the final newline of `println!`
+    if suggestion.description.is_some() {
+        5
+    } else {
+        4
+    }
+}
+
+/// Number of lines after printing `HELP`, which are overwritten by the prompt.
+fn help_tail() -> u16 {
+    (HELP.len() - HELP.trim_end_matches('\n').len()) as u16
+}
+
+/// Let the user edit `content` with the given editor command.
+///
+/// The command may carry arguments, i.e. `code --wait`. Returns `None` if the
+/// editor exits unsuccessfully, which is treated as cancelling the edit.
+fn edit_externally(editor: &str, content: &str) -> Result<Option<String>> {
+    let mut args = editor.split_whitespace();
+    let program = args
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("Editor command is empty"))?;
+
+    // a unique file which must not exist yet, so no other file is clobbered
+    let (path, mut file) = create_temporary(&std::env::temp_dir().join("cargo-spellcheck.txt"))?;
+    let written = file.write_all(content.as_bytes());
+    drop(file);
+    if let Err(e) = written {
+        let _ = fs::remove_file(&path);
+        return Err(anyhow::anyhow!("Failed to write {}", path.display()).context(e));
+    }
+
+    let status = std::process::Command::new(program)
+        .args(args)
+        .arg(&path)
+        .status()
+        .map_err(|e| anyhow::anyhow!("Failed to launch editor {}", program).context(e));
+    let edited = match status {
+        Ok(status) if status.success() => fs::read_to_string(&path)
+            .map(Some)
+            .map_err(|e| anyhow::anyhow!("Failed to read {}", path.display()).context(e)),
+        Ok(status) => {
+            info!("Editor exited with {}, discarding the edit", status);
+            Ok(None)
+        }
+        Err(e) => Err(e),
+    };
+    let _ = fs::remove_file(&path);
+
+    // most editors terminate the last line
+    Ok(edited?.map(|edited| {
+        edited
+            .strip_suffix('\n')
+            .map(|edited| edited.strip_suffix('\r').unwrap_or(edited))
+            .unwrap_or(&edited)
+            .to_owned()
+    }))
+}
+
+/// Which suggestion to present next, after processing a `Pick`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Next {
+    /// Present the suggestion with the given index of the current file.
+    Index(usize),
+    /// Skip the remaining suggestions of the current file.
+    SkipFile,
+    /// Apply the first replacement of all suggestions of the current file,
+    /// starting with the given index.
+    ApplyRemaining(usize),
+    /// Stop execution.
+    Quit,
+}
+
+/// Position of the currently presented suggestion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Progress {
+    /// 0-indexed position within the current file.
+    idx: usize,
+    /// Number of suggestions in the current file.
+    count: usize,
+    /// 0-indexed position across all files.
+    overall_idx: usize,
+    /// Number of suggestions across all files.
+    total: usize,
+}
+
+impl fmt::Display for Progress {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.total > self.count {
+            write!(
+                formatter,
+                "(file {}/{}, overall {}/{})",
+                self.idx + 1,
+                self.count,
+                self.overall_idx + 1,
+                self.total
+            )
+        } else {
+            write!(formatter, "({}/{})", self.idx + 1, self.count)
+        }
+    }
+}
+
+/// Parse the 1-based number of a suggestion out of `count` into an index.
+fn parse_goto(input: &str, count: usize) -> Option<usize> {
+    match input.trim().parse::<usize>() {
+        Ok(n) if n >= 1 && n <= count => Some(n - 1),
+        _ => None,
+    }
+}
+
+/// Keeps track of the cumulative position across all files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Tally {
+    /// Number of suggestions across all files.
+    total: usize,
+    /// Number of suggestions of all completed files.
+    offset: usize,
+}
+
+impl Tally {
+    fn new(suggestions_per_path: &SuggestionSet) -> Self {
+        Self {
+            total: suggestions_per_path.count(),
+            offset: 0,
+        }
+    }
+
+    /// Position of the suggestion `idx` of the current file with `count` suggestions.
+    fn progress(&self, idx: usize, count: usize) -> Progress {
+        Progress {
+            idx,
+            count,
+            overall_idx: self.offset + idx,
+            total: self.total,
+        }
+    }
+
+    /// Move on to the next file, after the current one with `count` suggestions.
+    fn complete_file(&mut self, count: usize) {
+        self.offset += count;
+    }
 }
 
 /// The user picked something. This is the pick representation.
@@ -63,6 +240,15 @@ pub(super) enum Pick {
     Skip,
     /// Jump to the previous suggestion.
     Previous,
+    /// Jump to the suggestion with the given index of the current file.
+    Goto(usize),
+    /// Revert the last picked replacement and present its suggestion again.
+    Undo,
+    /// Accept the flagged word and add it to the project dictionary.
+    AddWord(String),
+    /// Apply the replacement and the first replacement of all remaining
+    /// suggestions of the current file.
+    ApplyRemaining(BandAid),
     /// Print the help message and exit.
     Help,
     /// Skip the remaining fixes for the current file.
@@ -82,10 +268,18 @@ where
     pub suggestion: &'s Suggestion<'t>,
     /// The content the user provided for the suggestion, if any.
     pub custom_replacement: String,
+    /// Position of the cursor within `custom_replacement`, in characters.
+    pub cursor: usize,
     /// Which index to show as highlighted.
     pub pick_idx: usize,
-    /// Total number of pickable slots.
+    /// Total number of pickable slots, bounded by `max_replacements` plus the custom entry.
     pub n_items: usize,
+    /// Number of trailing lines of the previous print, which are overwritten by the prompt.
+    pub tail: u16,
+    /// Terminal row of the first line of the list of replacements.
+    pub origin: u16,
+    /// Columns a tab advances when printing the suggestion.
+    pub tab_width: usize,
 }
 
 impl<'s, 't> From<&'s Suggestion<'t>> for State<'s, 't> {
@@ -93,9 +287,13 @@ impl<'s, 't> From<&'s Suggestion<'t>> for State<'s, 't> {
         Self {
             suggestion,
             custom_replacement: String::new(),
+            cursor: 0usize,
             pick_idx: 0usize,
             // all items provided by the checkers plus the user provided
             n_items: suggestion.replacements.len() + 1,
+            tail: suggestion_tail(suggestion),
+            origin: 0u16,
+            tab_width: crate::DEFAULT_TAB_WIDTH,
         }
     }
 }
@@ -120,6 +318,68 @@ where
         self.pick_idx + 1 == self.n_items
     }
 
+    /// Byte offset of the `cursor` within `custom_replacement`.
+    fn cursor_offset(&self) -> usize {
+        self.custom_replacement
+            .char_indices()
+            .nth(self.cursor)
+            .map_or(self.custom_replacement.len(), |(offset, _c)| offset)
+    }
+
+    /// Insert `c` at the cursor and advance the cursor.
+    pub fn insert(&mut self, c: char) {
+        let offset = self.cursor_offset();
+        self.custom_replacement.insert(offset, c);
+        self.cursor += 1;
+    }
+
+    /// Remove the character before the cursor.
+    pub fn backspace(&mut self) {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            let offset = self.cursor_offset();
+            let _ = self.custom_replacement.remove(offset);
+        }
+    }
+
+    /// Remove the character under the cursor.
+    pub fn delete(&mut self) {
+        let offset = self.cursor_offset();
+        if offset < self.custom_replacement.len() {
+            let _ = self.custom_replacement.remove(offset);
+        }
+    }
+
+    pub fn cursor_left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    pub fn cursor_right(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.custom_replacement.chars().count());
+    }
+
+    pub fn cursor_home(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn cursor_end(&mut self) {
+        self.cursor = self.custom_replacement.chars().count();
+    }
+
+    /// Split the custom replacement into the part before the cursor, the
+    /// character under the cursor, which is a space at the end, and the rest.
+    fn split_at_cursor(&self) -> (&str, &str, &str) {
+        let offset = self.cursor_offset();
+        let (before, rest) = self.custom_replacement.split_at(offset);
+        match rest.chars().next() {
+            Some(c) => {
+                let (at, after) = rest.split_at(c.len_utf8());
+                (before, at, after)
+            }
+            None => (before, " ", ""),
+        }
+    }
+
     pub fn to_bandaid(&self) -> BandAid {
         if self.is_custom_entry() {
             BandAid::from((
@@ -137,6 +397,10 @@ where
 #[derive(Debug, Clone, Default)]
 pub struct UserPicked {
     pub bandaids: indexmap::IndexMap<PathBuf, Vec<BandAid>>,
+    /// Index of the suggestion each bandaid was picked for, in the order they were added.
+    history: Vec<(PathBuf, usize)>,
+    /// Words to be added to the project dictionary.
+    pub words: Vec<String>,
 }
 
 impl UserPicked {
@@ -153,6 +417,104 @@ impl UserPicked {
             .push(fix);
     }
 
+    /// Apply a single bandaid, picked for the suggestion with index `idx`.
+    ///
+    /// Replaces an earlier pick for the same suggestion.
+    fn add_picked_bandaid(&mut self, path: &Path, idx: usize, fix: BandAid) {
+        self.forget(path, idx);
+        self.add_bandaid(path, fix);
+        self.history.push((path.to_owned(), idx));
+    }
+
+    /// Remove the bandaid picked for the suggestion with index `idx`, if any.
+    fn forget(&mut self, path: &Path, idx: usize) {
+        // the bandaids of a file are in the same order as their history entries
+        let nth = self
+            .history
+            .iter()
+            .filter(|(picked_path, _)| picked_path == path)
+            .position(|(_, picked_idx)| *picked_idx == idx);
+        if let Some(nth) = nth {
+            self.history
+                .retain(|(picked_path, picked_idx)| !(picked_path == path && *picked_idx == idx));
+            if let Some(bandaids) = self.bandaids.get_mut(path) {
+                let _ = bandaids.remove(nth);
+                if bandaids.is_empty() {
+                    let _ = self.bandaids.remove(path);
+                }
+            }
+        }
+    }
+
+    /// Remove the most recently picked bandaid of `path`.
+    ///
+    /// Returns the index of the suggestion the bandaid was picked for.
+    fn undo(&mut self, path: &Path) -> Option<usize> {
+        match self.history.last() {
+            Some((last, _idx)) if last == path => {}
+            _ => return None,
+        }
+        let (_path, idx) = self.history.pop().expect("Must be Some(_)");
+        if let Some(bandaids) = self.bandaids.get_mut(path) {
+            let _ = bandaids.pop();
+            if bandaids.is_empty() {
+                let _ = self.bandaids.remove(path);
+            }
+        }
+        Some(idx)
+    }
+
+    /// Process the `pick` for the suggestion with index `idx` of `path`.
+    fn process(&mut self, path: &Path, idx: usize, pick: Pick) -> Next {
+        match pick {
+            Pick::Quit => Next::Quit,
+            Pick::SkipFile => Next::SkipFile,
+            Pick::Previous => {
+                if idx == 0 {
+                    trace!("Already at the first suggestion of {}", path.display());
+                }
+                Next::Index(idx.saturating_sub(1))
+            }
+            Pick::Help => unreachable!("Help must not be reachable here, it is handled before"),
+            Pick::Replacement(bandaid) => {
+                self.add_picked_bandaid(path, idx, bandaid);
+                Next::Index(idx + 1)
+            }
+            Pick::Undo => {
+                if let Some(previous) = self.undo(path) {
+                    Next::Index(previous)
+                } else {
+                    trace!("Nothing to undo in {}", path.display());
+                    Next::Index(idx)
+                }
+            }
+            Pick::Goto(target) => Next::Index(target),
+            Pick::ApplyRemaining(bandaid) => {
+                self.add_picked_bandaid(path, idx, bandaid);
+                Next::ApplyRemaining(idx + 1)
+            }
+            Pick::AddWord(word) => {
+                if !self.words.contains(&word) {
+                    self.words.push(word);
+                }
+                Next::Index(idx + 1)
+            }
+            Pick::Skip | Pick::Nop => Next::Index(idx + 1),
+        }
+    }
+
+    /// Pick the first replacement of all `suggestions` of `path`, starting with index `from`.
+    ///
+    /// Suggestions without any replacement are skipped.
+    fn apply_remaining(&mut self, path: &Path, suggestions: &[Suggestion], from: usize) {
+        for (idx, suggestion) in suggestions.iter().enumerate().skip(from) {
+            match BandAid::try_from((suggestion, 0)) {
+                Ok(bandaid) => self.add_picked_bandaid(path, idx, bandaid),
+                Err(_) => trace!("Suggestion did not contain a replacement, skip"),
+            }
+        }
+    }
+
     /// Apply multiple bandaids.
     #[allow(unused)]
     fn add_bandaids<I>(&mut self, path: &Path, fixes: I)
@@ -174,12 +536,23 @@ impl UserPicked {
             KeyCode::Up => state.select_next(),
             KeyCode::Down => state.select_previous(),
             KeyCode::Enter => {
-                let bandaid = BandAid::new(&state.custom_replacement, &state.suggestion.span);
+                let mut bandaid = BandAid::new(
+                    &state.custom_replacement,
+                    &state.suggestion.span,
+                    state.suggestion.literal.variant(),
+                );
+                bandaid.detector = Some(state.suggestion.detector);
                 return Ok(Pick::Replacement(bandaid));
             }
             KeyCode::Esc => return Ok(Pick::Quit),
             KeyCode::Char('c') if modifiers == KeyModifiers::CONTROL => return Ok(Pick::Quit),
-            KeyCode::Char(c) => state.custom_replacement.push(c), // @todo handle cursors and insert / delete mode
+            KeyCode::Char(c) => state.insert(c),
+            KeyCode::Backspace => state.backspace(),
+            KeyCode::Delete => state.delete(),
+            KeyCode::Left => state.cursor_left(),
+            KeyCode::Right => state.cursor_right(),
+            KeyCode::Home => state.cursor_home(),
+            KeyCode::End => state.cursor_end(),
             _ => {}
         }
 
@@ -196,27 +569,34 @@ impl UserPicked {
     fn print_replacements_list(&self, state: &State) -> Result<()> {
         let mut stdout = stdout();
 
-        let tick = ContentStyle::new()
-            .foreground(Color::Green)
-            .attribute(Attribute::Bold);
+        let tick = colored(
+            ContentStyle::new()
+                .foreground(Color::Green)
+                .attribute(Attribute::Bold),
+        );
 
-        let highlight = ContentStyle::new()
-            .background(Color::Black)
-            .foreground(Color::Green)
-            .attribute(Attribute::Bold);
+        let highlight = colored(
+            ContentStyle::new()
+                .background(Color::Black)
+                .foreground(Color::Green)
+                .attribute(Attribute::Bold),
+        );
 
-        let others = ContentStyle::new()
-            .background(Color::Black)
-            .foreground(Color::Blue);
+        let others = colored(
+            ContentStyle::new()
+                .background(Color::Black)
+                .foreground(Color::Blue),
+        );
 
-        let custom = ContentStyle::new()
-            .background(Color::Black)
-            .foreground(Color::Yellow);
+        let custom = colored(
+            ContentStyle::new()
+                .background(Color::Black)
+                .foreground(Color::Yellow),
+        );
 
-        // render all replacements in a vertical list
-
-        stdout.queue(cursor::SavePosition).unwrap();
-        let _ = stdout.flush();
+        // render all replacements in a vertical list, the custom entry is the
+        // last row, the first replacement right above it
+        let row = |idx: usize| state.origin + (state.n_items - 1 - idx) as u16;
 
         let active_idx = state.pick_idx;
 
@@ -227,7 +607,7 @@ impl UserPicked {
         };
         if state.n_items != active_idx + 1 {
             stdout
-                .queue(cursor::MoveUp(1))
+                .queue(cursor::MoveTo(0, row(0)))
                 .unwrap()
                 .queue(terminal::Clear(terminal::ClearType::CurrentLine))
                 .unwrap()
@@ -239,8 +619,10 @@ impl UserPicked {
                 )))
                 .unwrap();
         } else {
+            // the character under the cursor is rendered reversed
+            let (before, at, after) = state.split_at_cursor();
             stdout
-                .queue(cursor::MoveUp(1))
+                .queue(cursor::MoveTo(0, row(0)))
                 .unwrap()
                 .queue(terminal::Clear(terminal::ClearType::CurrentLine))
                 .unwrap()
@@ -251,9 +633,16 @@ impl UserPicked {
                 .queue(cursor::MoveToColumn(4))
                 .unwrap()
                 .queue(PrintStyledContent(StyledContent::new(
-                    custom,
-                    custom_content,
+                    custom.clone(),
+                    before,
+                )))
+                .unwrap()
+                .queue(PrintStyledContent(StyledContent::new(
+                    custom.clone().attribute(Attribute::Reverse),
+                    at,
                 )))
+                .unwrap()
+                .queue(PrintStyledContent(StyledContent::new(custom, after)))
                 .unwrap();
         }
         let _ = stdout.flush();
@@ -268,8 +657,7 @@ impl UserPicked {
                 if idx != active_idx as u16 {
                     // @todo figure out a way to deal with those errors better
                     stdout
-                        // .queue(cursor::MoveTo(start.0 + idx, start.1)).unwrap()
-                        .queue(cursor::MoveUp(1))
+                        .queue(cursor::MoveTo(0, row(idx as usize + 1)))
                         .unwrap()
                         .queue(terminal::Clear(terminal::ClearType::CurrentLine))
                         .unwrap()
@@ -282,8 +670,7 @@ impl UserPicked {
                         .unwrap();
                 } else {
                     stdout
-                        // .queue(cursor::MoveTo(start.0 + idx, start.1)).unwrap()
-                        .queue(cursor::MoveUp(1))
+                        .queue(cursor::MoveTo(0, row(idx as usize + 1)))
                         .unwrap()
                         .queue(terminal::Clear(terminal::ClearType::CurrentLine))
                         .unwrap()
@@ -301,62 +688,88 @@ impl UserPicked {
                 }
             });
 
-        stdout.queue(cursor::RestorePosition).unwrap();
+        // park the cursor right below the list
+        stdout
+            .queue(cursor::MoveTo(0, state.origin + state.n_items as u16))
+            .unwrap();
 
         let _ = stdout.flush();
         Ok(())
     }
 
-    /// Wait for user input and process it into a `Pick` enum
-    fn user_input(&self, state: &mut State, running_idx: (usize, usize)) -> Result<Pick> {
-        {
-            let _guard = ScopedRaw::new();
+    /// Ask for the number of a suggestion of the current file, until a valid one is given.
+    ///
+    /// An empty input stays with the current suggestion.
+    fn ask_goto(&self, progress: Progress) -> Result<usize> {
+        let mut stdout = stdout();
+        loop {
+            print!("Go to suggestion [1-{}]: ", progress.count);
+            let _ = stdout.flush();
+            let mut input = String::with_capacity(8);
+            if stdin().read_line(&mut input)? == 0 {
+                return Ok(progress.idx);
+            }
+            let input = input.trim();
+            if input.is_empty() {
+                return Ok(progress.idx);
+            }
+            match parse_goto(input, progress.count) {
+                Some(idx) => return Ok(idx),
+                None => println!("{} is not a suggestion of this file", input),
+            }
+        }
+    }
 
-            let boring = ContentStyle::new()
+    /// Print the question and reserve the lines for the list of replacements.
+    ///
+    /// Overwrites the last `state.tail` lines of the previous print and
+    /// records the row the list starts at, so it can be drawn at absolute
+    /// positions.
+    fn print_prompt(&self, state: &mut State, progress: Progress) -> Result<()> {
+        let boring = colored(
+            ContentStyle::new()
                 .foreground(Color::Blue)
-                .attribute(Attribute::Bold);
-
-            let question = format!(
-                "({nth}/{of_n}) Apply this suggestion [y,n,q,a,d,j,e,?]?",
-                nth = running_idx.0 + 1,
-                of_n = running_idx.1
-            );
-
-            // a new suggestion, so prepare for the number of items that are visible
-            // and also overwrite the last lines of the regular print which would
-            // already contain the suggestions
-            stdout()
-                .queue(cursor::Hide)
-                .unwrap()
-                .queue(cursor::MoveToColumn(0))
-                .unwrap()
-                .queue(cursor::MoveUp(5)) // erase the 5 last lines of suggestion print
-                .unwrap()
-                .queue(cursor::MoveToColumn(0))
-                .unwrap()
-                .queue(terminal::Clear(terminal::ClearType::CurrentLine))
-                .unwrap()
-                .queue(cursor::MoveDown(1))
-                .unwrap()
-                .queue(terminal::Clear(terminal::ClearType::CurrentLine))
-                .unwrap()
-                .queue(cursor::MoveToColumn(0))
-                .unwrap()
-                .queue(PrintStyledContent(StyledContent::new(boring, question)))
-                .unwrap()
-                .queue(cursor::MoveToColumn(0))
-                .unwrap()
-                .queue(cursor::MoveDown(1))
-                .unwrap()
-                .queue(terminal::Clear(terminal::ClearType::CurrentLine))
-                .unwrap()
-                .queue(cursor::MoveDown(1))
-                .unwrap()
-                .queue(terminal::Clear(terminal::ClearType::CurrentLine))
-                .unwrap() // @todo deal with error conversion
-                .queue(terminal::ScrollUp((state.n_items) as u16))
-                .unwrap();
-        }
+                .attribute(Attribute::Bold),
+        );
+
+        let question = format!(
+            "{progress} Apply this suggestion [y,n,q,a,A,d,g,j,e,u,?]?",
+            progress = progress
+        );
+
+        let mut stdout = stdout();
+        stdout
+            .queue(cursor::Hide)?
+            .queue(cursor::MoveToColumn(0))?
+            .queue(cursor::MoveUp(state.tail))?
+            .queue(terminal::Clear(terminal::ClearType::FromCursorDown))?
+            .queue(PrintStyledContent(StyledContent::new(boring, question)))?;
+        // one line for each item, scrolls the terminal if necessary
+        stdout.queue(Print("\n".repeat(state.n_items + 1)))?;
+        stdout.flush()?;
+
+        let (_column, row) = cursor::position()?;
+        state.origin = row.saturating_sub(state.n_items as u16);
+        Ok(())
+    }
+
+    /// Render the current suggestion and the prompt from scratch.
+    fn redraw(&self, state: &mut State, progress: Progress) -> Result<()> {
+        stdout()
+            .queue(terminal::Clear(terminal::ClearType::All))?
+            .queue(cursor::MoveTo(0, 0))?
+            .flush()?;
+        println!(
+            "{}",
+            state.suggestion.diagnostic().tab_width(state.tab_width)
+        );
+        state.tail = suggestion_tail(state.suggestion);
+        self.print_prompt(state, progress)
+    }
+
+    /// Wait for user input and process it into a `Pick` enum
+    fn user_input(&self, state: &mut State, progress: Progress) -> Result<Pick> {
+        self.print_prompt(state, progress)?;
 
         loop {
             let mut guard = ScopedRaw::new();
@@ -369,6 +782,9 @@ impl UserPicked {
                 Event::Key(event) => event,
                 Event::Resize(..) => {
                     drop(guard);
+                    // the terminal reflowed all content, relative positions
+                    // are meaningless now, so start over from a clean screen
+                    self.redraw(state, progress)?;
                     continue;
                 }
                 sth => {
@@ -402,14 +818,40 @@ impl UserPicked {
                     return Ok(Pick::Replacement(bandaid));
                 }
                 KeyCode::Char('n') => return Ok(Pick::Skip),
+                KeyCode::Char('g') => return Ok(Pick::Goto(self.ask_goto(progress)?)),
+                KeyCode::Char('A') => return Ok(Pick::ApplyRemaining(state.to_bandaid())),
+                KeyCode::Char('a') => {
+                    if let Some(word) = crate::report::covered_text(state.suggestion) {
+                        return Ok(Pick::AddWord(word));
+                    }
+                    warn!("Failed to obtain the flagged word, spanning multiple lines?");
+                }
                 KeyCode::Char('j') => return Ok(Pick::Previous),
                 KeyCode::Char('c') if modifiers == KeyModifiers::CONTROL => return Ok(Pick::Quit),
                 KeyCode::Char('q') | KeyCode::Esc => return Ok(Pick::Quit),
                 KeyCode::Char('d') => return Ok(Pick::SkipFile),
-                KeyCode::Char('e') => {
-                    // jump to the user input entry
-                    state.select_custom();
-                }
+                KeyCode::Char('u') => return Ok(Pick::Undo),
+                KeyCode::Char('e') => match std::env::var("EDITOR") {
+                    Ok(editor) if !editor.trim().is_empty() => {
+                        let original =
+                            crate::report::covered_text(state.suggestion).unwrap_or_default();
+                        if let Some(replacement) = edit_externally(&editor, &original)? {
+                            let mut bandaid = BandAid::new(
+                                &replacement,
+                                &state.suggestion.span,
+                                state.suggestion.literal.variant(),
+                            );
+                            bandaid.detector = Some(state.suggestion.detector);
+                            return Ok(Pick::Replacement(bandaid));
+                        }
+                        // the editor might have left the screen in any state
+                        self.redraw(state, progress)?;
+                    }
+                    _ => {
+                        // jump to the user input entry
+                        state.select_custom();
+                    }
+                },
                 KeyCode::Char('?') => return Ok(Pick::Help),
                 x => {
                     trace!("Unexpected input {:?}", x);
@@ -421,73 +863,384 @@ impl UserPicked {
 
     pub(super) fn select_interactive<'s>(
         suggestions_per_path: SuggestionSet<'s>,
-        _config: &Config,
+        config: &Config,
     ) -> Result<Self> {
         let mut picked = UserPicked::default();
 
+        // nothing to ask for, so there is no need for a terminal either
+        if suggestions_per_path.count() == 0 {
+            return Ok(picked);
+        }
+        ensure_tty(stdin().is_tty(), stdout().is_tty())?;
+
         trace!("Select the ones to actully use");
 
+        let mut tally = Tally::new(&suggestions_per_path);
         for (path, suggestions) in suggestions_per_path {
             let count = suggestions.len();
+            if count == 0 {
+                continue;
+            }
             println!("Path is {} and has {}", path.display(), count);
 
-            // @todo juck, uggly
-            let mut suggestions_it = suggestions.clone().into_iter().enumerate();
+            let mut idx = 0usize;
+            // suggestions without replacements are skipped in the direction of travel
+            let mut forward = true;
+            while let Some(suggestion) = suggestions.get(idx) {
+                trace!("next() ---> {:?}", suggestion);
 
-            let mut direction = Direction::Forward;
-            loop {
-                let opt: Option<(usize, Suggestion)> = match direction {
-                    Direction::Forward => suggestions_it.next(),
-                    Direction::Backward => suggestions_it.next_back(), // FIXME @todo this is just plain wrong
-                };
-
-                trace!("next() ---> {:?}", &opt);
-
-                if opt.is_none() {
-                    match direction {
-                        Direction::Forward => {
-                            trace!("completed file, continue to next");
-                            break; // we completed this file, move on to the next
-                        }
-                        Direction::Backward => {
-                            trace!("went back, now back at the beginning");
-                            suggestions_it = suggestions.clone().into_iter().enumerate();
-                            continue;
-                        } // go to the start
-                    }
-                }
-                let (idx, suggestion) = opt.expect("Must be Some(_)");
                 if suggestion.replacements.is_empty() {
                     trace!("Suggestion did not contain a replacement, skip");
+                    if forward || idx == 0 {
+                        forward = true;
+                        idx += 1;
+                    } else {
+                        idx -= 1;
+                    }
                     continue;
                 }
-                println!("{}", suggestion);
+                let mut state = State::from(suggestion);
+                state.tab_width = config.tab_width;
+                println!("{}", suggestion.diagnostic().tab_width(state.tab_width));
 
-                let mut state = State::from(&suggestion);
-
-                let mut pick = picked.user_input(&mut state, (idx, count))?;
+                let progress = tally.progress(idx, count);
+                let mut pick = picked.user_input(&mut state, progress)?;
                 while pick == Pick::Help {
                     println!("{}", HELP);
-                    pick = picked.user_input(&mut state, (idx, count))?;
+                    state.tail = help_tail();
+                    pick = picked.user_input(&mut state, progress)?;
                 }
-                match pick {
-                    Pick::Quit => return Ok(picked),
-                    Pick::SkipFile => break, // break the inner loop
-                    Pick::Previous => {
-                        unimplemented!("Requires a iterator which works bidrectionally")
-                    }
-                    Pick::Help => {
-                        unreachable!("Help must not be reachable here, it is handled before")
+                match picked.process(&path, idx, pick) {
+                    Next::Quit => return Ok(picked),
+                    Next::SkipFile => break, // break the inner loop
+                    Next::ApplyRemaining(from) => {
+                        picked.apply_remaining(&path, &suggestions, from);
+                        break;
                     }
-                    Pick::Replacement(bandaid) => {
-                        picked.add_bandaid(&path, bandaid);
+                    Next::Index(next) => {
+                        forward = next >= idx;
+                        idx = next;
                     }
-                    _ => continue,
-                };
-
-                direction = Direction::Forward;
+                }
             }
+            tally.complete_file(count);
         }
         Ok(picked)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overall_progress() {
+        let mut tally = Tally {
+            total: 5,
+            offset: 0,
+        };
+        // first file with 2 suggestions
+        assert_eq!(tally.progress(0, 2).to_string(), "(file 1/2, overall 1/5)");
+        assert_eq!(tally.progress(1, 2).to_string(), "(file 2/2, overall 2/5)");
+        tally.complete_file(2);
+        // second file with 3 suggestions
+        assert_eq!(tally.progress(0, 3).to_string(), "(file 1/3, overall 3/5)");
+        assert_eq!(tally.progress(2, 3).to_string(), "(file 3/3, overall 5/5)");
+
+        // a single file does not require the overall position
+        let tally = Tally {
+            total: 3,
+            offset: 0,
+        };
+        assert_eq!(tally.progress(1, 3).to_string(), "(2/3)");
+    }
+
+    #[test]
+    fn non_tty() {
+        assert!(ensure_tty(true, true).is_ok());
+        assert!(ensure_tty(false, true).is_err());
+        assert!(ensure_tty(true, false).is_err());
+        assert!(ensure_tty(false, false).is_err());
+
+        // an empty set never touches the terminal
+        let picked =
+            UserPicked::select_interactive(SuggestionSet::new(), &Config::default()).unwrap();
+        assert_eq!(picked.count(), 0);
+    }
+
+    #[test]
+    fn prompt_overwrites_tail() {
+        let docs = crate::testcase::documentation();
+        let set = crate::testcase::suggestion_set(&docs);
+        let (_, suggestions) = set.iter().next().unwrap();
+        let mut suggestion = suggestions[0].clone();
+
+        // lines from the inline replacements on, plus the one of `println!`
+        let tail = |suggestion: &Suggestion| {
+            let printed = console::strip_ansi_codes(&suggestion.to_string()).into_owned();
+            let lines = printed.lines().collect::<Vec<_>>();
+            let at = lines
+                .iter()
+                .position(|line| line.contains(" - typo"))
+                .unwrap();
+            (lines.len() - at + 1) as u16
+        };
+        assert_eq!(suggestion_tail(&suggestion), tail(&suggestion));
+        suggestion.description = None;
+        assert_eq!(suggestion_tail(&suggestion), tail(&suggestion));
+
+        assert_eq!(help_tail(), 5);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn external_editor() {
+        // leaving the file untouched keeps the content, minus a final newline
+        assert_eq!(
+            edit_externally("true", "tpyo\n").unwrap().as_deref(),
+            Some("tpyo")
+        );
+        // arguments are passed before the file path
+        assert_eq!(
+            edit_externally("sed -i s/tpyo/typo/", "tpyo")
+                .unwrap()
+                .as_deref(),
+            Some("typo")
+        );
+        // a failing editor cancels the edit
+        assert_eq!(edit_externally("false", "tpyo").unwrap(), None);
+        assert!(edit_externally(" ", "tpyo").is_err());
+    }
+
+    #[test]
+    fn accept_then_undo() {
+        let path = PathBuf::from("src/lib.rs");
+        let bandaid = |replacement: &str, column: usize| {
+            BandAid::from((
+                replacement.to_owned(),
+                Span::try_from((1usize, column..column + 4)).unwrap(),
+            ))
+        };
+        let mut picked = UserPicked::default();
+
+        // nothing to undo, so stay with the current suggestion
+        assert_eq!(picked.process(&path, 0, Pick::Undo), Next::Index(0));
+
+        assert_eq!(
+            picked.process(&path, 0, Pick::Replacement(bandaid("typo", 4))),
+            Next::Index(1)
+        );
+        assert_eq!(picked.process(&path, 1, Pick::Skip), Next::Index(2));
+        assert_eq!(
+            picked.process(&path, 2, Pick::Replacement(bandaid("word", 12))),
+            Next::Index(3)
+        );
+        assert_eq!(picked.count(), 2);
+
+        // the last pick is reverted and its suggestion presented again
+        assert_eq!(picked.process(&path, 3, Pick::Undo), Next::Index(2));
+        assert_eq!(picked.count(), 1);
+        assert_eq!(picked.bandaids[&path], vec![bandaid("typo", 4)]);
+
+        assert_eq!(picked.process(&path, 2, Pick::Undo), Next::Index(0));
+        assert_eq!(picked.count(), 0);
+
+        // decisions of other files are not reverted
+        let _ = picked.process(&path, 0, Pick::Replacement(bandaid("typo", 4)));
+        let other = PathBuf::from("src/other.rs");
+        assert_eq!(picked.process(&other, 0, Pick::Undo), Next::Index(0));
+        assert_eq!(picked.count(), 1);
+    }
+
+    #[test]
+    fn bounded_items() {
+        let docs = crate::testcase::documentation();
+        let mut set = crate::testcase::suggestion_set(&docs);
+        let (path, suggestions) = crate::testcase::first(set.iter());
+        let mut suggestion = suggestions[0].clone();
+        suggestion.replacements = (0..40).map(|i| format!("typo{}", i)).collect();
+        let path = path.clone();
+        set.add(path.clone(), suggestion);
+
+        let config = Config::parse("max_replacements = 6").unwrap();
+        set.truncate_replacements(config.max_replacements);
+        let suggestions = set.suggestions(&path).collect::<Vec<_>>();
+        let state = State::from(suggestions[2]);
+        assert_eq!(state.suggestion.replacements.len(), 6);
+        assert_eq!(state.suggestion.replacements[5], "typo5");
+        // the replacements and the custom entry
+        assert_eq!(state.n_items, 6 + 1);
+    }
+
+    #[test]
+    fn custom_replacement_editing() {
+        let docs = crate::testcase::documentation();
+        let set = crate::testcase::suggestion_set(&docs);
+        let (_path, suggestions) = crate::testcase::first(set.iter());
+        let mut state = State::from(&suggestions[0]);
+
+        for c in "tpo".chars() {
+            state.insert(c);
+        }
+        assert_eq!(state.custom_replacement, "tpo");
+        assert_eq!(state.cursor, 3);
+
+        // insert in the middle
+        state.cursor_left();
+        state.cursor_left();
+        state.insert('y');
+        assert_eq!(state.custom_replacement, "typo");
+        assert_eq!(state.cursor, 2);
+        assert_eq!(state.split_at_cursor(), ("ty", "p", "o"));
+
+        // delete removes the character under the cursor, backspace the one before
+        state.delete();
+        assert_eq!(state.custom_replacement, "tyo");
+        state.backspace();
+        assert_eq!(state.custom_replacement, "to");
+        assert_eq!(state.cursor, 1);
+
+        state.cursor_home();
+        state.backspace();
+        assert_eq!(state.custom_replacement, "to");
+        assert_eq!(state.cursor, 0);
+        state.insert('ü');
+        state.cursor_end();
+        assert_eq!(state.cursor, 3);
+        state.cursor_right();
+        assert_eq!(state.cursor, 3);
+        state.delete();
+        assert_eq!(state.custom_replacement, "üto");
+        assert_eq!(state.split_at_cursor(), ("üto", " ", ""));
+        state.cursor_home();
+        state.delete();
+        assert_eq!(state.custom_replacement, "to");
+    }
+
+    #[test]
+    fn undo_count() {
+        let path = PathBuf::from("src/lib.rs");
+        let mut picked = UserPicked::default();
+        for idx in 0..3 {
+            let bandaid =
+                BandAid::from(("typo".to_owned(), Span::try_from((idx + 1, 4..8)).unwrap()));
+            let _ = picked.process(&path, idx, Pick::Replacement(bandaid));
+        }
+        assert_eq!(picked.count(), 3);
+
+        // every undo takes back exactly one pick, most recent first
+        for expected in (0..3).rev() {
+            assert_eq!(picked.process(&path, 3, Pick::Undo), Next::Index(expected));
+            assert_eq!(picked.count(), expected);
+        }
+        assert!(picked.bandaids.is_empty());
+
+        // nothing left to undo
+        assert_eq!(picked.process(&path, 0, Pick::Undo), Next::Index(0));
+        assert_eq!(picked.count(), 0);
+    }
+
+    #[test]
+    fn goto() {
+        assert_eq!(parse_goto("1", 3), Some(0));
+        assert_eq!(parse_goto(" 3 ", 3), Some(2));
+        assert_eq!(parse_goto("0", 3), None);
+        assert_eq!(parse_goto("4", 3), None);
+        assert_eq!(parse_goto("-1", 3), None);
+        assert_eq!(parse_goto("two", 3), None);
+
+        let path = PathBuf::from("src/lib.rs");
+        let mut picked = UserPicked::default();
+        assert_eq!(picked.process(&path, 0, Pick::Goto(2)), Next::Index(2));
+        assert_eq!(picked.process(&path, 2, Pick::Goto(1)), Next::Index(1));
+
+        // the progress reflects the position after the jump
+        let tally = Tally {
+            total: 5,
+            offset: 2,
+        };
+        assert_eq!(tally.progress(2, 3).to_string(), "(file 3/3, overall 5/5)");
+    }
+
+    #[test]
+    fn add_word() {
+        let path = PathBuf::from("src/lib.rs");
+        let mut picked = UserPicked::default();
+        assert_eq!(
+            picked.process(&path, 0, Pick::AddWord("tokio".to_owned())),
+            Next::Index(1)
+        );
+        assert_eq!(
+            picked.process(&path, 1, Pick::AddWord("tokio".to_owned())),
+            Next::Index(2)
+        );
+        assert_eq!(picked.words, vec!["tokio".to_owned()]);
+        // no replacement was picked
+        assert_eq!(picked.count(), 0);
+    }
+
+    #[test]
+    fn apply_remaining() {
+        let docs = crate::testcase::documentation();
+        let set = crate::testcase::suggestion_set(&docs);
+        let (path, suggestions) = crate::testcase::first(set.iter());
+        let mut suggestions = suggestions.clone();
+        let mut empty = suggestions[1].clone();
+        empty.replacements.clear();
+        suggestions.insert(1, empty);
+
+        let mut picked = UserPicked::default();
+        let state = State::from(&suggestions[0]);
+        assert_eq!(
+            picked.process(&path, 0, Pick::ApplyRemaining(state.to_bandaid())),
+            Next::ApplyRemaining(1)
+        );
+        picked.apply_remaining(&path, &suggestions, 1);
+
+        // the suggestion without replacements is skipped
+        assert_eq!(picked.count(), 2);
+        let replacements = picked.bandaids[path]
+            .iter()
+            .map(|bandaid| bandaid.replacement.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(replacements, vec!["typo", "and"]);
+    }
+
+    #[test]
+    fn previous_then_replace() {
+        let path = PathBuf::from("src/lib.rs");
+        let bandaid = |replacement: &str, column: usize| {
+            BandAid::from((
+                replacement.to_owned(),
+                Span::try_from((1usize, column..column + 4)).unwrap(),
+            ))
+        };
+        let mut picked = UserPicked::default();
+
+        // there is nothing before the first suggestion
+        assert_eq!(picked.process(&path, 0, Pick::Previous), Next::Index(0));
+
+        let _ = picked.process(&path, 0, Pick::Replacement(bandaid("typo", 4)));
+        let _ = picked.process(&path, 1, Pick::Replacement(bandaid("word", 12)));
+        assert_eq!(picked.process(&path, 2, Pick::Previous), Next::Index(1));
+        assert_eq!(picked.process(&path, 1, Pick::Previous), Next::Index(0));
+
+        // revisiting a suggestion replaces the earlier pick
+        assert_eq!(
+            picked.process(&path, 0, Pick::Replacement(bandaid("type", 4))),
+            Next::Index(1)
+        );
+        assert_eq!(picked.count(), 2);
+        assert_eq!(
+            picked.bandaids[&path],
+            vec![bandaid("word", 12), bandaid("type", 4)]
+        );
+        // skipping a revisited suggestion retains its pick
+        assert_eq!(picked.process(&path, 1, Pick::Skip), Next::Index(2));
+        assert_eq!(picked.count(), 2);
+
+        // undo reverts the most recent pick
+        assert_eq!(picked.process(&path, 2, Pick::Undo), Next::Index(0));
+        assert_eq!(picked.bandaids[&path], vec![bandaid("word", 12)]);
+    }
+}
@@ -1,207 +1,771 @@
 use super::*;
-use anyhow::{anyhow, Result};
-use log::{debug, trace};
-use std::convert::TryInto;
+use crate::span::byte_offset;
+use anyhow::{anyhow, Error, Result};
+use crossterm::tty::IsTty;
+use log::{debug, info, trace, warn};
+use std::convert::TryFrom;
 use std::fs::{self, OpenOptions};
 use std::io::{BufRead, Read, Write};
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
+mod audit;
 pub mod bandaid;
 pub mod interactive;
 
 pub(crate) use bandaid::*;
 use interactive::*;
 
+/// Iterator over the lines of a reader, where each line retains its
+/// original line ending, be it `\n`, `\r\n` or none for the last line.
+struct LinesWithEndings<R: BufRead> {
+    reader: R,
+}
+
+impl<R: BufRead> LinesWithEndings<R> {
+    fn new(reader: R) -> Self {
+        Self { reader }
+    }
+}
+
+impl<R: BufRead> Iterator for LinesWithEndings<R> {
+    type Item = std::io::Result<String>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut line = String::with_capacity(128);
+        match self.reader.read_line(&mut line) {
+            Ok(0) => None,
+            Ok(_n) => Some(Ok(line)),
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+/// Number the lines yielded by `LinesWithEndings`, starting with `1`.
+///
+/// Stops at the first line which can not be read, i.e. due to invalid utf8,
+/// and stores the error in `failure`. Skipping such a line instead would
+/// remove it from the corrected file.
+fn numbered_lines<'f>(
+    lines: impl Iterator<Item = std::io::Result<String>> + 'f,
+    path: &'f Path,
+    failure: &'f mut Option<Error>,
+) -> impl Iterator<Item = (usize, String)> + 'f {
+    lines
+        .enumerate()
+        .scan(failure, move |failure, (lineno, line)| match line {
+            Ok(content) => Some((lineno + 1, content)),
+            Err(e) => {
+                **failure = Some(Error::from(e).context(format!(
+                    "Failed to read line {} of {}",
+                    lineno + 1,
+                    path.display()
+                )));
+                None
+            }
+        })
+}
+
+/// The utf8 encoded byte order mark.
+const BOM: &[u8] = "\u{feff}".as_bytes();
+
+/// Consume a leading byte order mark of `reader`, if any.
+///
+/// The spans of the suggestions do not account for it, see
+/// `documentation::strip_bom`, so it must be skipped before the lines are
+/// corrected and is written back unchanged by the callers.
+fn skip_bom(reader: &mut impl BufRead) -> std::io::Result<bool> {
+    let bom = reader.fill_buf()?.starts_with(BOM);
+    if bom {
+        reader.consume(BOM.len());
+    }
+    Ok(bom)
+}
+
+/// Split a line as yielded by `LinesWithEndings` into its content and line ending.
+fn split_line_ending(line: &str) -> (&str, &str) {
+    let content = line.trim_end_matches(|c| c == '\n' || c == '\r');
+    line.split_at(content.len())
+}
+
+/// Adjust the line endings within a `replacement` to match the `ending`
+/// of the line it is written to.
+fn adjust_line_endings<'r>(replacement: &'r str, ending: &str) -> std::borrow::Cow<'r, str> {
+    if ending == "\r\n" && replacement.contains('\n') {
+        replacement
+            .replace("\r\n", "\n")
+            .replace('\n', "\r\n")
+            .into()
+    } else {
+        replacement.into()
+    }
+}
+
 /// correct all lines
 /// `bandaids` are the fixes to be applied to the lines
 ///
-/// Note that `Lines` as created by `(x as BufLines).lines()` does
-/// not preserve trailing newlines, so either the iterator
-/// needs to be modified to yield an extra (i.e. with `.chain("".to_owned())`)
-/// or a manual newlines has to be written to the `sink`.
+/// Each line of `source` must contain its original line ending, as yielded by
+/// `LinesWithEndings`, which is written to the `sink` as is. So files
+/// with `\r\n` or even mixed line endings are not altered besides the
+/// replacements, neither is a missing newline at the end of the file.
+/// Line breaks within a replacement are written with the line ending of
+/// the line the replacement starts in.
+///
+/// A bandaid may span multiple lines, in which case the replacement is
+/// written once and all covered lines are consumed. The line ending of the
+/// last covered line is retained.
+///
+/// The bandaids must be sorted and must not overlap, otherwise an error
+/// is returned, since the output would be garbage.
+///
+/// Returns the original text replaced by each of the `bandaids`.
 fn correct_lines<'s>(
     mut bandaids: impl Iterator<Item = BandAid>,
     source: impl Iterator<Item = (usize, String)>,
     mut sink: impl Write,
-) -> Result<()> {
+) -> Result<Vec<String>> {
+    let mut originals = Vec::with_capacity(bandaids.size_hint().0);
     let mut nxt: Option<BandAid> = bandaids.next();
-    for (line_number, content) in source {
+    // a bandaid which started in a previous line and covers the current one
+    let mut spanning: Option<BandAid> = None;
+    'lines: for (line_number, line) in source {
         trace!("Processing line {}", line_number);
+        let (content, ending) = split_line_ending(line.as_str());
+        // the columns of a span are in characters, while `content` is sliced
+        // in bytes, so `remainder` is always a char boundary of `content`
         let mut remainder_column = 0usize;
-        // let content: String = content.map_err(|e| {
-        //     anyhow!("Line {} contains invalid utf8 characters", line_number).context(e)
-        // })?;
-
-        if nxt.is_none() {
-            // no candidates remaining, just keep going
-            sink.write(content.as_bytes())?;
-            sink.write("\n".as_bytes())?;
-            continue;
-        }
+        let mut remainder = 0usize;
 
-        if let Some(ref bandaid) = nxt {
-            if !bandaid.span.covers_line(line_number) {
-                sink.write(content.as_bytes())?;
-                sink.write("\n".as_bytes())?;
-                continue;
+        if let Some(bandaid) = spanning.take() {
+            let original: &mut String = originals.last_mut().expect("Pushed when spanning");
+            if line_number < bandaid.span.end.line {
+                trace!("Line {} is covered by {:?}", line_number, bandaid);
+                original.push_str(line.as_str());
+                spanning = Some(bandaid);
+                continue 'lines;
             }
+            // the replacement was already written, skip the covered part
+            remainder_column = bandaid.span.end.column + 1;
+            remainder = clamped_byte_offset(content, remainder_column)?;
+            original.push_str(&content[..remainder]);
         }
 
         while let Some(bandaid) = nxt.take() {
+            if bandaid.span.start.line > line_number {
+                nxt = Some(bandaid);
+                break;
+            }
+            if bandaid.span.start.line < line_number || bandaid.span.start.column < remainder_column
+            {
+                return Err(anyhow!(
+                    "Bandaid {:?} overlaps with a previous one in line {}",
+                    bandaid,
+                    line_number
+                ));
+            }
             trace!("Applying next bandaid {:?}", bandaid);
             trace!("where line {} is: >{}<", line_number, content);
-            let range: Range = bandaid
-                .span
-                .try_into()
-                .expect("There should be no multiline strings as of today");
+            let start = clamped_byte_offset(content, bandaid.span.start.column)?;
             // write prelude for this line between start or previous replacement
-            if range.start > remainder_column {
-                sink.write(content[remainder_column..range.start].as_bytes())?;
+            if start > remainder {
+                sink.write(content[remainder..start].as_bytes())?;
             }
-            // write the replacement chunk
-            sink.write(bandaid.replacement.as_bytes())?;
-
-            remainder_column = range.end;
+            // write the replacement chunk, which is empty for a deletion
+            sink.write(adjust_line_endings(bandaid.replacement.as_str(), ending).as_bytes())?;
             nxt = bandaids.next();
-            let complete_current_line = if let Some(ref bandaid) = nxt {
-                // if `nxt` is also targeting the current line, don't complete the line
-                !bandaid.span.covers_line(line_number)
-            } else {
-                true
-            };
-            if complete_current_line {
-                // the last replacement may be the end of content
-                if remainder_column < content.len() {
-                    debug!(
-                        "line {} len is {}, and remainder column is {}",
-                        line_number,
-                        content.len(),
-                        remainder_column
-                    );
-                    // otherwise write all
-                    // not that this also covers writing a line without any suggestions
-                    sink.write(content[remainder_column..].as_bytes())?;
-                } else {
-                    debug!(
-                        "line {} len is {}, and remainder column is {}",
-                        line_number,
-                        content.len(),
-                        remainder_column
-                    );
-                }
-                sink.write("\n".as_bytes())?;
-                // break the inner loop
-                break;
-                // } else {
-                // next suggestion covers same line
+
+            if bandaid.insert {
+                // nothing is replaced, the content continues right at `start`
+                remainder_column = bandaid.span.start.column;
+                remainder = start;
+                originals.push(String::new());
+                continue;
+            }
+
+            if bandaid.span.end.line > line_number {
+                // the remainder of this line and the covered lines are replaced
+                originals.push(line[start..].to_owned());
+                spanning = Some(bandaid);
+                continue 'lines;
+            }
+            remainder_column = bandaid.span.end.column + 1;
+            remainder = clamped_byte_offset(content, remainder_column)?;
+            originals.push(content[start..remainder].to_owned());
+        }
+
+        // the last replacement may be the end of content
+        trace!(
+            "line {} len is {}, and remainder is {}",
+            line_number,
+            content.len(),
+            remainder
+        );
+        if remainder < content.len() {
+            // note that this also covers writing a line without any suggestions
+            sink.write(content[remainder..].as_bytes())?;
+        }
+        // retain the original line ending
+        sink.write(ending.as_bytes())?;
+    }
+    Ok(originals)
+}
+
+/// Same as `byte_offset`, a `column` past the end of `content` is its end.
+fn clamped_byte_offset(content: &str, column: usize) -> Result<usize> {
+    byte_offset(content, column.min(content.chars().count()))
+}
+
+/// Create a unified diff between the content of `path` and the content
+/// after applying all `bandaids`, the file itself is left untouched.
+///
+/// The file name in the header is relative to the current working directory
+/// if possible, so it can be applied with `git apply` or `patch -p1`.
+fn unified_diff(path: &Path, bandaids: impl IntoIterator<Item = BandAid>) -> Result<String> {
+    let ro = fs::File::open(path)
+        .map_err(|e| anyhow!("Failed to open {}", path.display()).context(e))?;
+    unified_diff_of(path, std::io::BufReader::new(ro), bandaids)
+}
+
+/// Same as `unified_diff`, for the content of `path` provided by `reader`.
+fn unified_diff_of(
+    path: &Path,
+    mut reader: impl BufRead,
+    bandaids: impl IntoIterator<Item = BandAid>,
+) -> Result<String> {
+    let bom = skip_bom(&mut reader)
+        .map_err(|e| anyhow!("Failed to read {}", path.display()).context(e))?;
+    let mut failure = None;
+    let lines =
+        numbered_lines(LinesWithEndings::new(reader), path, &mut failure).collect::<Vec<_>>();
+    if let Some(e) = failure {
+        return Err(e);
+    }
+    let mut original = String::new();
+    if bom {
+        original.push('\u{feff}');
+    }
+    original.extend(lines.iter().map(|(_lineno, content)| content.as_str()));
+
+    let mut corrected: Vec<u8> = Vec::with_capacity(original.len() + 256);
+    if bom {
+        corrected.extend_from_slice(BOM);
+    }
+    correct_lines(bandaids.into_iter(), lines.into_iter(), &mut corrected)?;
+    let corrected = String::from_utf8(corrected)
+        .map_err(|e| anyhow!("Corrections of {} are not valid utf8", path.display()).context(e))?;
+
+    let name = std::env::current_dir()
+        .ok()
+        .and_then(|cwd| path.strip_prefix(cwd).ok().map(Path::to_owned))
+        .unwrap_or_else(|| path.to_owned());
+    let name = name.display();
+    Ok(
+        similar::TextDiff::from_lines(original.as_str(), corrected.as_str())
+            .unified_diff()
+            .header(&format!("a/{}", name), &format!("b/{}", name))
+            .to_string(),
+    )
+}
+
+/// Append `words` to the hunspell dictionary `dic`, which is created if it
+/// does not exist yet.
+///
+/// The first line of a dictionary is the number of entries, which is updated.
+fn extend_dictionary(dic: &Path, words: &[String]) -> Result<()> {
+    let content = match fs::read_to_string(dic) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => {
+            return Err(anyhow!("Failed to read dictionary {}", dic.display()).context(e));
+        }
+    };
+    let mut lines = content.lines();
+    let count = lines
+        .next()
+        .map(|first| {
+            first.trim().parse::<usize>().map_err(|e| {
+                anyhow!(
+                    "First line of dictionary {} must be the number of words",
+                    dic.display()
+                )
+                .context(e)
+            })
+        })
+        .transpose()?
+        .unwrap_or(0);
+
+    let mut extended = format!("{}\n", count + words.len());
+    for line in lines.map(str::to_owned).chain(words.iter().cloned()) {
+        extended.push_str(&line);
+        extended.push('\n');
+    }
+    fs::write(dic, extended)
+        .map_err(|e| anyhow!("Failed to write dictionary {}", dic.display()).context(e))?;
+    debug!("Added {} word(s) to {}", words.len(), dic.display());
+    Ok(())
+}
+
+/// Sort bandaids by their position, as expected by `correct_lines`.
+fn sort_bandaids(bandaids: &mut [BandAid]) {
+    bandaids.sort_by_key(|bandaid| {
+        (
+            bandaid.span.start.line,
+            bandaid.span.start.column,
+            bandaid.span.end.line,
+            bandaid.span.end.column,
+        )
+    });
+}
+
+/// The first position following `bandaid`, which is not replaced by it.
+fn free_after(bandaid: &BandAid) -> (usize, usize) {
+    if bandaid.insert {
+        (bandaid.span.start.line, bandaid.span.start.column)
+    } else {
+        (bandaid.span.end.line, bandaid.span.end.column + 1)
+    }
+}
+
+/// Fail if any of the sorted `bandaids` of `path` overlaps with a previous one,
+/// before anything is written.
+fn ensure_disjoint(path: &Path, bandaids: &[BandAid]) -> Result<()> {
+    // the first position which is not yet replaced
+    let mut free = (0usize, 0usize);
+    for bandaid in bandaids {
+        let start = (bandaid.span.start.line, bandaid.span.start.column);
+        if start < free {
+            return Err(anyhow!(
+                "Replacement {:?} overlaps with a previous one in {}",
+                bandaid,
+                path.display()
+            ));
+        }
+        free = free_after(bandaid);
+    }
+    Ok(())
+}
+
+/// Drop all of the sorted `bandaids` of `path` which overlap with a previous one.
+fn retain_disjoint(path: &Path, bandaids: &mut Vec<BandAid>) {
+    let mut free = (0usize, 0usize);
+    bandaids.retain(|bandaid| {
+        let start = (bandaid.span.start.line, bandaid.span.start.column);
+        if start < free {
+            warn!(
+                "Skipping overlapping replacement {:?} in {}",
+                bandaid,
+                path.display()
+            );
+            return false;
+        }
+        free = free_after(bandaid);
+        true
+    });
+}
+
+/// Create a new temporary file next to `path`.
+///
+/// The name is unique per process and invocation, and the file is never
+/// shared with a concurrently running instance, since it must not exist yet.
+fn create_temporary(path: &Path) -> Result<(PathBuf, fs::File)> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let dir = path.parent().ok_or_else(|| {
+        anyhow!(
+            "Failed to obtain the parent directory of {}",
+            path.display()
+        )
+    })?;
+    let file_name = path
+        .file_name()
+        .map(|file_name| file_name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    loop {
+        let tmp = dir.join(format!(
+            ".{}.{}-{}.spellcheck.tmp",
+            file_name,
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::SeqCst)
+        ));
+        trace!("Attempting to create temporary file {}", tmp.display());
+        match OpenOptions::new().write(true).create_new(true).open(&tmp) {
+            Ok(file) => return Ok((tmp, file)),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => {
+                return Err(anyhow!("Failed to create temporary file {}", tmp.display()).context(e))
             }
         }
     }
+}
+
+/// Copy the content of `path` to `<path>.bak` before it is modified.
+///
+/// The copy is written to a temporary file first, which is then moved
+/// into place, so there is never a partially written backup.
+fn create_backup(path: &Path, backup: Backup) -> Result<()> {
+    let mut bak = path.as_os_str().to_owned();
+    bak.push(".bak");
+    let bak = PathBuf::from(bak);
+
+    if backup == Backup::Preserve && bak.exists() {
+        debug!("Backup {} already exists, not overwriting", bak.display());
+        return Ok(());
+    }
+
+    let (tmp, _file) = create_temporary(path)?;
+    let moved = fs::copy(path, &tmp).map_err(Error::from).and_then(|_| {
+        if backup == Backup::Overwrite {
+            fs::rename(&tmp, &bak).map_err(Error::from)
+        } else {
+            // a hard link fails if the backup was created in the meantime
+            fs::hard_link(&tmp, &bak)
+                .map_err(Error::from)
+                .and_then(|_| fs::remove_file(&tmp).map_err(Error::from))
+        }
+    });
+    if let Err(e) = moved {
+        let _ = fs::remove_file(&tmp);
+        return Err(anyhow!("Failed to create backup {}", bak.display()).context(e));
+    }
+    debug!("Created backup {} of {}", bak.display(), path.display());
     Ok(())
 }
 
 /// Mode in which `cargo-spellcheck` operates
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum Action {
-    /// Fix issues without interaction if there is sufficient information
+    /// Fix issues without interaction if there is sufficient information,
+    /// that is a suggestion has exactly one replacement.
     Fix,
-    /// Only show errors
-    Check,
+    /// Only show errors, in the given output format
+    Check(OutputFormat),
     /// Interactively choose from __candidates__ provided, similar to `git add -p` .
     Interactive,
+    /// Apply the first replacement of every suggestion, no questions asked,
+    /// even if there are several.
+    ReplaceWithFirst,
+    /// Same as `Fix`, but print a unified diff of the changes instead of applying them.
+    DryRun,
 }
 
 impl Action {
     /// assumes suggestions are sorted by line number and column number and must be non overlapping
+    ///
+    /// Returns the original text replaced by each of the `bandaids`.
     fn correction<'s>(
         &self,
         path: PathBuf,
         bandaids: impl IntoIterator<Item = BandAid>,
-    ) -> Result<()> {
+        backup: Backup,
+    ) -> Result<Vec<String>> {
         let path = path
             .as_path()
             .canonicalize()
             .map_err(|e| anyhow!("Failed to canonicalize {}", path.display()).context(e))?;
-        let path = dbg!(path.as_path());
+        let path = path.as_path();
+        debug!("Writing corrections to {}", path.display());
         trace!("Attempting to open {} as read", path.display());
         let ro = std::fs::OpenOptions::new()
             .read(true)
             .open(path)
             .map_err(|e| anyhow!("Failed to open {}", path.display()).context(e))?;
 
+        // retain the permissions of the original, i.e. the executable bit
+        let permissions = ro
+            .metadata()
+            .map_err(|e| anyhow!("Failed to read metadata of {}", path.display()).context(e))?
+            .permissions();
+
         let mut reader = std::io::BufReader::new(ro);
 
-        const TEMPORARY: &'static str = ".spellcheck.tmp";
-
-        let tmp = std::env::current_dir()
-            .expect("Must have cwd")
-            .join(TEMPORARY);
-        // let tmp = tmp.canonicalize().map_err(|e| { anyhow!("Failed to canonicalize {}", tmp.display() ).context(e) })?;
-        //trace!("Attempting to open {} as read", tmp.display());
-        let wr = OpenOptions::new()
-            .write(true)
-            .truncate(true)
-            .create(true)
-            .open(&tmp)
-            .map_err(|e| anyhow!("Failed to open {}", path.display()).context(e))?;
+        if backup != Backup::Off {
+            create_backup(path, backup)?;
+        }
 
-        let mut writer = std::io::BufWriter::with_capacity(1024, wr);
+        // the temporary file must reside on the same file system as the
+        // target, otherwise the final rename fails
+        let (tmp, wr) = create_temporary(path)?;
 
-        correct_lines(
-            bandaids.into_iter(),
-            (&mut reader)
-                .lines()
-                .filter_map(|line| line.ok())
-                .enumerate()
-                .map(|(lineno, content)| (lineno + 1, content)),
-            &mut writer,
-        )?;
+        let mut writer = std::io::BufWriter::with_capacity(1024, wr);
 
-        writer.flush()?;
+        let mut failure = None;
+        let mut originals = Vec::new();
+        let written = skip_bom(&mut reader)
+            .and_then(|bom| if bom { writer.write_all(BOM) } else { Ok(()) })
+            .map_err(Error::from)
+            .and_then(|_| {
+                correct_lines(
+                    bandaids.into_iter(),
+                    numbered_lines(LinesWithEndings::new(&mut reader), path, &mut failure),
+                    &mut writer,
+                )
+            })
+            .and_then(|replaced| {
+                originals = replaced;
+                failure.map_or(Ok(()), Err)
+            })
+            .and_then(|_| writer.flush().map_err(Error::from))
+            .and_then(|_| {
+                writer
+                    .get_ref()
+                    .set_permissions(permissions)
+                    .map_err(Error::from)
+            });
+        drop(writer);
 
-        fs::rename(tmp, path)?;
+        if let Err(e) = written.and_then(|_| fs::rename(&tmp, path).map_err(Error::from)) {
+            let _ = fs::remove_file(&tmp);
+            return Err(anyhow!("Failed to write corrections to {}", path.display()).context(e));
+        }
 
-        Ok(())
+        Ok(originals)
     }
 
     // consume self, doing the same thing again would cause garbage file content.
-    pub fn write_changes_to_disk(&self, userpicked: UserPicked, _config: &Config) -> Result<()> {
+    pub fn write_changes_to_disk(&self, userpicked: UserPicked, config: &Config) -> Result<()> {
         if userpicked.count() > 0 {
+            let mut bandaids_per_path = userpicked.bandaids;
+            // all files are validated before any of them is written
+            for (path, bandaids) in bandaids_per_path.iter_mut() {
+                // picked interactively, so not necessarily in order
+                sort_bandaids(bandaids);
+                if matches!(self, Self::Fix | Self::ReplaceWithFirst) {
+                    // nobody to ask, so the later one of an overlapping pair is dropped
+                    retain_disjoint(path, bandaids);
+                } else {
+                    ensure_disjoint(path, bandaids)?;
+                }
+            }
             debug!("Writing changes back to disk");
-            for (path, bandaids) in userpicked.bandaids.into_iter() {
-                self.correction(path, bandaids.into_iter())?;
+            for (path, bandaids) in bandaids_per_path.into_iter() {
+                if let Some(ref audit_log) = config.audit_log {
+                    let originals =
+                        self.correction(path.clone(), bandaids.clone(), config.backup)?;
+                    audit::append(audit_log, &path, &bandaids, &originals)?;
+                } else {
+                    self.correction(path, bandaids.into_iter(), config.backup)?;
+                }
             }
         } else {
             debug!("No band aids to apply");
         }
+        // the fixes are already written, so the picks are never lost over the words
+        if !userpicked.words.is_empty() {
+            let dic = config
+                .hunspell
+                .as_ref()
+                .and_then(|hunspell| hunspell.extra_dictonaries().first());
+            match dic {
+                Some(dic) => {
+                    if let Err(e) = extend_dictionary(dic, &userpicked.words) {
+                        warn!(
+                            "Not adding {} word(s) to the dictionary: {:?}",
+                            userpicked.words.len(),
+                            e
+                        );
+                    }
+                }
+                None => warn!(
+                    "No extra dictionary configured to add {} word(s) to",
+                    userpicked.words.len()
+                ),
+            }
+        }
         Ok(())
     }
 
-    /// Purpose was to check, check complete, so print the results.
-    fn check(&self, suggestions_per_path: SuggestionSet, _config: &Config) -> Result<()> {
-        let mut count = 0usize;
-        for (_path, suggestions) in suggestions_per_path {
-            count += suggestions.len();
+    /// Print a unified diff per file of what `write_changes_to_disk` would change.
+    fn print_changes(&self, userpicked: UserPicked) -> Result<()> {
+        let stdout = std::io::stdout();
+        let mut stdout = stdout.lock();
+        for (path, mut bandaids) in userpicked.bandaids.into_iter() {
+            sort_bandaids(&mut bandaids);
+            ensure_disjoint(&path, &bandaids)?;
+            stdout.write_all(unified_diff(&path, bandaids)?.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Pick the top ranked replacement of every suggestion, or if `unambiguous`
+    /// only of the suggestions with exactly one replacement.
+    ///
+    /// All other suggestions are reported on `stderr` and left untouched.
+    /// The bandaids per file are sorted and non overlapping, as required by `correction`.
+    fn pick_first<'s>(suggestions_per_path: SuggestionSet<'s>, unambiguous: bool) -> UserPicked {
+        let mut picked = UserPicked::default();
+        for (path, suggestions) in suggestions_per_path {
+            let mut bandaids = Vec::with_capacity(suggestions.len());
             for suggestion in suggestions {
-                eprintln!("{}", suggestion);
+                if unambiguous && suggestion.replacements.len() > 1 {
+                    eprintln!("{}", suggestion);
+                    continue;
+                }
+                match BandAid::try_from((&suggestion, 0)) {
+                    Ok(bandaid) => bandaids.push(bandaid),
+                    Err(_) => eprintln!("{}", suggestion),
+                }
             }
+            sort_bandaids(&mut bandaids);
+            retain_disjoint(&path, &mut bandaids);
+            if !bandaids.is_empty() {
+                picked.bandaids.insert(path, bandaids);
+            }
+        }
+        picked
+    }
+
+    /// Purpose was to check, check complete, so print the results to `sink`.
+    ///
+    /// The summary is printed to `stderr` as well, unless `quiet`.
+    fn check(
+        &self,
+        suggestions_per_path: SuggestionSet,
+        config: &Config,
+        quiet: bool,
+        sink: impl Write,
+    ) -> Result<()> {
+        let format = match *self {
+            Self::Check(format) => format,
+            _ => unreachable!("Only a check prints its results"),
+        };
+        match format {
+            // the human readable variant is meant for the terminal, not for being piped,
+            // if it is nonetheless all replacements are listed to remain grep-able
+            OutputFormat::Human => {
+                // the human readable results always end up on `stderr`
+                let width = if std::io::stderr().is_tty() {
+                    crossterm::terminal::size()
+                        .ok()
+                        .map(|(columns, _rows)| columns as usize)
+                } else {
+                    None
+                };
+                crate::report::human(&suggestions_per_path, width, config.tab_width, sink)?
+            }
+            _ => format.write(&suggestions_per_path, sink)?,
         }
-        if count > 0 {
+        if !quiet {
+            eprintln!("{}", crate::report::Summary::of(&suggestions_per_path));
+        }
+        Self::verdict(&suggestions_per_path, config)
+    }
+
+    /// Fails if any of the suggestions fails according to the `exit` configuration.
+    fn verdict(suggestions_per_path: &SuggestionSet, config: &Config) -> Result<()> {
+        let count = suggestions_per_path.count();
+        let failing = suggestions_per_path
+            .iter()
+            .flat_map(|(_path, suggestions)| suggestions.iter())
+            .filter(|suggestion| config.exit.fails(suggestion))
+            .count();
+        if failing > 0 {
             Err(anyhow::anyhow!(
                 "Found {} potential spelling mistakes",
-                count
+                failing
             ))
         } else {
+            if count > 0 {
+                info!(
+                    "Found {} potential spelling mistakes, none of which fail",
+                    count
+                );
+            }
             Ok(())
         }
     }
 
-    /// Run the requested action.
-    pub fn run(self, suggestions_per_path: SuggestionSet, config: &Config) -> Result<()> {
+    /// Run the requested action on `content` read from stdin, which is
+    /// checked as the document at `path`.
+    ///
+    /// The results of `check` are printed to `stderr` while the content is
+    /// passed through to `stdout` unchanged, `quiet` suppresses the summary.
+    /// Fixes are written to `stdout`.
+    pub fn run_stdin(
+        self,
+        path: &Path,
+        content: &str,
+        suggestions_per_path: SuggestionSet,
+        config: &Config,
+        quiet: bool,
+    ) -> Result<()> {
+        let stdout = std::io::stdout();
+        self.run_on(
+            path,
+            content,
+            suggestions_per_path,
+            config,
+            quiet,
+            stdout.lock(),
+        )
+    }
+
+    fn run_on(
+        self,
+        path: &Path,
+        content: &str,
+        suggestions_per_path: SuggestionSet,
+        config: &Config,
+        quiet: bool,
+        mut sink: impl Write,
+    ) -> Result<()> {
+        match self {
+            Self::Check(_) => {
+                sink.write_all(content.as_bytes())?;
+                sink.flush()?;
+                // the content occupies `stdout`, so all results go to `stderr`
+                self.check(suggestions_per_path, config, quiet, std::io::stderr())
+            }
+            Self::Fix | Self::ReplaceWithFirst => {
+                let picked = Self::pick_first(suggestions_per_path, self == Self::Fix);
+                let bandaids = picked.bandaids.into_iter().flat_map(|(_path, b)| b);
+                let mut reader = content.as_bytes();
+                if skip_bom(&mut reader)? {
+                    sink.write_all(BOM)?;
+                }
+                let mut failure = None;
+                correct_lines(
+                    bandaids,
+                    numbered_lines(LinesWithEndings::new(reader), path, &mut failure),
+                    &mut sink,
+                )?;
+                failure.map_or(Ok(()), Err)?;
+                sink.flush()?;
+                Ok(())
+            }
+            Self::DryRun => {
+                let picked = Self::pick_first(suggestions_per_path, true);
+                let bandaids = picked.bandaids.into_iter().flat_map(|(_path, b)| b);
+                let diff = unified_diff_of(path, content.as_bytes(), bandaids)?;
+                sink.write_all(diff.as_bytes())?;
+                sink.flush()?;
+                Ok(())
+            }
+            Self::Interactive => Err(anyhow!(
+                "Interactive fixing is not possible with the input read from stdin"
+            )),
+        }
+    }
+
+    /// Run the requested action, `quiet` suppresses the summary of a check.
+    pub fn run(
+        self,
+        suggestions_per_path: SuggestionSet,
+        config: &Config,
+        quiet: bool,
+    ) -> Result<()> {
         match self {
-            Self::Fix => unimplemented!("Unsupervised fixing is not implemented just yet"),
-            Self::Check => self.check(suggestions_per_path, config)?,
+            Self::Fix | Self::ReplaceWithFirst => {
+                let picked = Self::pick_first(suggestions_per_path, self == Self::Fix);
+                self.write_changes_to_disk(picked, config)?;
+            }
+            Self::DryRun => {
+                let picked = Self::pick_first(suggestions_per_path, true);
+                self.print_changes(picked)?;
+            }
+            Self::Check(OutputFormat::Human) => {
+                self.check(suggestions_per_path, config, quiet, std::io::stderr())?
+            }
+            Self::Check(_) => self.check(suggestions_per_path, config, quiet, std::io::stdout())?,
             Self::Interactive => {
                 let picked =
                     interactive::UserPicked::select_interactive(suggestions_per_path, config)?;
@@ -215,6 +779,8 @@ impl Action {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testcase::scratch_dir;
+    use std::convert::TryInto;
 
     const TEXT: &'static str = r#"
 I like unicorns every second Mondays.
@@ -238,20 +804,25 @@ I like banana icecream every third day.
             BandAid {
                 span: (2usize, 7..15).try_into().unwrap(),
                 replacement: "banana icecream".to_owned(),
+                detector: None,
+                ..Default::default()
             },
             BandAid {
                 span: (2usize, 22..28).try_into().unwrap(),
                 replacement: "third".to_owned(),
+                detector: None,
+                ..Default::default()
             },
             BandAid {
                 span: (2usize, 29..36).try_into().unwrap(),
                 replacement: "day".to_owned(),
+                detector: None,
+                ..Default::default()
             },
         ];
 
-        let lines = TEXT
-            .lines()
-            .map(|line| line.to_owned())
+        let lines = LinesWithEndings::new(TEXT.as_bytes())
+            .filter_map(|line| line.ok())
             .enumerate()
             .map(|(lineno, content)| (lineno + 1, content));
 
@@ -259,4 +830,888 @@ I like banana icecream every third day.
 
         assert_eq!(String::from_utf8_lossy(sink.as_slice()), CORRECTED);
     }
+
+    #[test]
+    fn replace_multiline() {
+        const MULTILINE: &str = "First line\nI like unicorns every\nsecond Mondays, yay.\nLast\n";
+        const MULTILINE_CORRECTED: &str = "First line\nI like cake on Mondays, yay.\nLast\n";
+
+        let mut sink: Vec<u8> = Vec::with_capacity(1024);
+        let bandaids = vec![BandAid {
+            span: Span {
+                start: LineColumn { line: 2, column: 7 },
+                end: LineColumn { line: 3, column: 5 },
+            },
+            replacement: "cake on".to_owned(),
+            detector: None,
+            ..Default::default()
+        }];
+
+        let lines = LinesWithEndings::new(MULTILINE.as_bytes())
+            .filter_map(|line| line.ok())
+            .enumerate()
+            .map(|(lineno, content)| (lineno + 1, content));
+
+        correct_lines(bandaids.into_iter(), lines, &mut sink).expect("should be able to");
+
+        assert_eq!(
+            String::from_utf8_lossy(sink.as_slice()),
+            MULTILINE_CORRECTED
+        );
+    }
+
+    #[test]
+    fn crlf_line_endings() {
+        const CRLF: &str = "First line\r\nI like unicorns every second Mondays.\r\nLast\r\n";
+        const CRLF_CORRECTED: &str =
+            "First line\r\nI like bananas\r\nevery second Mondays.\r\nLast\r\n";
+
+        let mut sink: Vec<u8> = Vec::with_capacity(1024);
+        let bandaids = vec![BandAid {
+            span: (2usize, 7..16).try_into().unwrap(),
+            replacement: "bananas\n".to_owned(),
+            detector: None,
+            ..Default::default()
+        }];
+
+        let lines = LinesWithEndings::new(CRLF.as_bytes())
+            .filter_map(|line| line.ok())
+            .enumerate()
+            .map(|(lineno, content)| (lineno + 1, content));
+
+        correct_lines(bandaids.into_iter(), lines, &mut sink).expect("should be able to");
+
+        assert_eq!(String::from_utf8_lossy(sink.as_slice()), CRLF_CORRECTED);
+    }
+
+    /// Apply `bandaids` to `text` by means of `correct_lines`.
+    fn correct_text(text: &str, bandaids: Vec<BandAid>) -> String {
+        let mut sink: Vec<u8> = Vec::with_capacity(1024);
+        let lines = LinesWithEndings::new(text.as_bytes())
+            .filter_map(|line| line.ok())
+            .enumerate()
+            .map(|(lineno, content)| (lineno + 1, content));
+        correct_lines(bandaids.into_iter(), lines, &mut sink).expect("should be able to");
+        String::from_utf8_lossy(sink.as_slice()).to_string()
+    }
+
+    #[test]
+    fn overlapping_bandaids() {
+        let bandaid = |line: usize, range: Range, replacement: &str| BandAid {
+            span: (line, range).try_into().unwrap(),
+            replacement: replacement.to_owned(),
+            detector: None,
+            ..Default::default()
+        };
+        let correct = |bandaids: Vec<BandAid>| {
+            let mut sink: Vec<u8> = Vec::with_capacity(1024);
+            let lines = LinesWithEndings::new(TEXT.as_bytes())
+                .filter_map(|line| line.ok())
+                .enumerate()
+                .map(|(lineno, content)| (lineno + 1, content));
+            correct_lines(bandaids.into_iter(), lines, &mut sink)
+                .map(|_| String::from_utf8_lossy(sink.as_slice()).to_string())
+        };
+
+        // the second one starts within `unicorns`
+        let overlapping = vec![bandaid(2, 7..15, "bananas"), bandaid(2, 12..16, "pears")];
+        assert!(correct(overlapping).is_err());
+
+        let adjacent = vec![bandaid(2, 7..11, "bana"), bandaid(2, 11..15, "nas")];
+        assert_eq!(
+            correct(adjacent).expect("Adjacent bandaids are fine"),
+            TEXT.replacen("unicorns", "bananas", 1)
+        );
+    }
+
+    #[test]
+    fn final_newline() {
+        let bandaid = |line: usize| BandAid {
+            span: (line, 7..15).try_into().unwrap(),
+            replacement: "bananas".to_owned(),
+            detector: None,
+            ..Default::default()
+        };
+
+        assert_eq!(correct_text("", vec![]), "");
+        assert_eq!(correct_text("\n", vec![]), "\n");
+        assert_eq!(
+            correct_text("I like unicorns.", vec![bandaid(1)]),
+            "I like bananas."
+        );
+        assert_eq!(
+            correct_text("I like unicorns.\n", vec![bandaid(1)]),
+            "I like bananas.\n"
+        );
+        assert_eq!(
+            correct_text("I like unicorns.\nNo newline", vec![bandaid(1)]),
+            "I like bananas.\nNo newline"
+        );
+        assert_eq!(
+            correct_text("First\r\nI like unicorns", vec![bandaid(2)]),
+            "First\r\nI like bananas"
+        );
+    }
+
+    #[test]
+    fn multibyte_columns() {
+        // columns are in characters, `é` and `è` are two bytes each
+        let bandaid = |line: usize, range: Range, replacement: &str| BandAid {
+            span: (line, range).try_into().unwrap(),
+            replacement: replacement.to_owned(),
+            detector: None,
+            ..Default::default()
+        };
+        const TEXT: &str = "Café crème with unicrons.\nNaïve façade tpyo\n";
+        assert_eq!(
+            correct_text(
+                TEXT,
+                vec![
+                    bandaid(1, 16..24, "unicorns"),
+                    bandaid(2, 6..12, "facade"),
+                    bandaid(2, 13..17, "typo"),
+                ]
+            ),
+            "Café crème with unicorns.\nNaïve facade typo\n"
+        );
+        // right after a multibyte character and spanning lines
+        assert_eq!(
+            correct_text(
+                "Café\ncrème",
+                vec![BandAid {
+                    span: Span {
+                        start: LineColumn { line: 1, column: 3 },
+                        end: LineColumn { line: 2, column: 2 },
+                    },
+                    replacement: "e cre".to_owned(),
+                    detector: None,
+                    ..Default::default()
+                }]
+            ),
+            "Cafe creme"
+        );
+        // columns past the end of the line do not panic
+        assert_eq!(correct_text("Café", vec![bandaid(1, 4..9, "!")]), "Café!");
+    }
+
+    #[test]
+    fn multiline_replacement() {
+        let bandaid = |line: usize, range: Range, replacement: &str| BandAid {
+            span: (line, range).try_into().unwrap(),
+            replacement: replacement.to_owned(),
+            detector: None,
+            ..Default::default()
+        };
+        let reflow = bandaid(1, 10..11, "\n");
+        // subsequent bandaids still refer to the original lines and columns
+        assert_eq!(
+            correct_text(
+                "A sentence that is too long.\nAnother tpyo.\n",
+                vec![
+                    reflow,
+                    bandaid(1, 23..27, "wide"),
+                    bandaid(2, 8..12, "typo")
+                ]
+            ),
+            "A sentence\nthat is too wide.\nAnother typo.\n"
+        );
+
+        // and the other way around, the end column is inclusive
+        let joined = BandAid {
+            span: Span {
+                start: LineColumn {
+                    line: 1,
+                    column: 10,
+                },
+                end: LineColumn { line: 2, column: 0 },
+            },
+            replacement: " ".to_owned(),
+            detector: None,
+            ..Default::default()
+        };
+        assert_eq!(
+            correct_text("A sentence\n that is short.\n", vec![joined]),
+            "A sentence that is short.\n"
+        );
+    }
+
+    #[test]
+    fn insertions_and_deletions() {
+        let deletion =
+            |line: usize, range: Range| BandAid::deletion((line, range).try_into().unwrap());
+        // start, middle and end of the line
+        assert_eq!(
+            correct_text(
+                "So so this is is it it\n",
+                vec![deletion(1, 0..3), deletion(1, 11..14), deletion(1, 19..22)]
+            ),
+            "so this is it\n"
+        );
+        assert_eq!(
+            correct_text(
+                "However we know, or not\n",
+                vec![
+                    BandAid::insertion("Well, ", 1, 0),
+                    BandAid::insertion(",", 1, 7),
+                    BandAid::insertion("?", 1, 23),
+                ]
+            ),
+            "Well, However, we know, or not?\n"
+        );
+        // an insertion right before a replacement or a deletion
+        assert_eq!(
+            correct_text(
+                "I like unicorns.",
+                vec![
+                    BandAid::insertion("really ", 1, 2),
+                    BandAid {
+                        span: (1usize, 2..6).try_into().unwrap(),
+                        replacement: "love".to_owned(),
+                        detector: None,
+                        ..Default::default()
+                    },
+                    BandAid::insertion("pink ", 1, 7),
+                    deletion(1, 15..16),
+                ]
+            ),
+            "I really love pink unicorns"
+        );
+        // past the end of the line, before the line ending
+        assert_eq!(
+            correct_text("Café\r\n", vec![BandAid::insertion("!", 1, 9)]),
+            "Café!\r\n"
+        );
+    }
+
+    #[test]
+    fn mixed_line_endings() {
+        const MIXED: &str = "First line\r\nI like unicorns.\nThird line\r\n\r\nLast\n";
+        const MIXED_CORRECTED: &str = "First line\r\nI like bananas.\nThird line\r\n\r\nLast\n";
+
+        let mut sink: Vec<u8> = Vec::with_capacity(1024);
+        let bandaids = vec![BandAid {
+            span: (2usize, 7..15).try_into().unwrap(),
+            replacement: "bananas".to_owned(),
+            detector: None,
+            ..Default::default()
+        }];
+
+        let lines = LinesWithEndings::new(MIXED.as_bytes())
+            .filter_map(|line| line.ok())
+            .enumerate()
+            .map(|(lineno, content)| (lineno + 1, content));
+
+        correct_lines(bandaids.into_iter(), lines, &mut sink).expect("should be able to");
+
+        assert_eq!(String::from_utf8_lossy(sink.as_slice()), MIXED_CORRECTED);
+    }
+
+    #[test]
+    fn correction_in_place() {
+        let dir = scratch_dir("correction");
+        let path = dir.join("lib.rs");
+        fs::write(&path, TEXT).unwrap();
+
+        let bandaids = vec![BandAid {
+            span: (2usize, 7..15).try_into().unwrap(),
+            replacement: "bananas".to_owned(),
+            detector: None,
+            ..Default::default()
+        }];
+        Action::Fix
+            .correction(path.clone(), bandaids, Backup::Off)
+            .expect("Must apply");
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "\nI like bananas every second Mondays.\n\n"
+        );
+        // no temporary files are left behind
+        let remaining = fs::read_dir(&dir).unwrap().count();
+        assert_eq!(remaining, 1);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn byte_order_mark() {
+        const BOMMED: &str = "\u{feff}/// A tpyo here.\n";
+        let dir = scratch_dir("bom");
+        let path = dir.join("lib.rs");
+        fs::write(&path, BOMMED).unwrap();
+
+        // the columns of the first line do not account for the mark
+        let docs = crate::Documentation::load_from_str(&path, BOMMED, &Config::default())
+            .expect("Must load");
+        let (_origin, literal_sets) = docs.iter().next().expect("Must contain the comment");
+        let plain = literal_sets[0].erase_markdown();
+        let start = plain.as_str().find("tpyo").unwrap();
+        let spans = plain.linear_range_to_spans(start..start + 4);
+        let (literal, span) = spans.first().expect("Must map to a literal");
+        assert_eq!(span.start.line, 1);
+        assert_eq!(literal.variant.file_column(span.start.column), 6);
+
+        let bandaid = || BandAid {
+            span: (1usize, 6..10).try_into().unwrap(),
+            replacement: "typo".to_owned(),
+            detector: None,
+            ..Default::default()
+        };
+        let diff = unified_diff(&path, vec![bandaid()]).expect("Must diff");
+        assert!(diff.contains("-\u{feff}/// A tpyo here.\n"), "{}", diff);
+        assert!(diff.contains("+\u{feff}/// A typo here.\n"), "{}", diff);
+
+        Action::Fix
+            .correction(path.clone(), vec![bandaid()], Backup::Off)
+            .expect("Must apply");
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "\u{feff}/// A typo here.\n"
+        );
+
+        let mut sink = Vec::new();
+        Action::Fix
+            .run_on(
+                &path,
+                BOMMED,
+                SuggestionSet::new(),
+                &Config::default(),
+                true,
+                &mut sink,
+            )
+            .expect("Must pass through");
+        assert_eq!(String::from_utf8(sink).unwrap(), BOMMED);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn correction_retains_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = scratch_dir("permissions");
+        let path = dir.join("script.rs");
+        fs::write(&path, TEXT).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o754)).unwrap();
+
+        let bandaids = vec![BandAid {
+            span: (2usize, 7..15).try_into().unwrap(),
+            replacement: "bananas".to_owned(),
+            detector: None,
+            ..Default::default()
+        }];
+        Action::Fix
+            .correction(path.clone(), bandaids, Backup::Off)
+            .expect("Must apply");
+
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o754);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn correction_backup() {
+        let dir = scratch_dir("backup");
+        let path = dir.join("lib.rs");
+        let bak = dir.join("lib.rs.bak");
+        fs::write(&path, TEXT).unwrap();
+
+        let bandaids = |replacement: &str| {
+            vec![BandAid {
+                span: (2usize, 7..15).try_into().unwrap(),
+                replacement: replacement.to_owned(),
+                detector: None,
+                ..Default::default()
+            }]
+        };
+        Action::Fix
+            .correction(path.clone(), bandaids("bananas"), Backup::Preserve)
+            .expect("Must apply");
+        assert_eq!(fs::read_to_string(&bak).unwrap(), TEXT);
+
+        // an existing backup is retained
+        Action::Fix
+            .correction(path.clone(), bandaids("cherries"), Backup::Preserve)
+            .expect("Must apply");
+        assert_eq!(fs::read_to_string(&bak).unwrap(), TEXT);
+
+        // unless explicitly allowed
+        let previous = fs::read_to_string(&path).unwrap();
+        Action::Fix
+            .correction(path.clone(), bandaids("lemons"), Backup::Overwrite)
+            .expect("Must apply");
+        assert_eq!(fs::read_to_string(&bak).unwrap(), previous);
+
+        // no temporary files are left behind
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 2);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn correction_invalid_utf8() {
+        let dir = scratch_dir("invalid-utf8");
+        let path = dir.join("lib.rs");
+        let mut content = TEXT.as_bytes().to_vec();
+        content.extend_from_slice(b"Broken \xF0\x28\x8C\x28 line\nLast line\n");
+        fs::write(&path, &content).unwrap();
+
+        let bandaids = || {
+            vec![BandAid {
+                span: (2usize, 7..15).try_into().unwrap(),
+                replacement: "bananas".to_owned(),
+                detector: None,
+                ..Default::default()
+            }]
+        };
+        let err = Action::Fix
+            .correction(path.clone(), bandaids(), Backup::Off)
+            .expect_err("Must not drop the invalid line");
+        assert!(format!("{:?}", err).contains("line 4 of"));
+
+        let err = unified_diff(&path, bandaids()).expect_err("Must not drop the invalid line");
+        assert!(format!("{:?}", err).contains("line 4 of"));
+
+        // the file is untouched and no temporary files are left behind
+        assert_eq!(fs::read(&path).unwrap(), content);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn unsorted_picks() {
+        let dir = scratch_dir("unsorted");
+        let path = dir.join("lib.rs");
+        fs::write(&path, TEXT).unwrap();
+
+        let bandaid = |line: usize, range: std::ops::Range<usize>, replacement: &str| BandAid {
+            span: (line, range).try_into().unwrap(),
+            replacement: replacement.to_owned(),
+            detector: None,
+            ..Default::default()
+        };
+        let mut picked = UserPicked::default();
+        picked.bandaids.insert(
+            path.clone(),
+            vec![
+                bandaid(2, 29..36, "day"),
+                bandaid(2, 7..15, "banana icecream"),
+                bandaid(2, 22..28, "third"),
+            ],
+        );
+        Action::Fix
+            .write_changes_to_disk(picked, &Config::default())
+            .expect("Must apply");
+        assert_eq!(fs::read_to_string(&path).unwrap(), CORRECTED);
+
+        // overlapping picks of the user are rejected before anything is written,
+        // not even other files
+        fs::write(&path, TEXT).unwrap();
+        let other = dir.join("other.rs");
+        fs::write(&other, TEXT).unwrap();
+        let overlapping = || {
+            let mut picked = UserPicked::default();
+            picked
+                .bandaids
+                .insert(other.clone(), vec![bandaid(2, 7..15, "bananas")]);
+            picked.bandaids.insert(
+                path.clone(),
+                vec![bandaid(2, 10..20, "x"), bandaid(2, 7..15, "bananas")],
+            );
+            picked
+        };
+        assert!(Action::Interactive
+            .write_changes_to_disk(overlapping(), &Config::default())
+            .is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), TEXT);
+        assert_eq!(fs::read_to_string(&other).unwrap(), TEXT);
+
+        // while automatic picks skip the later overlapping one
+        Action::Fix
+            .write_changes_to_disk(overlapping(), &Config::default())
+            .expect("Must apply");
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            TEXT.replace("unicorns", "bananas")
+        );
+        assert_eq!(
+            fs::read_to_string(&other).unwrap(),
+            fs::read_to_string(&path).unwrap()
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn words_without_dictionary() {
+        let dir = scratch_dir("words");
+        let path = dir.join("lib.rs");
+        fs::write(&path, TEXT).unwrap();
+
+        let mut picked = UserPicked::default();
+        picked.bandaids.insert(
+            path.clone(),
+            vec![BandAid {
+                span: (2usize, 7..15).try_into().unwrap(),
+                replacement: "bananas".to_owned(),
+                detector: None,
+                ..Default::default()
+            }],
+        );
+        picked.words.push("tokio".to_owned());
+
+        let mut config = Config::default();
+        config.hunspell = None;
+        Action::Interactive
+            .write_changes_to_disk(picked, &config)
+            .expect("Missing dictionary is no failure");
+        assert!(fs::read_to_string(&path)
+            .unwrap()
+            .contains("I like bananas every second Mondays."));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn audit_log() {
+        let dir = scratch_dir("audit");
+        let path = dir.join("lib.rs");
+        let audit_log = dir.join("audit.jsonl");
+        fs::write(&path, TEXT).unwrap();
+
+        let mut picked = UserPicked::default();
+        picked.bandaids.insert(
+            path.clone(),
+            vec![
+                BandAid {
+                    span: (2usize, 22..28).try_into().unwrap(),
+                    replacement: "third".to_owned(),
+                    detector: None,
+                    ..Default::default()
+                },
+                BandAid {
+                    span: (2usize, 7..15).try_into().unwrap(),
+                    replacement: "bananas".to_owned(),
+                    detector: Some(Detector::Hunspell),
+                    ..Default::default()
+                },
+            ],
+        );
+        let config = Config {
+            audit_log: Some(audit_log.clone()),
+            ..Config::default()
+        };
+        Action::Fix
+            .write_changes_to_disk(picked, &config)
+            .expect("Must apply");
+
+        let records = fs::read_to_string(&audit_log)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0]["path"], path.display().to_string());
+        assert_eq!(records[0]["start"]["line"], 2);
+        assert_eq!(records[0]["start"]["column"], 7);
+        assert_eq!(records[0]["end"]["column"], 14);
+        assert_eq!(records[0]["original"], "unicorns");
+        assert_eq!(records[0]["replacement"], "bananas");
+        assert_eq!(records[0]["detector"], "Hunspell");
+        assert_eq!(records[1]["original"], "second");
+        assert_eq!(records[1]["replacement"], "third");
+        assert_eq!(records[1]["detector"], serde_json::Value::Null);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn extend_dictionary_count() {
+        let dir = scratch_dir("dictionary");
+        let dic = dir.join("project.dic");
+        let words = |words: &[&str]| words.iter().map(|w| (*w).to_owned()).collect::<Vec<_>>();
+
+        extend_dictionary(&dic, &words(&["tokio"])).expect("Must create");
+        assert_eq!(fs::read_to_string(&dic).unwrap(), "1\ntokio\n");
+
+        extend_dictionary(&dic, &words(&["serde", "toml"])).expect("Must extend");
+        assert_eq!(fs::read_to_string(&dic).unwrap(), "3\ntokio\nserde\ntoml\n");
+
+        fs::write(&dic, "no count\n").unwrap();
+        assert!(extend_dictionary(&dic, &words(&["tokio"])).is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn temporary_unique() {
+        let dir = scratch_dir("temporary");
+        let path = dir.join("lib.rs");
+        let (first, _) = create_temporary(&path).unwrap();
+        let (second, _) = create_temporary(&path).unwrap();
+        assert_ne!(first, second);
+        assert_eq!(first.parent(), Some(dir.as_path()));
+        assert_eq!(second.parent(), Some(dir.as_path()));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn pick_first_sorted_non_overlapping() {
+        let docs = Documentation::load_from_str(
+            "src/lib.rs",
+            "/// A tpyo and anothr one.\nstruct X;",
+            &Config::default(),
+        )
+        .expect("Must parse");
+        let (path, literal_sets) = crate::testcase::first(docs.iter());
+        let literal = literal_sets[0].literals()[0];
+        let suggestion = |start: usize, end: usize, replacements: &[&str]| Suggestion {
+            detector: Detector::Hunspell,
+            severity: Detector::Hunspell.severity(),
+            path: path.clone(),
+            literal: literal.into(),
+            span: Span {
+                start: LineColumn {
+                    line: 1,
+                    column: start,
+                },
+                end: LineColumn {
+                    line: 1,
+                    column: end,
+                },
+            },
+            replacements: replacements.iter().map(|r| (*r).to_owned()).collect(),
+            description: None,
+        };
+
+        let mut set = SuggestionSet::new();
+        set.extend(
+            path.clone(),
+            vec![
+                suggestion(13, 18, &["another", "anther"]),
+                suggestion(4, 7, &["typo"]),
+                // overlaps with the previous one
+                suggestion(4, 11, &["typo and"]),
+                // nothing to pick from
+                suggestion(20, 22, &[]),
+            ],
+        );
+
+        let picked = Action::pick_first(set.clone(), false);
+        assert_eq!(picked.count(), 2);
+        let bandaids = &picked.bandaids[path];
+        assert_eq!(bandaids[0].replacement, "typo");
+        assert_eq!(bandaids[0].span.start.column, 6);
+        assert_eq!(bandaids[1].replacement, "another");
+        assert_eq!(bandaids[1].span.start.column, 15);
+
+        // `fix` leaves the ambiguous one alone
+        let picked = Action::pick_first(set, true);
+        assert_eq!(picked.count(), 1);
+        assert_eq!(picked.bandaids[path][0].replacement, "typo");
+    }
+
+    #[test]
+    fn dry_run_diff() {
+        let dir = scratch_dir("dry-run");
+        let path = dir.join("lib.rs");
+        fs::write(&path, TEXT).unwrap();
+
+        let bandaids = vec![BandAid {
+            span: (2usize, 7..15).try_into().unwrap(),
+            replacement: "bananas".to_owned(),
+            detector: None,
+            ..Default::default()
+        }];
+        let diff = unified_diff(&path, bandaids).expect("Must diff");
+
+        let name = path.display();
+        assert_eq!(
+            diff,
+            format!(
+                "--- a/{name}\n+++ b/{name}\n@@ -1,3 +1,3 @@\n \n-I like unicorns every second Mondays.\n+I like bananas every second Mondays.\n \n",
+                name = name
+            )
+        );
+        // the file itself is untouched
+        assert_eq!(fs::read_to_string(&path).unwrap(), TEXT);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn replace_with_first() {
+        const SOURCE: &str = "/// A tpyo and anothr one.\nstruct X;\n";
+
+        let dir = scratch_dir("replace-with-first");
+        let path = dir.join("lib.rs");
+        fs::write(&path, SOURCE).unwrap();
+
+        let docs =
+            Documentation::load_from_str(&path, SOURCE, &Config::default()).expect("Must parse");
+        let (path, literal_sets) = crate::testcase::first(docs.iter());
+        let literal = literal_sets[0].literals()[0];
+        let suggestion = |start: usize, end: usize, replacements: &[&str]| Suggestion {
+            detector: Detector::Hunspell,
+            severity: Detector::Hunspell.severity(),
+            path: path.clone(),
+            literal: literal.into(),
+            span: Span {
+                start: LineColumn {
+                    line: 1,
+                    column: start,
+                },
+                end: LineColumn {
+                    line: 1,
+                    column: end,
+                },
+            },
+            replacements: replacements.iter().map(|r| (*r).to_owned()).collect(),
+            description: None,
+        };
+
+        let mut set = SuggestionSet::new();
+        set.extend(
+            path.clone(),
+            vec![
+                suggestion(4, 7, &["typo", "type"]),
+                suggestion(13, 18, &["another", "anther", "other"]),
+                // nothing to replace with
+                suggestion(20, 22, &[]),
+            ],
+        );
+
+        Action::ReplaceWithFirst
+            .run(set, &Config::default(), true)
+            .expect("Must apply");
+        assert_eq!(
+            fs::read_to_string(path).unwrap(),
+            "/// A typo and another one.\nstruct X;\n"
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn check_report_only() {
+        let docs = crate::testcase::documentation();
+        let mut config = Config::default();
+        config.exit.report_only = true;
+        Action::Check(OutputFormat::Json)
+            .check(
+                crate::testcase::suggestion_set(&docs),
+                &config,
+                true,
+                Vec::new(),
+            )
+            .expect("Report only mode never fails");
+    }
+
+    #[test]
+    fn check_strict() {
+        let docs = crate::testcase::documentation();
+        let check = |config: &Config| {
+            Action::Check(OutputFormat::Json).check(
+                crate::testcase::suggestion_set(&docs),
+                config,
+                true,
+                Vec::new(),
+            )
+        };
+        assert!(check(&Config::default()).is_err());
+
+        // only the grammar warning remains, which is not severe enough
+        let mut config = Config::default();
+        config.exit.threshold = Severity::Error;
+        config.exit.detectors = vec![Detector::LanguageTool];
+        assert!(check(&config).is_ok());
+
+        config.exit.threshold = Severity::Info;
+        let e = check(&config).expect_err("Strict mode fails on any finding");
+        assert_eq!(e.to_string(), "Found 1 potential spelling mistakes");
+    }
+
+    #[test]
+    fn stdin() {
+        const SOURCE: &str = "/// A tpyo and anothr one.\nstruct X;\n";
+
+        let path = Path::new("<stdin>");
+        let docs = crate::traverse::load_str(path, SOURCE, DocumentKind::Rust, &Config::default())
+            .expect("Must parse");
+        let (_path, literal_sets) = crate::testcase::first(docs.iter());
+        let literal = literal_sets[0].literals()[0];
+        let suggestions = || {
+            let suggestion = |start: usize, end: usize, replacement: &str| Suggestion {
+                detector: Detector::Hunspell,
+                severity: Detector::Hunspell.severity(),
+                path: path.to_owned(),
+                literal: literal.into(),
+                span: Span {
+                    start: LineColumn {
+                        line: 1,
+                        column: start,
+                    },
+                    end: LineColumn {
+                        line: 1,
+                        column: end,
+                    },
+                },
+                replacements: vec![replacement.to_owned()],
+                description: None,
+            };
+            let mut set = SuggestionSet::new();
+            set.extend(
+                path.to_owned(),
+                vec![suggestion(4, 7, "typo"), suggestion(13, 18, "another")],
+            );
+            set
+        };
+
+        // passed through unchanged, the results go to stderr
+        let mut sink = Vec::<u8>::new();
+        assert!(Action::Check(OutputFormat::Human)
+            .run_on(
+                path,
+                SOURCE,
+                suggestions(),
+                &Config::default(),
+                true,
+                &mut sink
+            )
+            .is_err());
+        assert_eq!(String::from_utf8(sink).unwrap(), SOURCE);
+
+        let mut sink = Vec::<u8>::new();
+        Action::ReplaceWithFirst
+            .run_on(
+                path,
+                SOURCE,
+                suggestions(),
+                &Config::default(),
+                true,
+                &mut sink,
+            )
+            .expect("Must apply");
+        assert_eq!(
+            String::from_utf8(sink).unwrap(),
+            "/// A typo and another one.\nstruct X;\n"
+        );
+
+        let mut sink = Vec::<u8>::new();
+        Action::DryRun
+            .run_on(
+                path,
+                SOURCE,
+                suggestions(),
+                &Config::default(),
+                true,
+                &mut sink,
+            )
+            .expect("Must diff");
+        let diff = String::from_utf8(sink).unwrap();
+        assert!(diff.contains("-/// A tpyo and anothr one.\n+/// A typo and another one.\n"));
+
+        assert!(Action::Interactive
+            .run_on(
+                path,
+                SOURCE,
+                suggestions(),
+                &Config::default(),
+                true,
+                Vec::new()
+            )
+            .is_err());
+    }
 }
@@ -0,0 +1,257 @@
+//! On disk cache of checker results.
+//!
+//! Results are stored per file and detector, keyed on a hash of the file's
+//! literals and a fingerprint of the detector configuration, including the
+//! dictionaries it reads. Any change to either results in a different key,
+//! so stale entries are never used.
+
+use crate::{Detector, LiteralSet, Severity, Span, Suggestion};
+
+use anyhow::{anyhow, Result};
+use log::debug;
+use proc_macro2::LineColumn;
+use serde::{Deserialize, Serialize};
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+/// Directory based cache of checker results.
+#[derive(Debug, Clone)]
+pub struct Cache {
+    dir: PathBuf,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+struct Position {
+    line: usize,
+    column: usize,
+}
+
+/// A single cached suggestion, referencing its literal by index.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub(super) struct Entry {
+    /// Index of the literal set within the file.
+    set: usize,
+    /// Index of the literal within the literal set.
+    literal: usize,
+    start: Position,
+    end: Position,
+    replacements: Vec<String>,
+    description: Option<String>,
+    /// Absent in entries written before suggestions carried a severity.
+    #[serde(default)]
+    severity: Option<Severity>,
+}
+
+impl Entry {
+    /// Convert a suggestion of a file with the given `literal_sets`.
+    ///
+    /// Returns `None` if the suggestion does not reference any of the literals.
+    pub(super) fn new(literal_sets: &[LiteralSet], suggestion: &Suggestion) -> Option<Self> {
+        let (set, literal) = literal_sets.iter().enumerate().find_map(|(set, cls)| {
+            cls.literals()
+                .iter()
+                .position(|literal| std::ptr::eq(*literal, suggestion.literal.as_ref()))
+                .map(|literal| (set, literal))
+        })?;
+        Some(Self {
+            set,
+            literal,
+            start: Position {
+                line: suggestion.span.start.line,
+                column: suggestion.span.start.column,
+            },
+            end: Position {
+                line: suggestion.span.end.line,
+                column: suggestion.span.end.column,
+            },
+            replacements: suggestion.replacements.clone(),
+            description: suggestion.description.clone(),
+            severity: Some(suggestion.severity),
+        })
+    }
+
+    /// Convert back to a suggestion referencing a literal of `literal_sets`.
+    pub(super) fn restore<'s>(
+        &self,
+        detector: Detector,
+        path: &Path,
+        literal_sets: &'s [LiteralSet],
+    ) -> Option<Suggestion<'s>> {
+        let literal = *literal_sets.get(self.set)?.literals().get(self.literal)?;
+        Some(Suggestion {
+            detector,
+            severity: self.severity.unwrap_or_else(|| detector.severity()),
+            path: path.to_owned(),
+            literal: literal.into(),
+            span: Span {
+                start: LineColumn {
+                    line: self.start.line,
+                    column: self.start.column,
+                },
+                end: LineColumn {
+                    line: self.end.line,
+                    column: self.end.column,
+                },
+            },
+            replacements: self.replacements.clone(),
+            description: self.description.clone(),
+        })
+    }
+}
+
+/// Fingerprint of a detector configuration, including size and modification
+/// time of all `files` the detector reads, such as dictionaries.
+pub(super) fn fingerprint<C: Serialize>(
+    detector: Detector,
+    config: &C,
+    files: impl IntoIterator<Item = PathBuf>,
+) -> Result<String> {
+    let mut fingerprint = format!(
+        "{} {}",
+        detector,
+        serde_json::to_string(config).map_err(|e| anyhow!(
+            "Failed to serialize {} config",
+            detector
+        )
+        .context(e))?
+    );
+    for file in files {
+        let stamp = std::fs::metadata(&file)
+            .and_then(|meta| Ok((meta.len(), meta.modified()?)))
+            .map(|stamp| format!("{:?}", stamp))
+            .unwrap_or_else(|_| "missing".to_owned());
+        fingerprint.push_str(&format!(" {}={}", file.display(), stamp));
+    }
+    Ok(fingerprint)
+}
+
+/// Key of the results of a file with `literal_sets`, checked by
+/// a detector with the given `fingerprint`.
+pub(super) fn key(fingerprint: &str, literal_sets: &[LiteralSet]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    env!("CARGO_PKG_VERSION").hash(&mut hasher);
+    fingerprint.hash(&mut hasher);
+    for cls in literal_sets {
+        let literals = cls.literals();
+        literals.len().hash(&mut hasher);
+        for literal in literals {
+            literal.hash(&mut hasher);
+        }
+    }
+    hasher.finish()
+}
+
+impl Cache {
+    /// Use `dir` to store the results, which is created if missing.
+    pub fn new(dir: PathBuf) -> Result<Self> {
+        std::fs::create_dir_all(&dir).map_err(|e| {
+            anyhow!("Failed to create cache directory {}", dir.display()).context(e)
+        })?;
+        Ok(Self { dir })
+    }
+
+    fn entry_path(&self, key: u64) -> PathBuf {
+        self.dir.join(format!("{:016x}.json", key))
+    }
+
+    /// Lookup the cached results for `key`.
+    ///
+    /// Unreadable entries are treated as missing.
+    pub(super) fn load(&self, key: u64) -> Option<Vec<Entry>> {
+        let path = self.entry_path(key);
+        let content = std::fs::read_to_string(&path).ok()?;
+        match serde_json::from_str(&content) {
+            Ok(entries) => Some(entries),
+            Err(e) => {
+                debug!("Ignoring corrupt cache entry {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    /// Store the results for `key`, replacing any previous ones.
+    pub(super) fn store(&self, key: u64, entries: &[Entry]) -> Result<()> {
+        let path = self.entry_path(key);
+        let content = serde_json::to_string(entries)
+            .map_err(|e| anyhow!("Failed to serialize cache entry").context(e))?;
+        std::fs::write(&path, content)
+            .map_err(|e| anyhow!("Failed to write cache entry {}", path.display()).context(e))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let dir = crate::testcase::scratch_dir("cache");
+        let cache = Cache::new(dir.clone()).unwrap();
+
+        let docs = crate::testcase::documentation();
+        let set = crate::testcase::suggestion_set(&docs);
+        let (path, literal_sets) = docs.iter().next().unwrap();
+        let hunspell = set
+            .suggestions(path)
+            .filter(|suggestion| suggestion.detector == Detector::Hunspell)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let fingerprint = fingerprint(Detector::Hunspell, &"en_US", None).unwrap();
+        let key = key(&fingerprint, literal_sets);
+        assert!(cache.load(key).is_none());
+
+        let entries = hunspell
+            .iter()
+            .map(|suggestion| Entry::new(literal_sets, suggestion).unwrap())
+            .collect::<Vec<_>>();
+        cache.store(key, &entries).unwrap();
+
+        let restored = cache
+            .load(key)
+            .unwrap()
+            .iter()
+            .map(|entry| {
+                entry
+                    .restore(Detector::Hunspell, path, literal_sets)
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(restored, hunspell);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn invalidation() {
+        let docs = crate::testcase::documentation();
+        let mut files = docs.iter();
+        let (_, first) = files.next().unwrap();
+        let (_, second) = files.next().unwrap();
+
+        let dir = crate::testcase::scratch_dir("invalidation");
+        let dic = dir.join("en_US.dic");
+        std::fs::write(&dic, "1\nfoo\n").unwrap();
+        let before = fingerprint(Detector::Hunspell, &"en_US", Some(dic.clone())).unwrap();
+        assert_eq!(
+            key(&before, first),
+            key(
+                &fingerprint(Detector::Hunspell, &"en_US", Some(dic.clone())).unwrap(),
+                first
+            )
+        );
+        assert_ne!(key(&before, first), key(&before, second));
+
+        // a changed dictionary or configuration must not reuse results
+        std::fs::write(&dic, "2\nfoo\nbar\n").unwrap();
+        let after = fingerprint(Detector::Hunspell, &"en_US", Some(dic.clone())).unwrap();
+        assert_ne!(key(&before, first), key(&after, first));
+        let other = fingerprint(Detector::Hunspell, &"de_DE", Some(dic.clone())).unwrap();
+        assert_ne!(key(&after, first), key(&other, first));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
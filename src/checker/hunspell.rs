@@ -1,4 +1,8 @@
-use super::{tokenize, Checker, Detector, Documentation, Suggestion, SuggestionSet};
+use super::ranking::{damerau_distance, rank_replacements, WordFrequencies};
+use super::{
+    par_find, tokenize, tokenize_prose, Checker, Detector, Documentation, Suggestion, SuggestionSet,
+};
+use crate::Range;
 use std::path::PathBuf;
 
 use log::{debug, trace};
@@ -25,45 +29,22 @@ impl Checker for HunspellChecker {
 
         let lang = config.lang();
 
-        // lookup paths are really just an attempt to provide a dictionary, so be more forgiving
-        // when encountering errors here
-        let (dic, aff): (PathBuf, PathBuf) = search_dirs
-            .into_iter()
-            .filter(|search_dir| {
-                let keep = search_dir.is_dir();
-                if !keep {
-                    // search_dir also contains the default paths, so just silently ignore these
-                    debug!(
-                        "Dictionary search path {} is not a directory",
-                        search_dir.display()
-                    );
+        let (dic, aff) = locate(search_dirs, lang)?;
+        // the dictionaries of further languages are only used to accept words
+        let extra_langs = config
+            .extra_langs()
+            .iter()
+            .map(|lang| {
+                let (dic, aff) = locate(search_dirs, lang)?;
+                match (dic.to_str(), aff.to_str()) {
+                    (Some(dic), Some(aff)) => Ok((dic.to_owned(), aff.to_owned())),
+                    _ => Err(anyhow!(
+                        "Failed to convert the dictionary of {} to a str",
+                        lang
+                    )),
                 }
-                keep
             })
-            .find_map(|search_dir| {
-                let dic = search_dir.join(lang).with_extension("dic");
-                if !dic.is_file() {
-                    debug!(
-                        "Dictionary path dervied from search dir {} is not a file",
-                        dic.display()
-                    );
-                    return None;
-                }
-                let aff = search_dir.join(lang).with_extension("aff");
-                if !aff.is_file() {
-                    debug!(
-                        "Affixes path dervied from search dir {} is not a file",
-                        aff.display()
-                    );
-                    return None;
-                }
-                trace!("Using dic {} and aff {}", dic.display(), aff.display());
-                Some((dic, aff))
-            })
-            .ok_or_else(|| {
-                anyhow!("Failed to find any {lang}.dic / {lang}.aff in any search dir or no search provided",
-                lang = lang)
-            })?;
+            .collect::<Result<Vec<_>>>()?;
 
         let dic = dic.to_str().unwrap();
         let aff = aff.to_str().unwrap();
@@ -71,6 +52,8 @@ impl Checker for HunspellChecker {
         let mut hunspell = Hunspell::new(aff, dic);
         hunspell.add_dictionary(dic);
 
+        let mut extra_dics = Vec::with_capacity(config.extra_dictonaries().len());
+
         if cfg!(debug_assertions) && lang == "en_US" {
             // "Test" is a valid word
             assert!(hunspell.check("Test"));
@@ -91,6 +74,7 @@ impl Checker for HunspellChecker {
                 if !hunspell.add_dictionary(extra_dic) {
                     return Err(anyhow!("Failed to add additional dict to hunspell"));
                 }
+                extra_dics.push(extra_dic);
             } else {
                 return Err(anyhow!(
                     "Failed to convert one of the extra dictionaries to a str"
@@ -98,53 +82,311 @@ impl Checker for HunspellChecker {
             }
         }
 
-        let suggestions = docu.iter().try_fold::<SuggestionSet, _, Result<_>>(
-            SuggestionSet::new(),
-            |mut acc, (path, literal_sets)| {
+        let frequencies = config
+            .word_frequencies()
+            .map(WordFrequencies::load_from)
+            .transpose()?;
+
+        // a hunspell instance can not be shared across threads, so every
+        // worker loads its own, the dictionaries are known to be fine by now,
+        // the first one is the primary one
+        let create = || {
+            let mut hunspell = Hunspell::new(aff, dic);
+            hunspell.add_dictionary(dic);
+            for extra_dic in extra_dics.iter() {
+                hunspell.add_dictionary(extra_dic);
+            }
+            std::iter::once(hunspell)
+                .chain(
+                    extra_langs
+                        .iter()
+                        .map(|(dic, aff)| Hunspell::new(aff.as_str(), dic.as_str())),
+                )
+                .collect::<Vec<_>>()
+        };
+
+        // the documentation can not be sent across threads either, but the plain text can
+        let rendering = &config.rendering;
+        let plains = docu
+            .iter()
+            .flat_map(|(path, literal_sets)| {
+                literal_sets
+                    .iter()
+                    .map(move |literal_set| (path, literal_set.erase_markdown_with(rendering)))
+            })
+            .collect::<Vec<_>>();
+        let txts = plains
+            .iter()
+            .map(|(_path, plain)| plain.as_str())
+            .collect::<Vec<_>>();
+        let mistakes = par_find(&txts, create, |hunspells, txt| {
+            find_mistakes(hunspells, txt, config, frequencies.as_ref())
+        });
+
+        let mut suggestions = SuggestionSet::new();
+        for ((path, plain), mistakes) in plains.iter().zip(mistakes.into_iter()) {
+            trace!("{:?}", plain);
+            for (range, replacements) in mistakes {
+                for (literal, span) in plain.linear_range_to_spans(range) {
+                    suggestions.add(
+                        PathBuf::from(*path),
+                        Suggestion {
+                            detector: Detector::Hunspell,
+                            severity: Detector::Hunspell.severity(),
+                            span,
+                            path: PathBuf::from(*path),
+                            replacements: replacements.clone(),
+                            literal: literal.into(),
+                            description: Some("Possible spelling mistake found.".to_owned()),
+                        },
+                    )
+                }
+            }
+        }
+
+        if config.check_code {
+            let hunspells = create();
+            for (path, literal_sets) in docu.iter() {
                 for literal_set in literal_sets {
-                    let plain = literal_set.erase_markdown();
-                    trace!("{:?}", &plain);
-                    let txt = plain.as_str();
-                    for range in tokenize(txt) {
-                        let word = &txt[range.clone()];
-                        if !hunspell.check(word) {
-                            trace!("No match for word (plain range: {:?}): >{}<", &range, word);
-                            // get rid of single character suggestions
-                            let replacements = hunspell
-                                .suggest(word)
-                                .into_iter()
-                                .filter(|x| x.len() > 1) // single char suggestions tend to be useless
-                                .collect::<Vec<_>>();
-
-                            for (literal, span) in plain.linear_range_to_spans(range.clone()) {
-                                acc.add(
-                                    path.to_owned(),
-                                    Suggestion {
-                                        detector: Detector::Hunspell,
-                                        span,
-                                        path: PathBuf::from(path),
-                                        replacements: replacements.clone(),
-                                        literal: literal.into(),
-                                        description: Some(
-                                            "Possible spelling mistake found.".to_owned(),
-                                        ),
-                                    },
-                                )
-                            }
-                        } else {
-                            trace!(
-                                "Found a match for word (plain range: {:?}): >{}<",
-                                &range,
-                                word
-                            );
+                    let code = literal_set.inline_code();
+                    for (range, replacements) in
+                        find_code_mistakes(&hunspells, code.as_str(), config)
+                    {
+                        for (literal, span) in code.linear_range_to_spans(range) {
+                            suggestions.add(
+                                path.clone(),
+                                Suggestion {
+                                    detector: Detector::Hunspell,
+                                    severity: Detector::Hunspell.severity(),
+                                    span,
+                                    path: path.clone(),
+                                    replacements: replacements.clone(),
+                                    literal: literal.into(),
+                                    description: Some(
+                                        "Possible spelling mistake within an identifier."
+                                            .to_owned(),
+                                    ),
+                                },
+                            )
                         }
                     }
                 }
-                Ok(acc)
-            },
-        )?;
+            }
+        }
 
         // TODO sort spans by file and line + column
         Ok(suggestions)
     }
 }
+
+/// Find the `.dic` and `.aff` files of `lang` in the first search dir containing both.
+fn locate(search_dirs: &[PathBuf], lang: &str) -> Result<(PathBuf, PathBuf)> {
+    // lookup paths are really just an attempt to provide a dictionary, so be more forgiving
+    // when encountering errors here
+    search_dirs
+        .into_iter()
+        .filter(|search_dir| {
+            let keep = search_dir.is_dir();
+            if !keep {
+                // search_dir also contains the default paths, so just silently ignore these
+                debug!(
+                    "Dictionary search path {} is not a directory",
+                    search_dir.display()
+                );
+            }
+            keep
+        })
+        .find_map(|search_dir| {
+            let dic = search_dir.join(lang).with_extension("dic");
+            if !dic.is_file() {
+                debug!(
+                    "Dictionary path dervied from search dir {} is not a file",
+                    dic.display()
+                );
+                return None;
+            }
+            let aff = search_dir.join(lang).with_extension("aff");
+            if !aff.is_file() {
+                debug!(
+                    "Affixes path dervied from search dir {} is not a file",
+                    aff.display()
+                );
+                return None;
+            }
+            trace!("Using dic {} and aff {}", dic.display(), aff.display());
+            Some((dic, aff))
+        })
+        .ok_or_else(|| {
+            anyhow!("Failed to find any {lang}.dic / {lang}.aff in any search dir or no search provided",
+            lang = lang)
+        })
+}
+
+/// Find all words in `txt` which none of the `hunspells` accepts, together
+/// with the replacements of the first one.
+fn find_mistakes(
+    hunspells: &[Hunspell],
+    txt: &str,
+    config: &crate::config::HunspellConfig,
+    frequencies: Option<&WordFrequencies>,
+) -> Vec<(Range, Vec<String>)> {
+    let mut mistakes = Vec::new();
+    for range in tokenize_prose(
+        txt,
+        config.separators(),
+        config.split_identifiers,
+        config.verbatim(),
+    ) {
+        let word = &txt[range.clone()];
+        if config.skip_digits.skip(word) {
+            trace!(
+                "Skipping word with digits (plain range: {:?}): >{}<",
+                &range,
+                word
+            );
+            continue;
+        }
+        if !hunspells.iter().any(|hunspell| hunspell.check(word)) {
+            trace!("No match for word (plain range: {:?}): >{}<", &range, word);
+            // get rid of single character suggestions
+            let mut replacements = hunspells[0]
+                .suggest(word)
+                .into_iter()
+                .filter(|x| x.len() > 1) // single char suggestions tend to be useless
+                .collect::<Vec<_>>();
+            if let Some(frequencies) = frequencies {
+                rank_replacements(word, &mut replacements, frequencies);
+            }
+            mistakes.push((range, replacements));
+        } else {
+            trace!(
+                "Found a match for word (plain range: {:?}): >{}<",
+                &range,
+                word
+            );
+        }
+    }
+    mistakes
+}
+
+/// Find the sub-words of identifiers in `code` which are a single edit,
+/// including a swap, away from a word of the first of `hunspells`, together
+/// with those words.
+///
+/// Identifiers accepted as a whole and short sub-words such as `foo` or `ptr`
+/// are never flagged, neither are sub-words without a close replacement.
+fn find_code_mistakes(
+    hunspells: &[Hunspell],
+    code: &str,
+    config: &crate::config::HunspellConfig,
+) -> Vec<(Range, Vec<String>)> {
+    let check = |word: &str| hunspells.iter().any(|hunspell| hunspell.check(word));
+    let mut mistakes = Vec::new();
+    for identifier in tokenize(code, config.separators(), false) {
+        if check(&code[identifier.clone()]) {
+            continue;
+        }
+        for range in tokenize(&code[identifier.clone()], config.separators(), true) {
+            let range = (identifier.start + range.start)..(identifier.start + range.end);
+            let word = &code[range.clone()];
+            if word.chars().count() < 4 || config.skip_digits.skip(word) || check(word) {
+                continue;
+            }
+            let replacements = hunspells[0]
+                .suggest(word)
+                .into_iter()
+                .filter(|replacement| {
+                    damerau_distance(&replacement.to_lowercase(), &word.to_lowercase()) <= 1
+                })
+                .collect::<Vec<_>>();
+            if replacements.is_empty() {
+                trace!(
+                    "No close match for identifier part (code range: {:?}): >{}<",
+                    &range,
+                    word
+                );
+                continue;
+            }
+            mistakes.push((range, replacements));
+        }
+    }
+    mistakes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Config, Documentation};
+
+    #[test]
+    fn extra_langs() {
+        let dir = crate::testcase::scratch_dir("langs");
+        for (lang, words) in &[
+            ("en_XX", "4\nCheck\nthe\nspelling\nof\n"),
+            ("de_XX", "1\nKartoffel\n"),
+        ] {
+            std::fs::write(dir.join(lang).with_extension("dic"), words).unwrap();
+            std::fs::write(dir.join(lang).with_extension("aff"), "SET UTF-8\n").unwrap();
+        }
+
+        let docs = Documentation::load_from_str(
+            "lib.rs",
+            "/// Check the spelling of Kartoffel.\nstruct X;",
+            &Config::default(),
+        )
+        .expect("Must parse");
+        let count = |extra_langs: &[&str]| {
+            let mut config = Config::default().hunspell.unwrap();
+            config.lang = Some("en_XX".to_owned());
+            config.search_dirs = Some(vec![dir.clone()]);
+            config.extra_langs = extra_langs.iter().map(|lang| (*lang).to_owned()).collect();
+            HunspellChecker::check(&docs, &config)
+                .expect("Must find all dictionaries")
+                .count()
+        };
+
+        // only valid in the second dictionary
+        assert_eq!(count(&[]), 1);
+        assert_eq!(count(&["de_XX"]), 0);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn check_code() {
+        let dir = crate::testcase::scratch_dir("code");
+        std::fs::write(
+            dir.join("en_XX.dic"),
+            "7\nCall\nwith\nor\ncalc\nlength\nfood\nqux_length\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("en_XX.aff"), "SET UTF-8\n").unwrap();
+
+        let mut config = Config::default().hunspell.unwrap();
+        config.lang = Some("en_XX".to_owned());
+        config.search_dirs = Some(vec![dir.clone()]);
+        let docs = Documentation::load_from_str(
+            "lib.rs",
+            "/// Call `calc_lenght` with `foo`, `qux_length` or `xyzzyq`.\nstruct X;",
+            &Config::default(),
+        )
+        .expect("Must parse");
+
+        // off by default
+        assert!(!config.check_code);
+        let set = HunspellChecker::check(&docs, &config).unwrap();
+        assert_eq!(set.count(), 0);
+
+        config.check_code = true;
+        let set = HunspellChecker::check(&docs, &config).unwrap();
+        let (_path, suggestions) = crate::testcase::first(set.iter());
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(
+            crate::report::covered_text(&suggestions[0]).as_deref(),
+            Some("lenght")
+        );
+        assert_eq!(suggestions[0].replacements, vec!["length".to_owned()]);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -1,6 +1,9 @@
 use super::*;
 
 use crate::literalset::Range;
+use std::path::PathBuf;
+
+use anyhow::anyhow;
 use languagetool_rs::{LanguageTool, Request};
 pub struct LanguageToolChecker;
 
@@ -10,57 +13,100 @@ impl Checker for LanguageToolChecker {
     where
         'a: 's,
     {
-        let lt = LanguageTool::new(config.url.as_str())?;
-        let suggestions = docu.iter().try_fold::<SuggestionSet, _, Result<_>>(
-            SuggestionSet::new(),
-            |mut acc, (path, literal_sets)| {
-                for cls in literal_sets {
-                    let plain = cls.erase_markdown();
-                    log::trace!("markdown erasure: {:?}", &plain);
-                    let req = Request::new(plain.to_string(), "en-US".to_owned());
-                    let resp = lt.check(req)?;
-                    if let Some(software) = resp.software {
-                        log::trace!("sw: {:?}", software);
-                    }
-                    if let Some(matches) = resp.matches {
-                        for item in matches {
-                            if let Some(rule) = item.rule {
-                                if rule.id == "EN_QUOTES" {
-                                    // really annoying and pointless in code related documentation
-                                    continue;
-                                }
-                                log::trace!("item.rule: {:?}", rule);
-                            }
-                            log::trace!("item.context: {:?}", item.context);
-                            log::trace!("item.message: {:?}", item.message);
-                            log::trace!("item.short_message: {:?}", item.short_message);
-                            // TODO convert response to offsets and errors with the matching literal
-                            for (literal, span) in plain.linear_range_to_spans(Range {
-                                start: item.offset as usize,
-                                end: (item.offset + item.length) as usize,
-                            }) {
-                                acc.add(
-                                    path.to_owned(),
-                                    Suggestion {
-                                        detector: Detector::LanguageTool,
-                                        span: span,
-                                        path: PathBuf::from(path),
-                                        replacements: item
-                                            .replacements
-                                            .iter()
-                                            .filter_map(|x| x.value.clone())
-                                            .collect(),
-                                        literal: literal.into(),
-                                        description: Some(item.message.clone()),
-                                    },
-                                );
-                            }
+        let url = config.url();
+        // fail early on an invalid url, every worker connects on its own
+        LanguageTool::new(url.as_str())
+            .map_err(|e| anyhow!("Failed to connect to LanguageTool server {}", url).context(e))?;
+        let rendering = &config.rendering;
+        // each block is checked on its own, so list items and alike
+        // are not considered a single run-on sentence
+        let plains = docu
+            .iter()
+            .flat_map(|(path, literal_sets)| {
+                literal_sets.iter().flat_map(move |literal_set| {
+                    literal_set
+                        .erase_markdown_chunks(rendering)
+                        .into_iter()
+                        .map(move |plain| (path, plain))
+                })
+            })
+            .collect::<Vec<_>>();
+        let txts = plains
+            .iter()
+            .map(|(_path, plain)| plain.as_str())
+            .collect::<Vec<_>>();
+        let items = par_find(
+            &txts,
+            || LanguageTool::new(url.as_str()).ok(),
+            |lt, txt| -> Result<Vec<(Range, Vec<String>, String)>> {
+                let lt = lt
+                    .as_ref()
+                    .ok_or_else(|| anyhow!("Failed to connect to LanguageTool server {}", url))?;
+                log::trace!("markdown erasure: {:?}", txt);
+                let mut req = Request::new(txt.to_owned(), "en-US".to_owned());
+                req.username = config.username.clone();
+                req.api_key = config.api_key.clone();
+                let resp = lt.check(req).map_err(|e| {
+                    anyhow!("LanguageTool server {} is unreachable", url).context(e)
+                })?;
+                if let Some(software) = resp.software {
+                    log::trace!("sw: {:?}", software);
+                }
+                let mut items = Vec::new();
+                for item in resp.matches.into_iter().flatten() {
+                    if let Some(rule) = item.rule {
+                        if rule.id == "EN_QUOTES" {
+                            // really annoying and pointless in code related documentation
+                            continue;
                         }
+                        log::trace!("item.rule: {:?}", rule);
                     }
+                    log::trace!("item.context: {:?}", item.context);
+                    log::trace!("item.message: {:?}", item.message);
+                    log::trace!("item.short_message: {:?}", item.short_message);
+                    items.push((
+                        Range {
+                            start: item.offset as usize,
+                            end: (item.offset + item.length) as usize,
+                        },
+                        item.replacements
+                            .iter()
+                            .filter_map(|x| x.value.clone())
+                            .collect(),
+                        item.message,
+                    ));
                 }
-                Ok(acc)
+                Ok(items)
             },
-        )?;
+        );
+
+        let mut suggestions = SuggestionSet::new();
+        for ((path, plain), items) in plains.iter().zip(items) {
+            for (range, replacements, message) in items? {
+                // a finding across lines yields one fragment per line, only the
+                // first one carries the replacements, so a fix is applied once
+                for (idx, (literal, span)) in
+                    plain.linear_range_to_spans(range).into_iter().enumerate()
+                {
+                    suggestions.add(
+                        PathBuf::from(*path),
+                        Suggestion {
+                            detector: Detector::LanguageTool,
+                            severity: Detector::LanguageTool.severity(),
+                            span,
+                            path: PathBuf::from(*path),
+                            replacements: if idx == 0 {
+                                replacements.clone()
+                            } else {
+                                Vec::new()
+                            },
+                            literal: literal.into(),
+                            description: Some(message.clone()),
+                        },
+                    );
+                }
+            }
+        }
 
         Ok(suggestions)
     }
@@ -1,14 +1,27 @@
+use crate::config::DEFAULT_SEPARATORS;
 use crate::{Config, Detector, Documentation, Suggestion, SuggestionSet};
 
 use anyhow::Result;
 
 use crate::Range;
-use log::debug;
+use log::{debug, warn};
+use rayon::prelude::*;
+use std::path::Path;
+use unicode_segmentation::UnicodeSegmentation;
+
+mod cache;
+pub(crate) mod ranking;
+mod repetition;
+mod wordlist;
+
+pub use self::cache::Cache;
 
 #[cfg(feature = "hunspell")]
 mod hunspell;
 #[cfg(feature = "languagetool")]
 mod languagetool;
+#[cfg(feature = "nlprule")]
+mod nlprule;
 
 /// Implementation for a checker
 pub(crate) trait Checker {
@@ -18,59 +31,430 @@ pub(crate) trait Checker {
         'a: 's;
 }
 
+/// Apostrophe code points which may be part of a contraction.
+fn is_apostrophe(c: char) -> bool {
+    c == '\'' || c == '’'
+}
+
+/// Split a word at all apostrophes which are not flanked by letters on both
+/// sides, since those are quotation marks rather than part of a contraction
+/// such as `don't`.
+///
+/// Returns the ranges relative to `word`, which might be empty.
+fn split_quotes(word: &str) -> Vec<Range> {
+    let mut acc = Vec::with_capacity(2);
+    let mut start = 0usize;
+    let mut previous: Option<char> = None;
+    let mut iter = word.char_indices().peekable();
+    while let Some((idx, c)) = iter.next() {
+        let next = iter.peek().map(|(_idx, c)| *c);
+        let in_word =
+            previous.map_or(false, char::is_alphabetic) && next.map_or(false, char::is_alphabetic);
+        if is_apostrophe(c) && !in_word {
+            acc.push(start..idx);
+            start = idx + c.len_utf8();
+        }
+        previous = Some(c);
+    }
+    acc.push(start..word.len());
+    acc
+}
+
+/// Split an identifier such as `camelCase`, `PascalCase` or `snake_case`
+/// into its sub-words.
+///
+/// Returns the ranges relative to `word`.
+fn split_identifier(word: &str) -> Vec<Range> {
+    let chars = word.char_indices().collect::<Vec<(usize, char)>>();
+    let mut acc = Vec::with_capacity(4);
+    let mut start: Option<usize> = None;
+    for (i, &(idx, c)) in chars.iter().enumerate() {
+        if c == '_' {
+            if let Some(start) = start.take() {
+                acc.push(start..idx);
+            }
+            continue;
+        }
+        if let Some(current) = start {
+            let previous = chars[i - 1].1;
+            let next = chars.get(i + 1).map(|&(_idx, c)| c);
+            // `camelCase` as well as the last capital letter of an acronym as in `HTTPServer`
+            let boundary = c.is_uppercase()
+                && (previous.is_lowercase()
+                    || previous.is_numeric()
+                    || (previous.is_uppercase() && next.map_or(false, char::is_lowercase)));
+            if boundary {
+                acc.push(current..idx);
+                start = Some(idx);
+            }
+        } else {
+            start = Some(idx);
+        }
+    }
+    if let Some(start) = start {
+        acc.push(start..word.len());
+    }
+    acc
+}
+
+/// Split `word` at all characters contained in `separators`.
+///
+/// Returns the ranges relative to `word`, which might be empty.
+fn split_separators(word: &str, separators: &str) -> Vec<Range> {
+    let mut acc = Vec::with_capacity(2);
+    let mut start = 0usize;
+    for (idx, c) in word.char_indices() {
+        if separators.contains(c) {
+            acc.push(start..idx);
+            start = idx + c.len_utf8();
+        }
+    }
+    acc.push(start..word.len());
+    acc
+}
+
+/// Split `s` into words, each word is a byte range within `s`.
+///
+/// Based on the unicode word segmentation rules (UAX#29), any character in
+/// `separators` as well as any whitespace always separates words. A default
+/// separator which is not contained in `separators` is kept within a word,
+/// as long as it is flanked by word segments on both sides, i.e. `and/or` is
+/// a single word if `/` is not a separator. Any other punctuation separates
+/// words according to UAX#29, i.e. `foo*bar` unless `*` is a separator.
+fn split_words(s: &str, separators: &str) -> Vec<Range> {
+    let mut acc = Vec::with_capacity(64);
+    let mut current: Option<Range> = None;
+    // punctuation directly following `current`, which might join the next segment
+    let mut glue = false;
+    for (offset, segment) in s.split_word_bound_indices() {
+        if segment.chars().any(char::is_alphanumeric) {
+            let subs = split_separators(segment, separators);
+            let last = subs.len() - 1;
+            for (i, sub) in subs.into_iter().enumerate() {
+                let sub = Range {
+                    start: offset + sub.start,
+                    end: offset + sub.end,
+                };
+                match current {
+                    Some(ref mut current) if i == 0 && glue => current.end = sub.end,
+                    _ => acc.extend(current.replace(sub)),
+                }
+                glue = false;
+                if i != last {
+                    acc.extend(current.take());
+                }
+            }
+        } else {
+            let mut chars = segment.chars();
+            let joining = match (chars.next(), chars.next()) {
+                (Some(c), None) => {
+                    DEFAULT_SEPARATORS.contains(c) && !c.is_whitespace() && !separators.contains(c)
+                }
+                _ => false,
+            };
+            if joining && !glue && current.is_some() {
+                glue = true;
+            } else {
+                acc.extend(current.take());
+                glue = false;
+            }
+        }
+    }
+    acc.extend(current);
+    acc
+}
+
 /// Returns absolute offsets and the data with the token in question.
 ///
+/// Word boundaries are determined according to the unicode word segmentation
+/// rules (UAX#29) and the given `separators`, see `split_words`, segments
+/// without any alphanumeric character are dropped.
+/// Apostrophes are only kept within a word, if flanked by letters on both sides.
+/// If `split_identifiers` is set, identifiers are further split into their
+/// sub-words, i.e. `SuggestionSet` yields `Suggestion` and `Set`.
 /// Does not handle hyphenation yet or partial words at boundaries.
-/// Returns the a vector of ranges for the input str.
-fn tokenize(s: &str) -> Vec<Range> {
-    let mut started = false;
-    let mut linear_start = 0usize;
-    let mut linear_end;
-    let mut bananasplit = Vec::with_capacity(32);
-    let _fin_char_idx = 0usize;
-
-    let blacklist = "\";:,.?!#(){}[]-\n\r/`".to_owned();
-    let is_ignore_char = |c: char| c.is_whitespace() || blacklist.contains(c);
-
-    for (c_idx, c) in s.char_indices() {
-        if is_ignore_char(c) {
-            linear_end = c_idx;
-            if started {
-                bananasplit.push(linear_start..linear_end);
+/// Returns the a vector of byte ranges for the input str.
+fn tokenize(s: &str, separators: &str, split_identifiers: bool) -> Vec<Range> {
+    split_words(s, separators)
+        .into_iter()
+        .flat_map(|word| {
+            split_quotes(&s[word.clone()])
+                .into_iter()
+                .map(move |range| Range {
+                    start: word.start + range.start,
+                    end: word.start + range.end,
+                })
+        })
+        .flat_map(|range| {
+            if split_identifiers {
+                split_identifier(&s[range.clone()])
+                    .into_iter()
+                    .map(|sub| Range {
+                        start: range.start + sub.start,
+                        end: range.start + sub.end,
+                    })
+                    .collect::<Vec<_>>()
+            } else {
+                vec![range]
             }
-            started = false;
-        // @todo handle hyphenation
-        // if c == '\n' {
-        //     column = 0;
-        //     line += 1;
-        // }
-        } else {
-            if !started {
-                linear_start = c_idx;
-                started = true;
+        })
+        .filter(|range| s[range.clone()].chars().any(char::is_alphanumeric))
+        .collect()
+}
+
+/// Whitespace separated chunks of text which are not prose, but would be
+/// split into multiple tokens, each kind is skipped as a whole if enabled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Verbatim {
+    /// File system paths such as `src/checker/mod.rs` or `./target`.
+    pub paths: bool,
+    /// Version numbers such as `v1.2.3` or `0.4.0-alpha.1`.
+    pub versions: bool,
+    /// Hexadecimal literals such as `0xDEADBEEF`.
+    pub hex: bool,
+}
+
+// only the spell checker skips verbatim chunks
+#[cfg(any(feature = "hunspell", test))]
+impl Verbatim {
+    /// If the `chunk`, stripped of surrounding punctuation, is not prose.
+    fn matches(&self, chunk: &str) -> bool {
+        lazy_static::lazy_static! {
+            static ref VERSION: regex::Regex =
+                regex::Regex::new(r"^[vV]?[0-9]+(\.[0-9]+)+([-+][0-9A-Za-z.+-]+)?$").unwrap();
+            static ref HEX: regex::Regex = regex::Regex::new(r"^0[xX][0-9a-fA-F_]+$").unwrap();
+        }
+        (self.versions && VERSION.is_match(chunk))
+            || (self.hex && HEX.is_match(chunk))
+            || (self.paths && is_path(chunk))
+    }
+
+    /// The byte ranges of all chunks of `s` which are not prose.
+    fn chunks(&self, s: &str) -> Vec<Range> {
+        if *self == Self::default() {
+            return Vec::new();
+        }
+        let mut acc = Vec::new();
+        let mut start = 0usize;
+        // whitespace may well be longer than a single byte, i.e. `\u{a0}`
+        let separators = s
+            .match_indices(char::is_whitespace)
+            .map(|(idx, whitespace)| (idx, idx + whitespace.len()))
+            .chain(std::iter::once((s.len(), s.len())));
+        for (end, next) in separators {
+            let chunk = &s[start..end];
+            let trimmed = chunk.trim_start_matches(|c| "([{\"'`‘“".contains(c));
+            let offset = start + chunk.len() - trimmed.len();
+            start = next;
+            let trimmed = trimmed.trim_end_matches(|c| ".,:;!?)]}\"'`’”".contains(c));
+            if !trimmed.is_empty() && self.matches(trimmed) {
+                acc.push(offset..offset + trimmed.len());
             }
         }
+        acc
+    }
+}
+
+/// A path is either anchored, i.e. `./src` or `~/.config`, or names a file
+/// with an extension within a directory, i.e. `src/lib.rs`, so `and/or`
+/// is still prose.
+#[cfg(any(feature = "hunspell", test))]
+fn is_path(chunk: &str) -> bool {
+    if !chunk
+        .chars()
+        .all(|c| c.is_alphanumeric() || "/\\._-~:".contains(c))
+    {
+        return false;
+    }
+    let mut segments = chunk.split(|c| c == '/' || c == '\\');
+    let last = segments.next_back().unwrap_or_default();
+    if segments.next().is_none() {
+        return false;
     }
-    // at the end of string, assume word complete
-    // @todo for hypenation, check if line ends with a dash
-    if started {
-        if let Some((idx, _)) = s.char_indices().next_back() {
-            // increase by one, since the range's end goes one beyond
-            let linear_end = idx + 1;
-            bananasplit.push(linear_start..linear_end)
+    let anchored = ["/", "./", "../", "~/"]
+        .iter()
+        .any(|anchor| chunk.starts_with(anchor));
+    let extension = match last.rfind('.') {
+        Some(dot) => dot > 0 && dot + 1 < last.len(),
+        None => false,
+    };
+    anchored || extension
+}
+
+/// Same as `tokenize`, without any tokens within the `verbatim` chunks of `s`.
+#[cfg(any(feature = "hunspell", test))]
+fn tokenize_prose(
+    s: &str,
+    separators: &str,
+    split_identifiers: bool,
+    verbatim: Verbatim,
+) -> Vec<Range> {
+    let chunks = verbatim.chunks(s);
+    tokenize(s, separators, split_identifiers)
+        .into_iter()
+        .filter(|range| {
+            !chunks
+                .iter()
+                .any(|chunk| chunk.start <= range.start && range.end <= chunk.end)
+        })
+        .collect()
+}
+
+/// Drop the spelling findings of words shorter than `min_word_length`
+/// characters, such as `el` or `ok`, which are rarely misspelled words.
+///
+/// Grammar findings may well cover a single character, so they are kept.
+fn skip_short_words(suggestions: &mut SuggestionSet, min_word_length: usize) {
+    suggestions.retain(|suggestion| {
+        !matches!(suggestion.detector, Detector::Hunspell | Detector::Wordlist)
+            || crate::report::covered_text(suggestion)
+                .map_or(true, |word| word.chars().count() >= min_word_length)
+    });
+}
+
+/// Apply `find` to all plain texts in parallel, where every worker thread
+/// creates its own state once with `init`.
+///
+/// The literals can not be sent across threads, their spans are resolved with
+/// the source map of the thread which parsed them. So only the plain texts are
+/// passed in, while the findings are mapped back to the literals by the caller.
+/// The order of the results is retained, so the suggestions are deterministic.
+fn par_find<S, T>(
+    plains: &[&str],
+    init: impl Fn() -> S + Sync + Send,
+    find: impl Fn(&mut S, &str) -> T + Sync + Send,
+) -> Vec<T>
+where
+    T: Send,
+{
+    plains
+        .par_iter()
+        .map_init(init, |state, txt| find(state, txt))
+        .collect()
+}
+
+/// Run the checker `C`, reusing the cached results of all files which are
+/// unchanged since they were checked with the same configuration.
+///
+/// Without a `cache` all files are checked.
+#[cfg(any(feature = "hunspell", feature = "languagetool", feature = "nlprule"))]
+fn run<'a, 's, C>(
+    documentation: &'a Documentation,
+    detector: Detector,
+    config: &C::Config,
+    files: Vec<std::path::PathBuf>,
+    cache: Option<&Cache>,
+    progress: &mut dyn FnMut(&Path),
+) -> Result<SuggestionSet<'s>>
+where
+    'a: 's,
+    C: Checker,
+    C::Config: serde::Serialize,
+{
+    use log::trace;
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    let cache = if let Some(cache) = cache {
+        cache
+    } else {
+        let suggestions = C::check(documentation, config)?;
+        documentation.iter().for_each(|(path, _)| progress(path));
+        // every checker attributes its findings itself, the cache relies on it
+        debug_assert!(suggestions.iter().all(|(_path, suggestions)| suggestions
+            .iter()
+            .all(|suggestion| suggestion.detector == detector)));
+        return Ok(suggestions);
+    };
+    let fingerprint = cache::fingerprint(detector, config, files)?;
+
+    let mut hits = HashMap::<&PathBuf, Vec<cache::Entry>>::with_capacity(64);
+    let mut misses = Vec::with_capacity(64);
+    let mut missing = Documentation::new();
+    for (path, literal_sets) in documentation.iter() {
+        let key = cache::key(&fingerprint, literal_sets);
+        if let Some(entries) = cache.load(key) {
+            trace!("Cache hit for {} of {}", detector, path.display());
+            hits.insert(path, entries);
+            progress(path);
         } else {
-            log::warn!("Most liekly lost a word when tokenizing! BUG");
+            missing.extend(path.clone(), literal_sets.clone());
+            misses.push((path, key));
+        }
+    }
+
+    if !missing.is_empty() {
+        let fresh = C::check(&missing, config)?
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+        for ((_, literal_sets), (path, key)) in missing.iter().zip(misses) {
+            let entries = fresh
+                .get(path)
+                .into_iter()
+                .flatten()
+                .filter_map(|suggestion| cache::Entry::new(literal_sets, suggestion))
+                .collect::<Vec<_>>();
+            if let Err(e) = cache.store(key, &entries) {
+                warn!("Failed to cache results of {}: {}", path.display(), e);
+            }
+            hits.insert(path, entries);
+            progress(path);
+        }
+    }
+
+    let mut suggestions = SuggestionSet::new();
+    for (path, literal_sets) in documentation.iter() {
+        if let Some(entries) = hits.get(path) {
+            suggestions.extend(
+                path.clone(),
+                entries
+                    .iter()
+                    .filter_map(|entry| entry.restore(detector, path, literal_sets)),
+            );
         }
     }
-    bananasplit
+    Ok(suggestions)
+}
+
+/// All detectors which are compiled in and enabled by the `config`, in the
+/// order they are run.
+pub fn detectors(config: &Config) -> impl Iterator<Item = Detector> + '_ {
+    [
+        (Detector::LanguageTool, cfg!(feature = "languagetool")),
+        (Detector::Nlprule, cfg!(feature = "nlprule")),
+        (Detector::Hunspell, cfg!(feature = "hunspell")),
+        (Detector::Repetition, true),
+        (Detector::Wordlist, true),
+    ]
+    .iter()
+    .filter(move |(detector, available)| *available && config.is_enabled(*detector))
+    .map(|(detector, _)| *detector)
 }
 
 /// Check a full document for violations using the tools we have.
-pub fn check<'a, 's>(documentation: &'a Documentation, config: &Config) -> Result<SuggestionSet<'s>>
+///
+/// If a `cache` is given, only files which changed since the last run
+/// are checked. Every file is passed to `progress` once per detector in
+/// `detectors`, as soon as that detector is done with it.
+pub fn check<'a, 's>(
+    documentation: &'a Documentation,
+    config: &Config,
+    cache: Option<&Cache>,
+    progress: &mut dyn FnMut(&Path),
+) -> Result<SuggestionSet<'s>>
 where
     'a: 's,
 {
     let mut collective = SuggestionSet::<'s>::new();
+    // register all files upfront, so files without any findings are retained
+    // in the same order as they appear in the documentation
+    for (path, _literal_sets) in documentation.iter() {
+        collective.extend(path.clone(), Vec::new());
+    }
+
+    // only the expensive detectors are cached, without any there is nothing to do
+    #[cfg(not(any(feature = "hunspell", feature = "languagetool", feature = "nlprule")))]
+    let _ = cache;
 
     #[cfg(feature = "languagetool")]
     {
@@ -80,11 +464,38 @@ where
                 .languagetool
                 .as_ref()
                 .expect("Must be Some(LanguageToolConfig) if is_enabled returns true");
-            if let Ok(mut suggestions) =
-                self::languagetool::LanguageToolChecker::check(documentation, config)
-            {
-                collective.join(suggestions);
-            }
+            // a misconfigured server must not look like a clean result
+            let suggestions = run::<self::languagetool::LanguageToolChecker>(
+                documentation,
+                Detector::LanguageTool,
+                config,
+                Vec::new(),
+                cache,
+                progress,
+            )?;
+            collective.join(suggestions);
+        }
+    }
+
+    #[cfg(feature = "nlprule")]
+    {
+        if config.is_enabled(Detector::Nlprule) {
+            debug!("Running nlprule checks");
+            let config = config
+                .nlprule
+                .as_ref()
+                .expect("Must be Some(NlpruleConfig) if is_enabled returns true");
+            let files = vec![config.tokenizer().to_owned(), config.rules().to_owned()];
+            // missing or corrupt binaries must not look like a clean result
+            let suggestions = run::<self::nlprule::NlpruleChecker>(
+                documentation,
+                Detector::Nlprule,
+                config,
+                files,
+                cache,
+                progress,
+            )?;
+            collective.join(suggestions);
         }
     }
 
@@ -96,12 +507,74 @@ where
                 .hunspell
                 .as_ref()
                 .expect("Must be Some(HunspellConfig) if is_enabled returns true");
-            if let Ok(suggestions) = self::hunspell::HunspellChecker::check(documentation, config) {
+            let mut files = config.extra_dictonaries().to_vec();
+            for lang in std::iter::once(config.lang())
+                .chain(config.extra_langs().iter().map(String::as_str))
+            {
+                if let Some(dic) = config.dictionary_of(lang) {
+                    files.push(dic.with_extension("aff"));
+                    files.push(dic);
+                }
+            }
+            files.extend(config.word_frequencies().map(|path| path.to_owned()));
+            if let Ok(suggestions) = run::<self::hunspell::HunspellChecker>(
+                documentation,
+                Detector::Hunspell,
+                config,
+                files,
+                cache,
+                progress,
+            ) {
                 collective.join(suggestions);
             }
         }
     }
 
+    if config.is_enabled(Detector::Repetition) {
+        debug!("Running Repetition checks");
+        let config = config
+            .repetition
+            .as_ref()
+            .expect("Must be Some(RepetitionConfig) if is_enabled returns true");
+        // cheap enough to not bother with the cache
+        if let Ok(suggestions) = self::repetition::RepetitionChecker::check(documentation, config) {
+            collective.join(suggestions);
+        }
+        documentation.iter().for_each(|(path, _)| progress(path));
+    }
+
+    if config.is_enabled(Detector::Wordlist) {
+        debug!("Running Wordlist checks");
+        let config = config
+            .wordlist
+            .as_ref()
+            .expect("Must be Some(WordlistConfig) if is_enabled returns true");
+        if let Ok(suggestions) = self::wordlist::WordlistChecker::check(documentation, config) {
+            collective.join(suggestions);
+        }
+        documentation.iter().for_each(|(path, _)| progress(path));
+        // allowed words are correct, no matter which detector flagged them
+        match self::wordlist::Wordlist::load(config) {
+            Ok(wordlist) => collective.retain(|suggestion| {
+                crate::report::covered_text(suggestion)
+                    .map_or(true, |covered| !wordlist.is_allowed(covered.as_str()))
+            }),
+            Err(e) => warn!("Failed to load the allowed words: {}", e),
+        }
+    }
+
+    skip_short_words(&mut collective, config.min_word_length);
+
+    // independent of which detector found what, the order must be the same for every run
+    collective.sort();
+
+    // one location must only be presented once, even if multiple detectors flagged it
+    collective.merge_same_span();
+
+    // the closest replacements come first, so only the top ones are kept
+    collective.rank_replacements();
+    collective.truncate_replacements(config.max_replacements);
+
     Ok(collective)
 }
 
@@ -120,11 +593,345 @@ mod tests {
         ];
     }
 
+    #[test]
+    fn par_find_ordered() {
+        let txts = (0..64).map(|i| i.to_string()).collect::<Vec<_>>();
+        let txts = txts.iter().map(String::as_str).collect::<Vec<_>>();
+        let found = par_find(&txts, || (), |_, txt| txt.parse::<usize>().unwrap());
+        assert_eq!(found, (0..64).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn sorted_by_span() {
+        let docs = crate::testcase::documentation();
+        let set = crate::testcase::suggestion_set(&docs);
+        let (path, suggestions) = crate::testcase::first(set.iter());
+
+        // as if the detectors reported in a different order
+        let mut reversed = SuggestionSet::new();
+        reversed.extend(path.clone(), suggestions.iter().rev().cloned());
+        reversed.sort();
+        let spans = |set: &SuggestionSet| {
+            set.suggestions(path)
+                .map(|suggestion| suggestion.span)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(spans(&reversed), spans(&set));
+    }
+
+    #[test]
+    fn merge_same_span() {
+        let docs = crate::testcase::documentation();
+        let mut set = crate::testcase::suggestion_set(&docs);
+        let (path, suggestions) = crate::testcase::first(set.iter());
+        let path = path.clone();
+        let hunspell = suggestions[0].clone();
+        let partial = suggestions[1].clone();
+
+        // a grammar checker flagging the very same range
+        let mut grammar = hunspell.clone();
+        grammar.detector = Detector::LanguageTool;
+        grammar.replacements = vec!["type".to_owned(), "tip".to_owned()];
+        grammar.description = Some("Did you mean a different word?".to_owned());
+        set.add(path.clone(), grammar);
+        set.sort();
+        set.merge_same_span();
+
+        let merged = set.suggestions(&path).cloned().collect::<Vec<_>>();
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].detector, Detector::Hunspell);
+        assert_eq!(merged[0].span, hunspell.span);
+        assert_eq!(merged[0].replacements, vec!["typo", "type", "tip"]);
+        assert_eq!(
+            merged[0].description.as_deref(),
+            Some(
+                "Possible spelling mistake found. (Hunspell) \
+                 Did you mean a different word? (LanguageTool)"
+            )
+        );
+        // unrelated suggestions are untouched
+        assert_eq!(merged[1], partial);
+    }
+
+    #[test]
+    fn max_replacements() {
+        assert_eq!(Config::parse("").unwrap().max_replacements, 8);
+        let config = Config::parse("max_replacements = 5").unwrap();
+
+        let docs = crate::testcase::documentation();
+        let mut set = crate::testcase::suggestion_set(&docs);
+        let (path, suggestions) = crate::testcase::first(set.iter());
+        let mut suggestion = suggestions[0].clone();
+        suggestion.replacements = (0..20).map(|i| format!("typo{}", i)).collect();
+        let path = path.clone();
+        set.add(path.clone(), suggestion);
+
+        set.truncate_replacements(config.max_replacements);
+        let replacements = set
+            .suggestions(&path)
+            .map(|suggestion| suggestion.replacements.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(replacements[0], vec!["typo", "type"]);
+        assert_eq!(replacements[1], vec!["and"]);
+        assert_eq!(
+            replacements[2],
+            vec!["typo0", "typo1", "typo2", "typo3", "typo4"]
+        );
+    }
+
+    #[test]
+    fn min_word_length() {
+        assert_eq!(Config::parse("").unwrap().min_word_length, 2);
+        assert_eq!(
+            Config::parse("min_word_length = 4")
+                .unwrap()
+                .min_word_length,
+            4
+        );
+
+        let docs = crate::testcase::documentation();
+        let all = crate::testcase::suggestion_set(&docs);
+        let (path, suggestions) = crate::testcase::first(all.iter());
+        // the grammar finding covers the single character `&`, flagged as a typo too
+        let mut typo = suggestions[1].clone();
+        typo.detector = Detector::Hunspell;
+        let path = path.clone();
+        let flagged = |min_word_length: usize| {
+            let mut set = all.clone();
+            set.add(path.clone(), typo.clone());
+            skip_short_words(&mut set, min_word_length);
+            set.suggestions(&path)
+                .map(|suggestion| {
+                    (
+                        suggestion.detector,
+                        crate::report::covered_text(suggestion).unwrap(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            flagged(2),
+            vec![
+                (Detector::Hunspell, "tpyo".to_owned()),
+                (Detector::LanguageTool, "&".to_owned()),
+            ]
+        );
+        assert_eq!(flagged(5), vec![(Detector::LanguageTool, "&".to_owned())]);
+        assert_eq!(flagged(0).len(), 3);
+    }
+
+    #[test]
+    fn nearest_replacement_first() {
+        let docs = crate::testcase::documentation();
+        let all = crate::testcase::suggestion_set(&docs);
+        let (path, suggestions) = crate::testcase::first(all.iter());
+        // the flagged text is `tpyo`
+        let mut suggestion = suggestions[0].clone();
+        suggestion.replacements = vec!["tape", "type", "tyro", "typo"]
+            .into_iter()
+            .map(str::to_owned)
+            .collect();
+        let mut set = SuggestionSet::new();
+        set.add(path.clone(), suggestion);
+
+        set.rank_replacements();
+        let (_path, suggestions) = set.iter().next().unwrap();
+        // `type` and `tyro` are equidistant, so their order is retained
+        assert_eq!(
+            suggestions[0].replacements,
+            vec!["typo", "type", "tyro", "tape"]
+        );
+    }
+
     #[test]
     fn tokens() {
-        let ranges: Vec<Range> = tokenize(TEXT);
+        let ranges: Vec<Range> = tokenize(TEXT, DEFAULT_SEPARATORS, false);
         for (range, expect) in ranges.into_iter().zip(TOKENS.iter()) {
             assert_eq!(&&TEXT[range], expect);
         }
     }
+
+    macro_rules! tokenize_test {
+        ($name:ident, $text:literal => [$($expected:literal),* $(,)?]) => {
+            #[test]
+            fn $name() {
+                const TEXT: &str = $text;
+                let tokens = tokenize(TEXT, DEFAULT_SEPARATORS, false)
+                    .into_iter()
+                    .map(|range| &TEXT[range])
+                    .collect::<Vec<&str>>();
+                assert_eq!(tokens, vec![$($expected),*]);
+            }
+        };
+    }
+
+    macro_rules! verbatim_test {
+        ($name:ident, $verbatim:expr, $text:literal => [$($expected:literal),* $(,)?]) => {
+            #[test]
+            fn $name() {
+                const TEXT: &str = $text;
+                let tokens = tokenize_prose(TEXT, DEFAULT_SEPARATORS, false, $verbatim)
+                    .into_iter()
+                    .map(|range| &TEXT[range])
+                    .collect::<Vec<&str>>();
+                assert_eq!(tokens, vec![$($expected),*]);
+            }
+        };
+    }
+
+    const PATHS: Verbatim = Verbatim {
+        paths: true,
+        versions: false,
+        hex: false,
+    };
+    const VERSIONS: Verbatim = Verbatim {
+        paths: false,
+        versions: true,
+        hex: false,
+    };
+    const HEX: Verbatim = Verbatim {
+        paths: false,
+        versions: false,
+        hex: true,
+    };
+
+    verbatim_test!(verbatim_paths, PATHS, "See src/checker/mod.rs, ./target or ~/.config and/or C:\\tmp\\log.txt wiht care." => ["See", "or", "and", "or", "wiht", "care"]);
+    verbatim_test!(verbatim_paths_off, Verbatim::default(), "See src/checker/mod.rs" => ["See", "src", "checker", "mod", "rs"]);
+    verbatim_test!(verbatim_versions, VERSIONS, "Since v1.2.3 (or 0.4.0-alpha.1) it is fixd, not in 1.0." => ["Since", "or", "it", "is", "fixd", "not", "in"]);
+    verbatim_test!(verbatim_versions_only, VERSIONS, "A v1 or 0xDEADBEEF and src/lib.rs" => ["A", "v1", "or", "0xDEADBEEF", "and", "src", "lib", "rs"]);
+    verbatim_test!(verbatim_wide_whitespace, VERSIONS, "Since\u{a0}v1.2.3\u{3000}it is fixd\u{a0}in 1.0." => ["Since", "it", "is", "fixd", "in"]);
+    verbatim_test!(verbatim_hex, HEX, "Writes 0xDEADBEEF or 0x00_ff to the regsiter, not 0xGG." => ["Writes", "or", "to", "the", "regsiter", "not", "0xGG"]);
+
+    tokenize_test!(tokens_accented, "Un café naïve, très\u{A0}bien!" => ["Un", "café", "naïve", "très", "bien"]);
+    tokenize_test!(tokens_cjk, "日本語 and 中文." => ["日", "本", "語", "and", "中", "文"]);
+    tokenize_test!(tokens_contractions, "It's O'Brien, don't!" => ["It's", "O'Brien", "don't"]);
+    tokenize_test!(tokens_contractions_curly, "It’s rock’n’roll, don’t!" => ["It’s", "rock’n’roll", "don’t"]);
+    tokenize_test!(tokens_quoted, "A 'quoted' and ‘curly’ word, 'twas 1'000" => ["A", "quoted", "and", "curly", "word", "twas", "1", "000"]);
+    tokenize_test!(tokens_combining, "Cafe\u{301}s (plural)" => ["Cafe\u{301}s", "plural"]);
+    tokenize_test!(tokens_identifiers_unsplit, "A SuggestionSet and plain_range." => ["A", "SuggestionSet", "and", "plain_range"]);
+
+    #[test]
+    fn tokens_identifiers() {
+        const TEXT: &str = "Use SugestionSet, plain_range or HTTPServer in camelCase.";
+        let tokens = tokenize(TEXT, DEFAULT_SEPARATORS, true)
+            .into_iter()
+            .map(|range| &TEXT[range])
+            .collect::<Vec<&str>>();
+        assert_eq!(
+            tokens,
+            vec![
+                "Use",
+                "Sugestion",
+                "Set",
+                "plain",
+                "range",
+                "or",
+                "HTTP",
+                "Server",
+                "in",
+                "camel",
+                "Case"
+            ]
+        );
+        // the sub-word must map back to the original text
+        assert_eq!(tokenize(TEXT, DEFAULT_SEPARATORS, true)[1], 4..13);
+    }
+
+    #[test]
+    fn tokens_custom_separators() {
+        const TEXT: &str = "Either and/or, foo*bar, or e.g. this.";
+        let words = |separators: &str| {
+            tokenize(TEXT, separators, false)
+                .into_iter()
+                .map(|range| &TEXT[range])
+                .collect::<Vec<&str>>()
+        };
+        assert_eq!(
+            words(DEFAULT_SEPARATORS),
+            vec!["Either", "and", "or", "foo", "bar", "or", "e", "g", "this"]
+        );
+        // `/` is part of the word, `*` separates either way
+        let custom = DEFAULT_SEPARATORS.replace('/', "") + "*";
+        assert_eq!(
+            words(custom.as_str()),
+            vec!["Either", "and/or", "foo", "bar", "or", "e", "g", "this"]
+        );
+        assert_eq!(tokenize(TEXT, custom.as_str(), false)[1], 7..13);
+    }
+
+    #[test]
+    fn words_split() {
+        const TEXT: &str = "a+b x@y, 3*4 and/or (really) semi;colon";
+        let words = |separators: &str| {
+            split_words(TEXT, separators)
+                .into_iter()
+                .map(|range| &TEXT[range])
+                .collect::<Vec<&str>>()
+        };
+        // punctuation which is no default separator never joins words
+        assert_eq!(
+            words(DEFAULT_SEPARATORS),
+            vec!["a", "b", "x", "y", "3", "4", "and", "or", "really", "semi", "colon"]
+        );
+        // only separators removed from the defaults join words
+        assert_eq!(
+            words(""),
+            vec![
+                "a",
+                "b",
+                "x",
+                "y",
+                "3",
+                "4",
+                "and/or",
+                "really",
+                "semi;colon"
+            ]
+        );
+    }
+
+    #[test]
+    fn progress() {
+        let mut docs = Documentation::new();
+        for idx in 0..3 {
+            docs.join(
+                Documentation::load_from_str(
+                    format!("src/file{}.rs", idx),
+                    "/// A doc comment.\nstruct X;",
+                    &Config::default(),
+                )
+                .unwrap(),
+            );
+        }
+        let config = Config::default();
+        let mut checked = Vec::new();
+        check(&docs, &config, None, &mut |path| {
+            checked.push(path.to_owned())
+        })
+        .expect("Must check");
+        // every file once per detector, no matter in which order
+        assert_eq!(checked.len(), 3 * detectors(&config).count());
+        for (path, _) in docs.iter() {
+            assert_eq!(
+                checked.iter().filter(|checked| *checked == path).count(),
+                detectors(&config).count()
+            );
+        }
+    }
+
+    #[cfg(feature = "nlprule")]
+    #[test]
+    fn nlprule_misconfigured() {
+        let dir = crate::testcase::scratch_dir("nlprule");
+        let mut config = Config::parse(format!(
+            "[nlprule]\ntokenizer = {:?}\nrules = {:?}\n",
+            dir.join("en_tokenizer.bin"),
+            dir.join("en_rules.bin"),
+        ))
+        .unwrap();
+        config.hunspell = None;
+        let docs = crate::testcase::documentation();
+        assert!(check(&docs, &config, None, &mut |_path| ()).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
@@ -0,0 +1,103 @@
+use super::*;
+
+use crate::literalset::Range;
+use std::path::PathBuf;
+
+use anyhow::anyhow;
+use nlprule_rs::{Rules, Tokenizer};
+
+pub struct NlpruleChecker;
+
+impl Checker for NlpruleChecker {
+    type Config = crate::config::NlpruleConfig;
+    fn check<'a, 's>(docu: &'a Documentation, config: &Self::Config) -> Result<SuggestionSet<'s>>
+    where
+        'a: 's,
+    {
+        let tokenizer = Tokenizer::new(config.tokenizer()).map_err(|e| {
+            anyhow!(
+                "Failed to load nlprule tokenizer from {}",
+                config.tokenizer().display()
+            )
+            .context(e)
+        })?;
+        let rules = Rules::new(config.rules()).map_err(|e| {
+            anyhow!(
+                "Failed to load nlprule rules from {}",
+                config.rules().display()
+            )
+            .context(e)
+        })?;
+        let rendering = &config.rendering;
+        // rules are applied per block, see `erase_markdown_chunks`
+        let plains = docu
+            .iter()
+            .flat_map(|(path, literal_sets)| {
+                literal_sets.iter().flat_map(move |literal_set| {
+                    literal_set
+                        .erase_markdown_chunks(rendering)
+                        .into_iter()
+                        .map(move |plain| (path, plain))
+                })
+            })
+            .collect::<Vec<_>>();
+        let txts = plains
+            .iter()
+            .map(|(_path, plain)| plain.as_str())
+            .collect::<Vec<_>>();
+        let items = par_find(
+            &txts,
+            || (),
+            |_, txt| {
+                log::trace!("markdown erasure: {:?}", txt);
+                rules
+                    .suggest(txt, &tokenizer)
+                    .into_iter()
+                    .map(|item| {
+                        log::trace!("item.source: {:?}", item.source());
+                        log::trace!("item.message: {:?}", item.message());
+                        let range = item.span().byte();
+                        (
+                            Range {
+                                start: range.start,
+                                end: range.end,
+                            },
+                            item.replacements().to_vec(),
+                            item.message().to_owned(),
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            },
+        );
+
+        let mut suggestions = SuggestionSet::new();
+        for ((path, plain), items) in plains.iter().zip(items) {
+            for (range, replacements, message) in items {
+                // a finding across lines yields one fragment per line, only the
+                // first one carries the replacements, so a fix is applied once
+                for (idx, (literal, span)) in
+                    plain.linear_range_to_spans(range).into_iter().enumerate()
+                {
+                    suggestions.add(
+                        PathBuf::from(*path),
+                        Suggestion {
+                            detector: Detector::Nlprule,
+                            severity: Detector::Nlprule.severity(),
+                            span,
+                            path: PathBuf::from(*path),
+                            replacements: if idx == 0 {
+                                replacements.clone()
+                            } else {
+                                Vec::new()
+                            },
+                            literal: literal.into(),
+                            description: Some(message.clone()),
+                        },
+                    );
+                }
+            }
+        }
+
+        Ok(suggestions)
+    }
+}
@@ -0,0 +1,182 @@
+//! Ranking of replacement candidates provided by a checker.
+//!
+//! Candidates closer to the original word come first, the word frequency
+//! is used as tie breaker.
+
+use anyhow::{anyhow, Result};
+use log::trace;
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Occurrence counts of words, where a higher count means more common.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct WordFrequencies {
+    counts: HashMap<String, u64>,
+}
+
+impl WordFrequencies {
+    /// Parse a list of `word count` pairs, one per line.
+    ///
+    /// Empty lines and lines starting with `#` are ignored.
+    pub fn parse(s: &str) -> Result<Self> {
+        let mut counts = HashMap::with_capacity(1024);
+        for (lineno, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut it = line.split_whitespace();
+            let (word, count) = match (it.next(), it.next()) {
+                (Some(word), Some(count)) => (word, count),
+                _ => {
+                    return Err(anyhow!(
+                        "Line {} of the word frequency list is not a `word count` pair",
+                        lineno + 1
+                    ))
+                }
+            };
+            let count = count
+                .parse::<u64>()
+                .map_err(|e| anyhow!("Invalid word count in line {}", lineno + 1).context(e))?;
+            counts.insert(word.to_owned(), count);
+        }
+        Ok(Self { counts })
+    }
+
+    pub fn load_from<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            anyhow!("Failed to read word frequency list {}", path.display()).context(e)
+        })?;
+        Self::parse(content.as_str())
+    }
+
+    /// Frequency of `word`, falls back to the lowercase variant.
+    pub fn get(&self, word: &str) -> Option<u64> {
+        self.counts
+            .get(word)
+            .or_else(|| self.counts.get(&word.to_lowercase()))
+            .copied()
+    }
+}
+
+/// Levenshtein distance of two strings, counted in characters.
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<char>>();
+    let mut previous = (0..=b.len()).collect::<Vec<usize>>();
+    let mut current = vec![0usize; b.len() + 1];
+    for (i, ca) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + if ca == *cb { 0 } else { 1 };
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+/// Damerau-Levenshtein distance of two strings, counted in characters, where
+/// swapping two adjacent characters counts as a single edit.
+///
+/// This is the optimal string alignment variant, no substring is edited twice.
+pub(crate) fn damerau_distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<char>>();
+    let b = b.chars().collect::<Vec<char>>();
+    let mut d = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for j in 0..=b.len() {
+        d[0][j] = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+/// Order `replacements` for `word` by edit distance, equidistant candidates
+/// are ordered by their frequency.
+///
+/// Candidates without frequency data retain their relative order.
+pub(crate) fn rank_replacements(
+    word: &str,
+    replacements: &mut Vec<String>,
+    frequencies: &WordFrequencies,
+) {
+    replacements.sort_by_key(|replacement| {
+        (
+            edit_distance(word, replacement),
+            Reverse(frequencies.get(replacement).unwrap_or(0)),
+        )
+    });
+    trace!("Ranked replacements for {}: {:?}", word, replacements);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distance() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("café", "cafe"), 1);
+        assert_eq!(edit_distance("tset", "test"), 2);
+    }
+
+    #[test]
+    fn transpositions() {
+        assert_eq!(damerau_distance("", "abc"), 3);
+        assert_eq!(damerau_distance("teh", "the"), 1);
+        assert_eq!(damerau_distance("lenght", "length"), 1);
+        assert_eq!(damerau_distance("kitten", "sitting"), 3);
+        // no substring is edited twice
+        assert_eq!(damerau_distance("ca", "abc"), 3);
+    }
+
+    #[test]
+    fn single_edits() {
+        assert!(damerau_distance("lenght", "length") <= 1);
+        assert!(damerau_distance("cafe", "café") <= 1);
+        assert!(damerau_distance("cat", "cats") <= 1);
+        assert!(damerau_distance("tset", "sett") > 1);
+        assert!(damerau_distance("kitten", "sitting") > 1);
+        assert!(damerau_distance("abcd", "badc") > 1);
+    }
+
+    #[test]
+    fn frequency_tie_breaker() {
+        let frequencies = WordFrequencies::parse(
+            r#"
+# word count
+bat 120
+cat 9000
+"#,
+        )
+        .expect("Must parse");
+
+        let mut replacements = vec!["bat".to_owned(), "cat".to_owned(), "chart".to_owned()];
+        rank_replacements("dat", &mut replacements, &frequencies);
+        assert_eq!(replacements, vec!["cat", "bat", "chart"]);
+    }
+
+    #[test]
+    fn frequency_missing() {
+        let frequencies = WordFrequencies::default();
+        let mut replacements = vec!["bat".to_owned(), "cat".to_owned()];
+        rank_replacements("dat", &mut replacements, &frequencies);
+        assert_eq!(replacements, vec!["bat", "cat"]);
+    }
+}
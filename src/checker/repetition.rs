@@ -0,0 +1,176 @@
+//! Detects accidentally repeated words such as "the the".
+//!
+//! Works on the same tokens as the spell checker, so repetitions are also
+//! found across a line break.
+
+use super::{par_find, tokenize, Checker, Detector, Documentation, Suggestion, SuggestionSet};
+use crate::config::DEFAULT_SEPARATORS;
+use crate::Range;
+use std::path::PathBuf;
+
+use log::trace;
+
+use anyhow::Result;
+
+pub struct RepetitionChecker;
+
+impl Checker for RepetitionChecker {
+    type Config = crate::config::RepetitionConfig;
+    fn check<'a, 's>(docu: &'a Documentation, config: &Self::Config) -> Result<SuggestionSet<'s>>
+    where
+        'a: 's,
+    {
+        let plains = docu
+            .iter()
+            .flat_map(|(path, literal_sets)| {
+                literal_sets
+                    .iter()
+                    .map(move |literal_set| (path, literal_set.erase_markdown()))
+            })
+            .collect::<Vec<_>>();
+        let txts = plains
+            .iter()
+            .map(|(_path, plain)| plain.as_str())
+            .collect::<Vec<_>>();
+        let repetitions = par_find(
+            &txts,
+            || (),
+            |_, txt| {
+                find_repetitions(txt, config)
+                    .into_iter()
+                    .map(|(range, word)| (range, word.to_owned()))
+                    .collect::<Vec<_>>()
+            },
+        );
+
+        let mut suggestions = SuggestionSet::new();
+        for ((path, plain), repetitions) in plains.iter().zip(repetitions) {
+            for (range, word) in repetitions {
+                for (literal, span) in plain.linear_range_to_spans(range) {
+                    suggestions.add(
+                        PathBuf::from(*path),
+                        Suggestion {
+                            detector: Detector::Repetition,
+                            severity: Detector::Repetition.severity(),
+                            span,
+                            path: PathBuf::from(*path),
+                            replacements: vec![String::new()],
+                            literal: literal.into(),
+                            description: Some(format!("Repeated word `{}`.", word)),
+                        },
+                    )
+                }
+            }
+        }
+        Ok(suggestions)
+    }
+}
+
+/// Find all words in `txt` which are directly preceded by the same word,
+/// ignoring case.
+///
+/// The returned ranges cover the repeated word and the whitespace separating
+/// it from the first one, so removing them leaves a single word. If both are
+/// on different lines, the whitespace following the repeated word is covered
+/// instead.
+fn find_repetitions<'t>(
+    txt: &'t str,
+    config: &crate::config::RepetitionConfig,
+) -> Vec<(Range, &'t str)> {
+    let mut repetitions = Vec::new();
+    let tokens = tokenize(txt, DEFAULT_SEPARATORS, false);
+    for (previous, current) in tokens.iter().zip(tokens.iter().skip(1)) {
+        let word = &txt[current.clone()];
+        if !word.chars().any(char::is_alphabetic) {
+            continue;
+        }
+        if txt[previous.clone()].to_lowercase() != word.to_lowercase() {
+            continue;
+        }
+        // any punctuation in between, i.e. "Yes, yes", is deliberate
+        let gap = &txt[previous.end..current.start];
+        if !gap.chars().all(char::is_whitespace) {
+            continue;
+        }
+        if config.is_ignored(word) {
+            trace!("Ignoring intentional repetition of >{}<", word);
+            continue;
+        }
+        let range = if gap.contains('\n') {
+            let trailing = txt[current.end..]
+                .chars()
+                .take_while(|c| *c == ' ' || *c == '\t')
+                .count();
+            current.start..(current.end + trailing)
+        } else {
+            previous.end..current.end
+        };
+        trace!("Repeated word (plain range: {:?}): >{}<", &range, word);
+        repetitions.push((range, word));
+    }
+    repetitions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RepetitionConfig;
+    use crate::report::covered_text;
+    use crate::{Config, Documentation};
+
+    fn repetitions(txt: &str) -> Vec<&str> {
+        find_repetitions(txt, &RepetitionConfig::default())
+            .into_iter()
+            .map(|(range, _word)| &txt[range])
+            .collect()
+    }
+
+    #[test]
+    fn same_line() {
+        assert_eq!(repetitions("Check the the spelling."), vec![" the"]);
+        assert_eq!(repetitions("It is Is it?"), vec![" Is"]);
+        assert_eq!(repetitions("No repetition at all."), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn punctuation() {
+        assert_eq!(repetitions("Yes, yes."), Vec::<&str>::new());
+        assert_eq!(repetitions("It ends. Ends"), Vec::<&str>::new());
+        assert_eq!(repetitions("Numbers 1 1 are fine."), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn newline() {
+        assert_eq!(repetitions("Check the\nthe spelling."), vec!["the "]);
+
+        let docs = Documentation::load_from_str(
+            "lib.rs",
+            "/// Check the\n/// the spelling.\nstruct X;",
+            &Config::default(),
+        )
+        .expect("Must parse");
+        let set = RepetitionChecker::check(&docs, &RepetitionConfig::default()).unwrap();
+        let (_path, suggestions) = crate::testcase::first(set.iter());
+        assert_eq!(suggestions.len(), 1);
+        let suggestion = &suggestions[0];
+        assert_eq!(suggestion.detector, Detector::Repetition);
+        assert_eq!(suggestion.span.start.line, 2);
+        assert_eq!(covered_text(suggestion).as_deref(), Some("the "));
+        assert_eq!(suggestion.replacements, vec![String::new()]);
+    }
+
+    #[test]
+    fn ignored() {
+        assert_eq!(repetitions("He had had enough."), Vec::<&str>::new());
+        let config = Config::parse(
+            r#"
+[repetition]
+ignore = ["bye"]
+"#,
+        )
+        .unwrap();
+        let config = config.repetition.expect("Must be enabled");
+        assert!(find_repetitions("Bye bye now.", &config).is_empty());
+        assert_eq!(find_repetitions("He had had enough.", &config).len(), 1);
+    }
+}
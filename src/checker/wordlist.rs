@@ -0,0 +1,190 @@
+//! Project local lists of allowed and denied words.
+//!
+//! Allowed words are never flagged by any detector, denied words are always
+//! flagged, optionally with a replacement.
+
+use super::{par_find, tokenize, Checker, Detector, Documentation, Suggestion, SuggestionSet};
+use crate::config::{WordlistConfig, DEFAULT_SEPARATORS};
+use std::path::{Path, PathBuf};
+
+use log::trace;
+
+use anyhow::{anyhow, Result};
+
+use std::collections::{HashMap, HashSet};
+
+/// Parsed allow and deny lists.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Wordlist {
+    allow: HashSet<String>,
+    deny: HashMap<String, Option<String>>,
+}
+
+/// Lines of a word list, empty lines and lines starting with `#` are ignored.
+fn entries(s: &str) -> impl Iterator<Item = &str> {
+    s.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+}
+
+fn read(path: &Path) -> Result<String> {
+    std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read word list {}", path.display()).context(e))
+}
+
+impl Wordlist {
+    /// Parse the allowed words, one per line, and the denied words, one per
+    /// line with an optional `= replacement`.
+    pub fn parse(allow: &str, deny: &str) -> Self {
+        let allow = entries(allow).map(|word| word.to_owned()).collect();
+        let deny = entries(deny)
+            .map(|line| {
+                let mut it = line.splitn(2, '=');
+                let word = it.next().unwrap_or_default().trim().to_owned();
+                let replacement = it
+                    .next()
+                    .map(str::trim)
+                    .filter(|replacement| !replacement.is_empty())
+                    .map(|replacement| replacement.to_owned());
+                (word, replacement)
+            })
+            .collect();
+        Self { allow, deny }
+    }
+
+    pub fn load(config: &WordlistConfig) -> Result<Self> {
+        let allow = config.allow().map(read).transpose()?.unwrap_or_default();
+        let deny = config.deny().map(read).transpose()?.unwrap_or_default();
+        Ok(Self::parse(allow.as_str(), deny.as_str()))
+    }
+
+    /// Lookup `word`, falls back to the lowercase variant.
+    fn lookup<T>(word: &str, contains: impl Fn(&str) -> Option<T>) -> Option<T> {
+        contains(word).or_else(|| contains(&word.to_lowercase()))
+    }
+
+    pub fn is_allowed(&self, word: &str) -> bool {
+        Self::lookup(word, |word| self.allow.get(word)).is_some()
+    }
+
+    /// The replacement of a denied `word`, `Some(None)` if there is none.
+    pub fn denied(&self, word: &str) -> Option<Option<&str>> {
+        Self::lookup(word, |word| self.deny.get(word)).map(|replacement| replacement.as_deref())
+    }
+}
+
+pub struct WordlistChecker;
+
+impl Checker for WordlistChecker {
+    type Config = WordlistConfig;
+    fn check<'a, 's>(docu: &'a Documentation, config: &Self::Config) -> Result<SuggestionSet<'s>>
+    where
+        'a: 's,
+    {
+        let wordlist = Wordlist::load(config)?;
+        let plains = docu
+            .iter()
+            .flat_map(|(path, literal_sets)| {
+                literal_sets
+                    .iter()
+                    .map(move |literal_set| (path, literal_set.erase_markdown()))
+            })
+            .collect::<Vec<_>>();
+        let txts = plains
+            .iter()
+            .map(|(_path, plain)| plain.as_str())
+            .collect::<Vec<_>>();
+        let denied = par_find(
+            &txts,
+            || (),
+            |_, txt| {
+                tokenize(txt, DEFAULT_SEPARATORS, false)
+                    .into_iter()
+                    .filter_map(|range| {
+                        let word = &txt[range.clone()];
+                        let replacement = wordlist.denied(word)?;
+                        trace!("Denied word (plain range: {:?}): >{}<", &range, word);
+                        Some((range, word.to_owned(), replacement.map(str::to_owned)))
+                    })
+                    .collect::<Vec<_>>()
+            },
+        );
+
+        let mut suggestions = SuggestionSet::new();
+        for ((path, plain), denied) in plains.iter().zip(denied) {
+            for (range, word, replacement) in denied {
+                for (literal, span) in plain.linear_range_to_spans(range) {
+                    suggestions.add(
+                        PathBuf::from(*path),
+                        Suggestion {
+                            detector: Detector::Wordlist,
+                            severity: Detector::Wordlist.severity(),
+                            span,
+                            path: PathBuf::from(*path),
+                            replacements: replacement.iter().cloned().collect(),
+                            literal: literal.into(),
+                            description: Some(format!("The word `{}` is on the deny list.", word)),
+                        },
+                    )
+                }
+            }
+        }
+        Ok(suggestions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Config, Documentation};
+
+    #[test]
+    fn parse() {
+        let wordlist = Wordlist::parse(
+            "# project lingo\nfoobarization\n\nKuabe\n",
+            "utilize = use\nwhitelist = allowlist\nsimply\n",
+        );
+        assert!(wordlist.is_allowed("foobarization"));
+        assert!(wordlist.is_allowed("Foobarization"));
+        assert!(wordlist.is_allowed("Kuabe"));
+        assert!(!wordlist.is_allowed("kuabe"));
+        assert!(!wordlist.is_allowed("# project lingo"));
+        assert_eq!(wordlist.denied("Utilize"), Some(Some("use")));
+        assert_eq!(wordlist.denied("simply"), Some(None));
+        assert_eq!(wordlist.denied("use"), None);
+    }
+
+    #[test]
+    fn deny() {
+        let dir = crate::testcase::scratch_dir("wordlist");
+        std::fs::write(dir.join("deny.txt"), "utilize = use\n").unwrap();
+        std::fs::write(
+            dir.join("spellcheck.toml"),
+            "[wordlist]\ndeny = \"deny.txt\"\n",
+        )
+        .unwrap();
+
+        // relative to the configuration file
+        let config = Config::load_from(dir.join("spellcheck.toml")).unwrap();
+        let config = config.wordlist.expect("Must be enabled");
+        assert_eq!(config.deny(), Some(dir.join("deny.txt").as_path()));
+
+        let docs = Documentation::load_from_str(
+            "lib.rs",
+            "/// We utilize the cache.\nstruct X;",
+            &Config::default(),
+        )
+        .expect("Must parse");
+        let set = WordlistChecker::check(&docs, &config).unwrap();
+        let (_path, suggestions) = crate::testcase::first(set.iter());
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].detector, Detector::Wordlist);
+        assert_eq!(
+            crate::report::covered_text(&suggestions[0]).as_deref(),
+            Some("utilize")
+        );
+        assert_eq!(suggestions[0].replacements, vec!["use".to_owned()]);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
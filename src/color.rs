@@ -0,0 +1,81 @@
+//! Whether styled output is colored.
+//!
+//! A single decision at startup, honoring `--color`, the `NO_COLOR`
+//! environment variable and whether the output is a terminal. It applies
+//! to all `console` styles, the interactive mode follows it too.
+
+use serde::Deserialize;
+
+/// When to color the output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum ColorChoice {
+    /// Color if the output is a terminal and `NO_COLOR` is not set.
+    Auto,
+    /// Always color, even if `NO_COLOR` is set or the output is piped.
+    Always,
+    /// Never color.
+    Never,
+}
+
+impl Default for ColorChoice {
+    fn default() -> Self {
+        Self::Auto
+    }
+}
+
+impl ColorChoice {
+    /// Decide for a single output stream, where `terminal` tells if the
+    /// stream would be colored by default.
+    pub fn enabled(&self, no_color: bool, terminal: bool) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Never => false,
+            Self::Auto => terminal && !no_color,
+        }
+    }
+
+    /// Apply the decision to stdout and stderr for the rest of the process.
+    pub fn apply(&self) {
+        // any value but an empty one disables colors, see https://no-color.org
+        let no_color = std::env::var_os("NO_COLOR").map_or(false, |value| !value.is_empty());
+        // the defaults already account for the terminal and `CLICOLOR`
+        console::set_colors_enabled(self.enabled(no_color, console::colors_enabled()));
+        console::set_colors_enabled_stderr(
+            self.enabled(no_color, console::colors_enabled_stderr()),
+        );
+    }
+}
+
+/// If styled output to stdout is colored.
+pub fn colors_enabled() -> bool {
+    console::colors_enabled()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SuggestionSet;
+
+    #[test]
+    fn decision() {
+        assert!(ColorChoice::Auto.enabled(false, true));
+        assert!(!ColorChoice::Auto.enabled(true, true));
+        assert!(!ColorChoice::Auto.enabled(false, false));
+        assert!(ColorChoice::Always.enabled(true, false));
+        assert!(!ColorChoice::Never.enabled(false, true));
+    }
+
+    #[test]
+    fn plain_ascii() {
+        ColorChoice::Never.apply();
+        let docs = crate::testcase::documentation();
+        let set: SuggestionSet = crate::testcase::suggestion_set(&docs);
+        for (_path, suggestions) in set.iter() {
+            for suggestion in suggestions {
+                let printed = suggestion.to_string();
+                assert!(printed.is_ascii(), "{:?}", printed);
+                assert!(!printed.contains('\x1b'));
+            }
+        }
+    }
+}
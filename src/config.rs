@@ -1,14 +1,17 @@
 //! Configure cargo-spellcheck
 //!
-//! Supports `Hunspell` and `LanguageTool` scopes.
+//! Supports `Hunspell`, `LanguageTool`, `Nlprule`, `Repetition` and `Wordlist` scopes.
 //!
 //! A default configuration will be generated in the default
 //! location by default. Default. Default default default.
 
-use crate::suggestion::Detector;
+use crate::checker::Verbatim;
+use crate::markdown::Rendering;
+use crate::suggestion::{Detector, Severity, Suggestion};
 use anyhow::{anyhow, Error, Result};
-use log::trace;
+use log::{trace, warn};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs::File;
 use std::io::Read;
 use std::io::Write;
@@ -16,16 +19,188 @@ use std::path::{Path, PathBuf};
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct Config {
+    /// Which comments are extracted from source files.
+    // must precede any tables, otherwise serialization to toml fails
+    #[serde(default)]
+    pub comment_scope: CommentScope,
+    /// Names of attributes with prose string arguments, i.e. `error` for `#[error("..")]`.
+    #[serde(default)]
+    pub attributes: Vec<String>,
+    /// Create a `.bak` copy of each file before applying any fixes.
+    #[serde(default)]
+    pub backup: Backup,
+    /// Append every applied correction to this file, as JSON lines.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audit_log: Option<PathBuf>,
+    /// Maximum number of replacements kept per suggestion.
+    #[serde(default = "default_max_replacements")]
+    pub max_replacements: usize,
+    /// Words shorter than this many characters are never flagged as misspelled.
+    #[serde(default = "default_min_word_length")]
+    pub min_word_length: usize,
+    /// Columns a tab advances to the next tab stop in the printed source lines.
+    #[serde(default = "default_tab_width")]
+    pub tab_width: usize,
+    /// Walk directories without a manifest with multiple threads.
+    #[serde(default)]
+    pub parallel_discovery: bool,
+    /// Also walk files ignored by `.gitignore` and `.ignore` files.
+    #[serde(default)]
+    pub no_ignore: bool,
+    /// Regular expressions, suggestions for text matching any of them are dropped.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignore_patterns: Vec<String>,
+    /// Only check discovered files matching any of these globs, all if empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
+    /// Never check discovered files matching any of these globs, even if included.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+    // tables must follow all plain values to remain serializable as toml
+    /// Treat files matching a glob as the given kind of document, i.e. `*.md.tpl` as markdown.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub document_kinds: BTreeMap<String, DocumentKind>,
     pub hunspell: Option<HunspellConfig>,
     pub languagetool: Option<LanguageToolConfig>,
+    pub nlprule: Option<NlpruleConfig>,
+    pub repetition: Option<RepetitionConfig>,
+    pub wordlist: Option<WordlistConfig>,
+    /// When findings result in a non-zero exit code.
+    #[serde(default)]
+    pub exit: ExitConfig,
+}
+
+fn default_max_replacements() -> usize {
+    8
+}
+
+fn default_min_word_length() -> usize {
+    2
+}
+
+fn default_tab_width() -> usize {
+    crate::DEFAULT_TAB_WIDTH
+}
+
+/// Selects if and how backups are created before fixes are written.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Backup {
+    /// No backups.
+    Off,
+    /// Create a backup, unless there is one already.
+    Preserve,
+    /// Create a backup, replacing any existing one.
+    Overwrite,
+}
+
+impl Default for Backup {
+    fn default() -> Self {
+        Self::Off
+    }
+}
+
+/// Selects which kind of comments are checked.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CommentScope {
+    /// Only doc comments, i.e. `///` and `//!`.
+    Doc,
+    /// Doc comments as well as regular `//` comments.
+    All,
+}
+
+impl Default for CommentScope {
+    fn default() -> Self {
+        Self::Doc
+    }
+}
+
+/// Characters separating two words, besides whitespace.
+pub const DEFAULT_SEPARATORS: &str = "\";:,.?!#(){}[]-\n\r/`";
+
+/// The kind of a document, which determines how the file content is parsed.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DocumentKind {
+    /// Rust source, the comments are checked.
+    Rust,
+    /// Markdown, the rendered text is checked.
+    Markdown,
+    /// Plain text, the content is checked as is.
+    Text,
+    /// reStructuredText, the rendered text is checked.
+    #[serde(rename = "rst", alias = "restructuredtext")]
+    ReStructuredText,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct HunspellConfig {
     pub lang: Option<String>, // TODO impl a custom xx_YY code deserializer based on iso crates
+    /// Further languages, a word is correct if any of the dictionaries accepts it.
+    ///
+    /// Replacements are only provided by the dictionary of `lang`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_langs: Vec<String>,
     // must be option so it can be omitted in the config
     pub search_dirs: Option<Vec<PathBuf>>,
     pub extra_dictonaries: Option<Vec<PathBuf>>,
+    /// A list of `word count` pairs, used to rank equally good replacements.
+    pub word_frequencies: Option<PathBuf>,
+    /// Split identifiers such as `camelCase` or `snake_case` into their sub-words.
+    #[serde(default)]
+    pub split_identifiers: bool,
+    /// Which tokens containing digits are not checked at all.
+    #[serde(default)]
+    pub skip_digits: SkipDigits,
+    /// Characters which separate words, all other punctuation is considered part of a word.
+    pub separators: Option<String>,
+    /// Also check the sub-words of identifiers in inline code, which are only
+    /// flagged if a dictionary word is a single edit away, i.e. `calc_lenght`.
+    #[serde(default)]
+    pub check_code: bool,
+    /// Adjustments of the plain text the dictionaries are applied to.
+    #[serde(flatten)]
+    pub rendering: Rendering,
+    /// Skip file system paths, such as `src/lib.rs` or `./target`, as a whole.
+    #[serde(default)]
+    pub skip_paths: bool,
+    /// Skip version numbers, such as `v1.2.3`, as a whole.
+    #[serde(default)]
+    pub skip_versions: bool,
+    /// Skip hexadecimal literals, such as `0xDEADBEEF`, as a whole.
+    #[serde(default)]
+    pub skip_hex: bool,
+}
+
+/// Selects which tokens containing digits are skipped.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SkipDigits {
+    /// Check all tokens, strict mode.
+    Off,
+    /// Skip tokens containing both digits and letters, such as `utf8` or `sha256`.
+    Mixed,
+    /// Skip all tokens containing any digits.
+    Any,
+}
+
+impl Default for SkipDigits {
+    fn default() -> Self {
+        Self::Mixed
+    }
+}
+
+impl SkipDigits {
+    /// Check if the token `word` is supposed to be skipped.
+    pub fn skip(&self, word: &str) -> bool {
+        let digits = word.chars().any(char::is_numeric);
+        match self {
+            Self::Off => false,
+            Self::Mixed => digits && word.chars().any(char::is_alphabetic),
+            Self::Any => digits,
+        }
+    }
 }
 
 impl HunspellConfig {
@@ -57,7 +232,51 @@ impl HunspellConfig {
         }
     }
 
+    pub fn separators(&self) -> &str {
+        if let Some(ref separators) = self.separators {
+            separators.as_str()
+        } else {
+            DEFAULT_SEPARATORS
+        }
+    }
+
+    pub fn word_frequencies(&self) -> Option<&Path> {
+        self.word_frequencies.as_ref().map(|path| path.as_path())
+    }
+
+    /// Chunks of text which are skipped as a whole, instead of being split
+    /// into words.
+    pub fn verbatim(&self) -> Verbatim {
+        Verbatim {
+            paths: self.skip_paths,
+            versions: self.skip_versions,
+            hex: self.skip_hex,
+        }
+    }
+
+    pub fn extra_langs(&self) -> &[String] {
+        self.extra_langs.as_slice()
+    }
+
+    /// The first dictionary of `lang` found in any of the search dirs.
+    pub fn dictionary(&self) -> Option<PathBuf> {
+        self.dictionary_of(self.lang())
+    }
+
+    /// The first dictionary of the given language found in any of the search dirs.
+    pub fn dictionary_of(&self, lang: &str) -> Option<PathBuf> {
+        self.search_dirs()
+            .iter()
+            .map(|search_dir| search_dir.join(lang).with_extension("dic"))
+            .find(|dic| dic.is_file())
+    }
+
     pub fn sanitize_paths(&mut self, base: &Path) -> Result<()> {
+        if let Some(ref mut path) = &mut self.word_frequencies {
+            if !path.is_absolute() {
+                *path = base.join(path.clone());
+            }
+        }
         if let Some(ref mut search_dirs) = &mut self.search_dirs {
             for path in search_dirs.iter_mut() {
                 let abspath = if !path.is_absolute() {
@@ -81,12 +300,160 @@ impl HunspellConfig {
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct LanguageToolConfig {
-    pub url: url::Url,
+    /// Address of the server, i.e. `https://api.languagetoolplus.com`.
+    #[serde(default = "default_languagetool_server", alias = "url")]
+    pub server: url::Url,
+    /// Port of the server, overrides any port given with `server`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    /// Username of the account the `api_key` belongs to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    /// API key of the premium API, requires `username`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    /// Adjustments of the plain text the server checks.
+    #[serde(flatten)]
+    pub rendering: Rendering,
+}
+
+fn default_languagetool_server() -> url::Url {
+    url::Url::parse("http://127.0.0.1:8010").expect("Default ip must be ok")
+}
+
+impl Default for LanguageToolConfig {
+    fn default() -> Self {
+        Self {
+            server: default_languagetool_server(),
+            port: None,
+            username: None,
+            api_key: None,
+            rendering: Rendering::default(),
+        }
+    }
 }
 
 impl LanguageToolConfig {
-    pub fn url(&self) -> &url::Url {
-        &self.url
+    /// The url of the server, including the configured port.
+    pub fn url(&self) -> url::Url {
+        let mut url = self.server.clone();
+        if let Some(port) = self.port {
+            if url.set_port(Some(port)).is_err() {
+                warn!("Ignoring port {} of LanguageTool server {}", port, url);
+            }
+        }
+        url
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct NlpruleConfig {
+    /// Path to the binary tokenizer data, i.e. `en_tokenizer.bin`.
+    pub tokenizer: PathBuf,
+    /// Path to the binary rules data, i.e. `en_rules.bin`.
+    pub rules: PathBuf,
+    /// Adjustments of the plain text the rules are applied to.
+    #[serde(flatten)]
+    pub rendering: Rendering,
+}
+
+impl NlpruleConfig {
+    pub fn tokenizer(&self) -> &Path {
+        self.tokenizer.as_path()
+    }
+
+    pub fn rules(&self) -> &Path {
+        self.rules.as_path()
+    }
+
+    pub fn sanitize_paths(&mut self, base: &Path) -> Result<()> {
+        for path in vec![&mut self.tokenizer, &mut self.rules] {
+            if !path.is_absolute() {
+                *path = base.join(path.clone());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(default)]
+pub struct RepetitionConfig {
+    /// Words which are repeated on purpose, i.e. `had` in "he had had enough".
+    pub ignore: Vec<String>,
+}
+
+impl Default for RepetitionConfig {
+    fn default() -> Self {
+        Self {
+            ignore: vec!["had".to_owned(), "that".to_owned()],
+        }
+    }
+}
+
+impl RepetitionConfig {
+    pub fn is_ignored(&self, word: &str) -> bool {
+        self.ignore
+            .iter()
+            .any(|ignored| ignored.to_lowercase() == word.to_lowercase())
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct WordlistConfig {
+    /// Newline delimited list of words which are always correct.
+    pub allow: Option<PathBuf>,
+    /// List of words which are always flagged, one per line, optionally
+    /// followed by `= replacement`.
+    pub deny: Option<PathBuf>,
+}
+
+impl WordlistConfig {
+    pub fn allow(&self) -> Option<&Path> {
+        self.allow.as_ref().map(|path| path.as_path())
+    }
+
+    pub fn deny(&self) -> Option<&Path> {
+        self.deny.as_ref().map(|path| path.as_path())
+    }
+
+    pub fn sanitize_paths(&mut self, base: &Path) -> Result<()> {
+        for path in self.allow.iter_mut().chain(self.deny.iter_mut()) {
+            if !path.is_absolute() {
+                *path = base.join(path.clone());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(default)]
+pub struct ExitConfig {
+    /// Print the findings, but never fail because of them.
+    pub report_only: bool,
+    /// Only findings at least this severe fail.
+    pub threshold: Severity,
+    /// Only findings of these detectors fail, all if empty.
+    pub detectors: Vec<Detector>,
+}
+
+impl Default for ExitConfig {
+    fn default() -> Self {
+        Self {
+            report_only: false,
+            threshold: Severity::Warning,
+            detectors: Vec::new(),
+        }
+    }
+}
+
+impl ExitConfig {
+    /// If `suggestion` results in a non-zero exit code.
+    pub fn fails(&self, suggestion: &Suggestion) -> bool {
+        !self.report_only
+            && suggestion.severity >= self.threshold
+            && (self.detectors.is_empty() || self.detectors.contains(&suggestion.detector))
     }
 }
 
@@ -101,6 +468,12 @@ impl Config {
         if let Some(ref mut hunspell) = self.hunspell {
             hunspell.sanitize_paths(base)?;
         }
+        if let Some(ref mut nlprule) = self.nlprule {
+            nlprule.sanitize_paths(base)?;
+        }
+        if let Some(ref mut wordlist) = self.wordlist {
+            wordlist.sanitize_paths(base)?;
+        }
         Ok(())
     }
 
@@ -179,6 +552,19 @@ impl Config {
         Self::default().write_values_to_path(path)
     }
 
+    /// Directory to store cached checker results in.
+    pub fn cache_dir() -> Result<PathBuf> {
+        if let Some(base) =
+            directories::ProjectDirs::from(Self::QUALIFIER, Self::ORGANIZATION, Self::APPLICATION)
+        {
+            Ok(base.cache_dir().to_owned())
+        } else {
+            Err(anyhow!(
+                "No idea where your cache directory is located. `$HOME` must be set."
+            ))
+        }
+    }
+
     pub fn default_path() -> Result<PathBuf> {
         if let Some(base) =
             directories::ProjectDirs::from(Self::QUALIFIER, Self::ORGANIZATION, Self::APPLICATION)
@@ -196,19 +582,162 @@ impl Config {
         Self::write_default_values_to(d.join("config.toml"))
     }
 
+    /// Lookup the document kind of `path` as configured by `document_kinds`.
+    ///
+    /// A glob matches either the file name or the full path.
+    pub fn document_kind(&self, path: &Path) -> Option<DocumentKind> {
+        let file_name = path.file_name().map(Path::new);
+        self.document_kinds
+            .iter()
+            .find_map(|(glob, kind)| match glob::Pattern::new(glob) {
+                Ok(pattern) => {
+                    let matches = pattern.matches_path(path)
+                        || file_name.map_or(false, |file_name| pattern.matches_path(file_name));
+                    if matches {
+                        Some(*kind)
+                    } else {
+                        None
+                    }
+                }
+                Err(e) => {
+                    warn!("Invalid glob {} in document kinds: {}", glob, e);
+                    None
+                }
+            })
+    }
+
+    /// Describe everything that makes a word pass without being flagged.
+    ///
+    /// Lists the dictionaries including all words of the extra dictionaries,
+    /// the patterns which are never checked and the enabled heuristics.
+    pub fn dump_accepted(&self) -> Result<String> {
+        use std::fmt::Write;
+
+        let mut dump = String::with_capacity(1024);
+        let _ = writeln!(dump, "comment scope: {:?}", self.comment_scope);
+
+        if let Some(allow) = self.wordlist.as_ref().and_then(|wordlist| wordlist.allow()) {
+            let content = std::fs::read_to_string(allow).map_err(|e| {
+                anyhow!("Failed to read allowed words {}", allow.display()).context(e)
+            })?;
+            let _ = writeln!(dump, "allowed words ({}):", allow.display());
+            for word in content
+                .lines()
+                .map(str::trim)
+                .filter(|word| !word.is_empty())
+            {
+                let _ = writeln!(dump, "  {}", word);
+            }
+        }
+
+        let hunspell = self.hunspell.as_ref();
+        if let Some(hunspell) = hunspell {
+            let _ = writeln!(dump, "dictionaries:");
+            match hunspell.dictionary() {
+                Some(dic) => {
+                    let _ = writeln!(dump, "  {} ({})", hunspell.lang(), dic.display());
+                }
+                None => {
+                    let _ = writeln!(dump, "  {} (not found)", hunspell.lang());
+                }
+            }
+            for lang in hunspell.extra_langs() {
+                match hunspell.dictionary_of(lang) {
+                    Some(dic) => {
+                        let _ = writeln!(dump, "  {} ({})", lang, dic.display());
+                    }
+                    None => {
+                        let _ = writeln!(dump, "  {} (not found)", lang);
+                    }
+                }
+            }
+
+            for extra_dic in hunspell.extra_dictonaries() {
+                let content = std::fs::read_to_string(extra_dic).map_err(|e| {
+                    anyhow!("Failed to read extra dictionary {}", extra_dic.display()).context(e)
+                })?;
+                let _ = writeln!(dump, "extra words ({}):", extra_dic.display());
+                // the first line of a `.dic` file may contain the number of entries
+                for word in content
+                    .lines()
+                    .map(|line| line.split('/').next().unwrap_or_default().trim())
+                    .filter(|word| !word.is_empty() && !word.chars().all(|c| c.is_ascii_digit()))
+                {
+                    let _ = writeln!(dump, "  {}", word);
+                }
+            }
+        } else {
+            let _ = writeln!(dump, "hunspell: disabled");
+        }
+
+        let _ = writeln!(dump, "ignored patterns:");
+        for attribute in self.attributes.iter() {
+            let _ = writeln!(dump, "  {{..}} within #[{}(..)]", attribute);
+        }
+        for pattern in self.ignore_patterns.iter() {
+            let _ = writeln!(dump, "  words matching `{}`", pattern);
+        }
+        if self.min_word_length > 1 {
+            let _ = writeln!(
+                dump,
+                "  words shorter than {} characters",
+                self.min_word_length
+            );
+        }
+        let hunspell = if let Some(hunspell) = hunspell {
+            hunspell
+        } else {
+            return Ok(dump);
+        };
+        match hunspell.skip_digits {
+            SkipDigits::Off => {}
+            SkipDigits::Mixed => {
+                let _ = writeln!(dump, "  tokens with both digits and letters");
+            }
+            SkipDigits::Any => {
+                let _ = writeln!(dump, "  tokens with digits");
+            }
+        }
+        if hunspell.skip_paths {
+            let _ = writeln!(dump, "  paths such as `src/lib.rs`");
+        }
+        if hunspell.skip_versions {
+            let _ = writeln!(dump, "  version numbers such as `v1.2.3`");
+        }
+        if hunspell.skip_hex {
+            let _ = writeln!(dump, "  hex literals such as `0xDEADBEEF`");
+        }
+        if hunspell.rendering.skip_math {
+            let _ = writeln!(dump, "  math such as `$x^2$`");
+        }
+
+        let _ = writeln!(dump, "heuristics:");
+        let _ = writeln!(dump, "  skip_digits: {:?}", hunspell.skip_digits);
+        let _ = writeln!(dump, "  split_identifiers: {}", hunspell.split_identifiers);
+        let _ = writeln!(dump, "  separators: {:?}", hunspell.separators());
+        let _ = writeln!(
+            dump,
+            "  word_frequencies: {}",
+            hunspell
+                .word_frequencies()
+                .map_or_else(|| "none".to_owned(), |path| path.display().to_string())
+        );
+        Ok(dump)
+    }
+
     pub fn is_enabled(&self, detector: Detector) -> bool {
         match detector {
             Detector::Hunspell => self.hunspell.is_some(),
             Detector::LanguageTool => self.languagetool.is_some(),
+            Detector::Nlprule => self.nlprule.is_some(),
+            Detector::Repetition => self.repetition.is_some(),
+            Detector::Wordlist => self.wordlist.is_some(),
         }
     }
 
     pub fn full() -> Self {
-        let languagetool = LanguageToolConfig {
-            url: url::Url::parse("http://127.0.0.1:8010").expect("Default ip must be ok"),
-        };
         Self {
-            languagetool: Some(languagetool),
+            languagetool: Some(LanguageToolConfig::default()),
             ..Default::default()
         }
     }
@@ -237,12 +766,39 @@ impl Default for Config {
         ]);
 
         Self {
+            comment_scope: CommentScope::default(),
+            attributes: Vec::new(),
+            backup: Backup::default(),
+            audit_log: None,
+            max_replacements: default_max_replacements(),
+            min_word_length: default_min_word_length(),
+            tab_width: default_tab_width(),
+            parallel_discovery: false,
+            no_ignore: false,
+            ignore_patterns: Vec::new(),
+            document_kinds: BTreeMap::new(),
+            include: Vec::new(),
+            exclude: Vec::new(),
             hunspell: Some(HunspellConfig {
                 lang: Some("en_US".to_owned()),
+                extra_langs: Vec::new(),
                 search_dirs: Some(search_dirs),
                 extra_dictonaries: Some(Vec::new()),
+                word_frequencies: None,
+                split_identifiers: false,
+                skip_digits: SkipDigits::default(),
+                separators: Some(DEFAULT_SEPARATORS.to_owned()),
+                check_code: false,
+                rendering: Rendering::default(),
+                skip_paths: false,
+                skip_versions: false,
+                skip_hex: false,
             }),
             languagetool: None,
+            nlprule: None,
+            repetition: Some(RepetitionConfig::default()),
+            wordlist: None,
+            exit: ExitConfig::default(),
         }
     }
 }
@@ -341,6 +897,139 @@ lang = "en_US"
         .unwrap();
     }
 
+    #[test]
+    fn languagetool() {
+        let cfg = Config::parse(
+            r#"
+[languagetool]
+server = "https://api.languagetoolplus.com"
+port = 8443
+username = "alice@example.com"
+api_key = "0123456789"
+			"#,
+        )
+        .unwrap();
+        let languagetool = cfg.languagetool.expect("LanguageTool section is present");
+        assert_eq!(
+            languagetool.url().as_str(),
+            "https://api.languagetoolplus.com:8443/"
+        );
+        assert_eq!(languagetool.username.as_deref(), Some("alice@example.com"));
+        assert_eq!(languagetool.api_key.as_deref(), Some("0123456789"));
+
+        // the former `url` key is still accepted
+        let cfg = Config::parse("[languagetool]\nurl = \"http://192.168.1.11:1337\"").unwrap();
+        assert_eq!(
+            cfg.languagetool.unwrap().url().as_str(),
+            "http://192.168.1.11:1337/"
+        );
+
+        // falls back to a local server
+        let cfg = Config::parse("[languagetool]").unwrap();
+        assert_eq!(
+            cfg.languagetool.unwrap().url().as_str(),
+            "http://127.0.0.1:8010/"
+        );
+    }
+
+    #[test]
+    fn nlprule() {
+        let mut cfg = Config::parse(
+            r#"
+[nlprule]
+tokenizer = "en_tokenizer.bin"
+rules = "/opt/nlprule/en_rules.bin"
+			"#,
+        )
+        .unwrap();
+        cfg.sanitize_paths(Path::new("/etc/spellcheck")).unwrap();
+        assert!(cfg.is_enabled(Detector::Nlprule));
+        let nlprule = cfg.nlprule.expect("Nlprule section is present");
+        assert_eq!(
+            nlprule.tokenizer(),
+            Path::new("/etc/spellcheck/en_tokenizer.bin")
+        );
+        assert_eq!(nlprule.rules(), Path::new("/opt/nlprule/en_rules.bin"));
+    }
+
+    #[test]
+    fn rendering() {
+        let cfg = Config::parse(
+            r#"
+[hunspell]
+lang = "en_US"
+soft_break_as_space = true
+link_titles = true
+
+[languagetool]
+soft_break_as_space = true
+skip_math = true
+
+[nlprule]
+tokenizer = "en_tokenizer.bin"
+rules = "en_rules.bin"
+code_placeholder = "code"
+			"#,
+        )
+        .unwrap();
+        assert!(cfg.hunspell.as_ref().unwrap().rendering.soft_break_as_space);
+        assert!(cfg.hunspell.as_ref().unwrap().rendering.link_titles);
+        assert!(!cfg.languagetool.as_ref().unwrap().rendering.link_titles);
+        assert!(cfg.languagetool.as_ref().unwrap().rendering.skip_math);
+        assert!(!cfg.nlprule.as_ref().unwrap().rendering.skip_math);
+        assert!(
+            cfg.languagetool
+                .as_ref()
+                .unwrap()
+                .rendering
+                .soft_break_as_space
+        );
+        assert!(!cfg.nlprule.as_ref().unwrap().rendering.soft_break_as_space);
+        assert_eq!(
+            cfg.languagetool
+                .as_ref()
+                .unwrap()
+                .rendering
+                .code_placeholder,
+            None
+        );
+        assert_eq!(
+            cfg.nlprule
+                .as_ref()
+                .unwrap()
+                .rendering
+                .code_placeholder
+                .as_deref(),
+            Some("code")
+        );
+
+        // flattened into the section of each checker
+        let toml = cfg.to_toml().expect("Must remain serializable");
+        let cfg = Config::parse(toml).expect("Must remain parsable");
+        let hunspell = cfg.hunspell.unwrap();
+        assert!(hunspell.rendering.soft_break_as_space);
+        assert!(hunspell.rendering.link_titles);
+        let languagetool = cfg.languagetool.unwrap();
+        assert!(languagetool.rendering.soft_break_as_space);
+        assert!(languagetool.rendering.skip_math);
+        let nlprule = cfg.nlprule.unwrap();
+        assert!(!nlprule.rendering.soft_break_as_space);
+        assert_eq!(nlprule.rendering.code_placeholder.as_deref(), Some("code"));
+    }
+
+    #[test]
+    fn repetition() {
+        assert!(Config::default().is_enabled(Detector::Repetition));
+
+        let cfg = Config::parse("[repetition]\nignore = [\"bye\"]").unwrap();
+        assert!(cfg.is_enabled(Detector::Repetition));
+        assert!(cfg.repetition.unwrap().is_ignored("Bye"));
+
+        // like all other checkers, disabled by omitting its section
+        let cfg = Config::parse("[hunspell]\nlang = \"en_US\"").unwrap();
+        assert!(!cfg.is_enabled(Detector::Repetition));
+    }
+
     #[test]
     fn partial_5() {
         let _ = Config::parse(
@@ -361,4 +1050,188 @@ lang = "en_US"
         )
         .unwrap();
     }
+
+    #[test]
+    fn skip_digits() {
+        for word in &["utf8", "base64", "sha256"] {
+            assert!(SkipDigits::Mixed.skip(word));
+            assert!(SkipDigits::Any.skip(word));
+            assert!(!SkipDigits::Off.skip(word));
+        }
+        assert!(!SkipDigits::Mixed.skip("1337"));
+        assert!(SkipDigits::Any.skip("1337"));
+        assert!(!SkipDigits::Mixed.skip("tpyo"));
+        assert!(!SkipDigits::Any.skip("tpyo"));
+
+        let cfg = Config::parse(
+            r#"
+[hunspell]
+skip_digits = "off"
+			"#,
+        )
+        .unwrap();
+        assert_eq!(cfg.hunspell.unwrap().skip_digits, SkipDigits::Off);
+    }
+
+    #[test]
+    fn verbatim() {
+        let cfg = Config::parse(
+            r#"
+[hunspell]
+skip_paths = true
+skip_hex = true
+			"#,
+        )
+        .unwrap();
+        let verbatim = cfg.hunspell.unwrap().verbatim();
+        assert!(verbatim.paths);
+        assert!(!verbatim.versions);
+        assert!(verbatim.hex);
+        let hunspell = Config::parse("[hunspell]").unwrap().hunspell.unwrap();
+        assert_eq!(hunspell.verbatim(), Verbatim::default());
+    }
+
+    #[test]
+    fn comment_scope() {
+        let cfg = Config::parse(
+            r#"
+comment_scope = "all"
+
+[hunspell]
+lang = "en_US"
+			"#,
+        )
+        .unwrap();
+        assert_eq!(cfg.comment_scope, CommentScope::All);
+        assert_eq!(Config::parse("").unwrap().comment_scope, CommentScope::Doc);
+    }
+
+    #[test]
+    fn attributes() {
+        let cfg = Config::parse(
+            r#"
+attributes = ["error", "deprecated"]
+			"#,
+        )
+        .unwrap();
+        assert_eq!(cfg.attributes, vec!["error", "deprecated"]);
+        assert!(Config::parse("").unwrap().attributes.is_empty());
+    }
+
+    #[test]
+    fn document_kinds() {
+        let cfg = Config::parse(
+            r#"
+[document_kinds]
+"*.md.tpl" = "markdown"
+"src/*.rs.in" = "rust"
+"NOTES" = "text"
+			"#,
+        )
+        .unwrap();
+        assert_eq!(
+            cfg.document_kind(Path::new("/project/docs/intro.md.tpl")),
+            Some(DocumentKind::Markdown)
+        );
+        assert_eq!(
+            cfg.document_kind(Path::new("src/generated.rs.in")),
+            Some(DocumentKind::Rust)
+        );
+        assert_eq!(
+            cfg.document_kind(Path::new("/project/NOTES")),
+            Some(DocumentKind::Text)
+        );
+        assert_eq!(cfg.document_kind(Path::new("src/main.rs")), None);
+        // must remain serializable
+        assert!(cfg.to_toml().is_ok());
+    }
+
+    #[test]
+    fn include_exclude() {
+        let cfg = Config::parse(
+            r#"
+include = ["src/**/*.rs", "*.md"]
+exclude = ["CHANGELOG.md"]
+			"#,
+        )
+        .unwrap();
+        assert_eq!(cfg.include, vec!["src/**/*.rs", "*.md"]);
+        assert_eq!(cfg.exclude, vec!["CHANGELOG.md"]);
+        assert!(Config::parse("").unwrap().include.is_empty());
+
+        // along with a table, which must follow them
+        let mut cfg = cfg;
+        cfg.document_kinds
+            .insert("*.md.tpl".to_owned(), DocumentKind::Markdown);
+        let toml = cfg.to_toml().expect("Must remain serializable");
+        let parsed = Config::parse(toml.as_str()).expect("Must parse its own output");
+        assert_eq!(parsed.include, cfg.include);
+        assert_eq!(parsed.exclude, cfg.exclude);
+        assert_eq!(parsed.document_kinds, cfg.document_kinds);
+    }
+
+    #[test]
+    fn exit() {
+        let cfg = Config::parse(
+            r#"
+[exit]
+threshold = "error"
+detectors = ["hunspell", "languagetool"]
+			"#,
+        )
+        .unwrap();
+        assert!(!cfg.exit.report_only);
+        assert_eq!(cfg.exit.threshold, Severity::Error);
+        assert_eq!(
+            cfg.exit.detectors,
+            vec![Detector::Hunspell, Detector::LanguageTool]
+        );
+        assert_eq!(Config::parse("").unwrap().exit.threshold, Severity::Warning);
+        // must remain serializable
+        assert!(cfg.to_toml().is_ok());
+    }
+
+    #[test]
+    fn dump_accepted() {
+        let dir = crate::testcase::scratch_dir("dump");
+        let extra = dir.join("extra.dic");
+        std::fs::write(&extra, "2\nfoobarization/S\nquux\n").unwrap();
+
+        let mut cfg = Config::parse(
+            r#"
+attributes = ["error"]
+ignore_patterns = ['^[A-Z]{2,}$']
+min_word_length = 3
+
+[hunspell]
+lang = "en_US"
+search_dirs = []
+extra_dictonaries = ["extra.dic"]
+skip_digits = "any"
+skip_paths = true
+skip_hex = true
+skip_math = true
+			"#,
+        )
+        .unwrap();
+        cfg.hunspell.as_mut().unwrap().extra_dictonaries = Some(vec![extra]);
+
+        let dump = cfg.dump_accepted().expect("Extra dictionary exists");
+        assert!(dump.contains("  foobarization\n"));
+        assert!(dump.contains("  quux\n"));
+        assert!(!dump.contains("  2\n"));
+        assert!(dump.contains("  {..} within #[error(..)]\n"));
+        assert!(dump.contains("  tokens with digits\n"));
+        assert!(dump.contains("  en_US (not found)\n"));
+        // a single section of everything ignored
+        assert_eq!(dump.matches("patterns:").count(), 1);
+        assert!(dump.contains("  words matching `^[A-Z]{2,}$`\n"));
+        assert!(dump.contains("  words shorter than 3 characters\n"));
+        assert!(dump.contains("  paths such as `src/lib.rs`\n"));
+        assert!(!dump.contains("  version numbers"));
+        assert!(dump.contains("  hex literals such as `0xDEADBEEF`\n"));
+        assert!(dump.contains("  math such as `$x^2$`\n"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
@@ -0,0 +1,241 @@
+//! Restrict the findings to the lines changed according to `git diff`.
+//!
+//! Newly added files are changed entirely, deleted files have no lines left
+//! to check and renamed files are tracked by their new path, so a rename
+//! without any modifications does not yield any changed lines.
+
+use crate::SuggestionSet;
+
+use anyhow::{anyhow, Result};
+use log::{debug, trace};
+
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The changed lines per file, 1-indexed and inclusive.
+#[derive(Debug, Clone, Default)]
+pub struct ChangedLines {
+    per_file: HashMap<PathBuf, Vec<RangeInclusive<usize>>>,
+}
+
+fn git(args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .output()
+        .map_err(|e| anyhow!("Failed to run git {}", args.join(" ")).context(e))?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    String::from_utf8(output.stdout)
+        .map_err(|e| anyhow!("Output of git {} is not UTF-8", args.join(" ")).context(e))
+}
+
+/// Parse the added side `+start,count` of a hunk header, where the count
+/// defaults to `1`.
+fn added_lines(header: &str) -> Option<RangeInclusive<usize>> {
+    let added = header
+        .strip_prefix("@@ ")?
+        .split_whitespace()
+        .find(|part| part.starts_with('+'))?;
+    let mut it = added[1..].splitn(2, ',');
+    let start = it.next()?.parse::<usize>().ok()?;
+    let count = it
+        .next()
+        .map_or(Some(1), |count| count.parse::<usize>().ok())?;
+    if count == 0 {
+        // only removed lines
+        None
+    } else {
+        Some(start..=(start + count - 1))
+    }
+}
+
+impl ChangedLines {
+    /// Lines changed by `git diff <range>`, i.e. `origin/main...HEAD` for the
+    /// commits of a branch or `HEAD` for the working tree.
+    pub fn from_git(range: &str) -> Result<Self> {
+        let root = git(&["rev-parse", "--show-toplevel"])?;
+        let diff = git(&[
+            "diff",
+            "--unified=0",
+            "--no-color",
+            "--no-ext-diff",
+            "--no-prefix",
+            "--find-renames",
+            range,
+        ])?;
+        Ok(Self::parse(&diff, Path::new(root.trim())))
+    }
+
+    /// Parse a diff with zero context lines and without path prefixes,
+    /// where all paths are relative to `root`.
+    pub fn parse(diff: &str, root: &Path) -> Self {
+        let mut per_file = HashMap::<PathBuf, Vec<RangeInclusive<usize>>>::new();
+        let mut current: Option<PathBuf> = None;
+        // added lines may look like a header too, i.e. `+++ b` for an added `++ b`
+        let mut in_header = false;
+        for line in diff.lines() {
+            if line.starts_with("diff ") {
+                current = None;
+                in_header = true;
+            } else if line.starts_with("@@ ") {
+                in_header = false;
+                if let (Some(path), Some(lines)) = (current.as_ref(), added_lines(line)) {
+                    trace!("Changed lines {:?} of {}", &lines, path.display());
+                    per_file.entry(path.clone()).or_default().push(lines);
+                }
+            } else if !in_header {
+                continue;
+            } else if let Some(path) = line.strip_prefix("+++ ") {
+                // deleted files have no lines left
+                current = if path == "/dev/null" {
+                    None
+                } else {
+                    let path = root.join(path.trim_end());
+                    per_file.entry(path.clone()).or_default();
+                    Some(path)
+                };
+            }
+        }
+        Self { per_file }
+    }
+
+    /// If any line of `lines` of the file at `path` changed.
+    pub fn intersects(&self, path: &Path, lines: RangeInclusive<usize>) -> bool {
+        self.per_file.get(path).map_or(false, |changed| {
+            changed
+                .iter()
+                .any(|changed| changed.start() <= lines.end() && lines.start() <= changed.end())
+        })
+    }
+
+    /// Only keep the suggestions which cover at least one changed line.
+    pub fn retain(&self, suggestions: &mut SuggestionSet) -> Result<()> {
+        let cwd = std::env::current_dir().map_err(|e| anyhow!("Missing cwd!").context(e))?;
+        suggestions.retain(|suggestion| {
+            let path = cwd.join(&suggestion.path);
+            let keep =
+                self.intersects(&path, suggestion.span.start.line..=suggestion.span.end.line);
+            if !keep {
+                debug!(
+                    "Dropping suggestion in unchanged line {} of {}",
+                    suggestion.span.start.line,
+                    path.display()
+                );
+            }
+            keep
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIFF: &str = r#"diff --git src/lib.rs src/lib.rs
+index 3b18e51..a9c1b2d 100644
+--- src/lib.rs
++++ src/lib.rs
+@@ -3 +3 @@ struct X;
+-/// An tpyo.
++/// A tpyo.
+@@ -10,0 +11,3 @@ struct Y;
++/// Three
++++ new
++/// lines
+@@ -20,2 +22,0 @@ struct Z;
+-/// Two removed
+-/// lines
+diff --git src/new.rs src/new.rs
+new file mode 100644
+index 0000000..e69de29
+--- /dev/null
++++ src/new.rs
+@@ -0,0 +1,2 @@
++/// All new.
++struct New;
+diff --git src/gone.rs src/gone.rs
+deleted file mode 100644
+index e69de29..0000000
+--- src/gone.rs
++++ /dev/null
+@@ -1 +0,0 @@
+-/// Gone.
+diff --git src/old.rs src/renamed.rs
+similarity 90%
+rename from src/old.rs
+rename to src/renamed.rs
+index 1111111..2222222 100644
+--- src/old.rs
++++ src/renamed.rs
+@@ -5 +5 @@
+-/// Old.
++/// Renamed.
+diff --git src/moved.rs src/elsewhere.rs
+similarity 100%
+rename from src/moved.rs
+rename to src/elsewhere.rs
+"#;
+
+    #[test]
+    fn hunks() {
+        assert_eq!(added_lines("@@ -3 +3 @@ struct X;"), Some(3..=3));
+        assert_eq!(added_lines("@@ -10,0 +11,3 @@"), Some(11..=13));
+        assert_eq!(added_lines("@@ -20,2 +22,0 @@"), None);
+        assert_eq!(added_lines("+++ src/lib.rs"), None);
+    }
+
+    #[test]
+    fn parse() {
+        let root = Path::new("/repo");
+        let changed = ChangedLines::parse(DIFF, root);
+        let lib = root.join("src/lib.rs");
+        assert!(changed.intersects(&lib, 3..=3));
+        assert!(!changed.intersects(&lib, 4..=10));
+        assert!(changed.intersects(&lib, 10..=11));
+        assert!(changed.intersects(&lib, 13..=13));
+        assert!(!changed.intersects(&lib, 20..=22));
+
+        // all lines of a new file
+        assert!(changed.intersects(&root.join("src/new.rs"), 1..=1));
+        assert!(changed.intersects(&root.join("src/new.rs"), 2..=2));
+        // deleted
+        assert!(!changed.intersects(&root.join("src/gone.rs"), 1..=1));
+        // renamed, tracked by the new path
+        assert!(changed.intersects(&root.join("src/renamed.rs"), 5..=5));
+        assert!(!changed.intersects(&root.join("src/old.rs"), 5..=5));
+        assert!(!changed.intersects(&root.join("src/elsewhere.rs"), 1..=1));
+        // unrelated
+        assert!(!changed.intersects(&root.join("src/main.rs"), 1..=1));
+    }
+
+    #[test]
+    fn retain() {
+        let docs = crate::testcase::documentation();
+        let mut set = crate::testcase::suggestion_set(&docs);
+        let (path, _) = crate::testcase::first(docs.iter());
+        let path = path.clone();
+        assert_eq!(set.count(), 2);
+
+        let cwd = std::env::current_dir().unwrap();
+        let second_line = format!("diff --git {0} {0}\n+++ {0}\n@@ -2 +2 @@\n", path.display());
+        ChangedLines::parse(&second_line, &cwd)
+            .retain(&mut set)
+            .unwrap();
+        assert_eq!(set.count(), 0);
+
+        let mut set = crate::testcase::suggestion_set(&docs);
+        let first_line = format!("diff --git {0} {0}\n+++ {0}\n@@ -1 +1 @@\n", path.display());
+        ChangedLines::parse(&first_line, &cwd)
+            .retain(&mut set)
+            .unwrap();
+        assert_eq!(set.count(), 2);
+    }
+}
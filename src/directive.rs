@@ -0,0 +1,184 @@
+//! Inline directives to silence findings locally.
+//!
+//! Directives are searched for in the whole file, so they may be placed in
+//! doc comments, markdown comments such as `<!-- spellcheck:ignore Kube -->`,
+//! or in trailing regular comments such as `// spellcheck:ignore-line`.
+//!
+//! * `spellcheck:ignore-line` silences all findings on the same line.
+//! * `spellcheck:ignore-next-line` silences all findings on the following line.
+//! * `spellcheck:ignore <words>..` silences the listed words within the whole file.
+
+use crate::{Suggestion, SuggestionSet};
+
+use log::{debug, trace};
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+const MARKER: &str = "spellcheck:ignore";
+
+/// The directives of a single file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Directives {
+    /// Lines without findings, 1-indexed.
+    lines: HashSet<usize>,
+    words: HashSet<String>,
+}
+
+impl Directives {
+    pub fn parse(content: &str) -> Self {
+        let mut directives = Self::default();
+        for (idx, line) in content.lines().enumerate() {
+            let lineno = idx + 1;
+            let mut rest = line;
+            while let Some(start) = rest.find(MARKER) {
+                rest = &rest[start + MARKER.len()..];
+                if let Some(after) = rest.strip_prefix("-next-line") {
+                    trace!("Ignoring line {}", lineno + 1);
+                    directives.lines.insert(lineno + 1);
+                    rest = after;
+                } else if let Some(after) = rest.strip_prefix("-line") {
+                    trace!("Ignoring line {}", lineno);
+                    directives.lines.insert(lineno);
+                    rest = after;
+                } else if rest.starts_with(char::is_whitespace) {
+                    // the words end with the line or the end of the enclosing comment
+                    let end = ["-->", "*/"]
+                        .iter()
+                        .filter_map(|terminator| rest.find(terminator))
+                        .min()
+                        .unwrap_or(rest.len());
+                    for word in rest[..end].split(|c: char| c.is_whitespace() || c == ',') {
+                        if !word.is_empty() {
+                            trace!("Ignoring word >{}<", word);
+                            directives.words.insert(word.to_owned());
+                        }
+                    }
+                    rest = &rest[end..];
+                }
+            }
+        }
+        directives
+    }
+
+    /// If `suggestion` is silenced by any of the directives.
+    pub fn suppresses(&self, suggestion: &Suggestion) -> bool {
+        let start = suggestion.span.start.line;
+        let end = suggestion.span.end.line;
+        if (start..=end).any(|line| self.lines.contains(&line)) {
+            return true;
+        }
+        !self.words.is_empty()
+            && crate::report::covered_text(suggestion)
+                .map_or(false, |covered| self.words.contains(covered.as_str()))
+    }
+}
+
+/// Drop all suggestions silenced by a directive of their file, where
+/// `read` provides the content of a file, i.e. for content which is not on disk.
+pub fn retain_with<F>(suggestions: &mut SuggestionSet, mut read: F)
+where
+    F: FnMut(&Path) -> Option<String>,
+{
+    let mut per_file = HashMap::<PathBuf, Directives>::new();
+    suggestions.retain(|suggestion| {
+        let directives = per_file.entry(suggestion.path.clone()).or_insert_with(|| {
+            read(&suggestion.path)
+                .map(|content| Directives::parse(content.as_str()))
+                .unwrap_or_default()
+        });
+        let suppressed = directives.suppresses(suggestion);
+        if suppressed {
+            debug!(
+                "Dropping suggestion in line {} of {} due to an ignore directive",
+                suggestion.span.start.line,
+                suggestion.path.display()
+            );
+        }
+        !suppressed
+    });
+}
+
+/// Drop all suggestions silenced by a directive of their file.
+pub fn retain(suggestions: &mut SuggestionSet) {
+    retain_with(suggestions, |path| std::fs::read_to_string(path).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let directives = Directives::parse(
+            r#"//! Runs on <!-- spellcheck:ignore Kubernetes, kubectl --> clusters.
+let x = 1; // spellcheck:ignore-line
+// spellcheck:ignore-next-line
+/* spellcheck:ignore foo */ /* spellcheck:ignore-line */
+"#,
+        );
+        assert_eq!(
+            directives.words,
+            vec!["Kubernetes", "kubectl", "foo"]
+                .into_iter()
+                .map(|word| word.to_owned())
+                .collect()
+        );
+        assert_eq!(directives.lines, vec![2, 4].into_iter().collect());
+    }
+
+    #[test]
+    fn same_line() {
+        let docs = crate::testcase::documentation();
+        let mut set = crate::testcase::suggestion_set(&docs);
+        assert_eq!(set.count(), 2);
+        retain_with(&mut set, |_path| {
+            Some("/// A tpyo here & there. // spellcheck:ignore-line\n".to_owned())
+        });
+        assert_eq!(set.count(), 0);
+
+        // the directive only covers its own line
+        let mut set = crate::testcase::suggestion_set(&docs);
+        retain_with(&mut set, |_path| {
+            Some("/// A tpyo here & there.\nstruct X; // spellcheck:ignore-line\n".to_owned())
+        });
+        assert_eq!(set.count(), 2);
+    }
+
+    #[test]
+    fn next_line() {
+        const SOURCE: &str = r#"// spellcheck:ignore-next-line
+/// Check the the spelling.
+/// Check the the spelling.
+struct X;
+"#;
+        let docs = crate::Documentation::load_from_str("lib.rs", SOURCE, &crate::Config::default())
+            .expect("Must parse");
+        let mut set = crate::testcase::check(&docs, &crate::Config::default());
+        let lines = |set: &SuggestionSet| {
+            set.iter()
+                .flat_map(|(_path, suggestions)| suggestions.iter())
+                .map(|suggestion| suggestion.span.start.line)
+                .collect::<HashSet<_>>()
+        };
+        assert_eq!(lines(&set), vec![2, 3].into_iter().collect());
+
+        retain_with(&mut set, |_path| Some(SOURCE.to_owned()));
+        assert_eq!(lines(&set), vec![3].into_iter().collect());
+    }
+
+    #[test]
+    fn words() {
+        let docs = crate::testcase::documentation();
+        let mut set = crate::testcase::suggestion_set(&docs);
+        retain_with(&mut set, |_path| {
+            Some("/// A tpyo here & there.\n/// <!-- spellcheck:ignore tpyo -->\n".to_owned())
+        });
+        assert_eq!(set.count(), 1);
+        let (_path, suggestions) = set.iter().next().unwrap();
+        assert_eq!(
+            crate::report::covered_text(&suggestions[0]).as_deref(),
+            Some("&")
+        );
+    }
+}
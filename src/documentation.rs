@@ -7,9 +7,10 @@ use crate::LiteralSet;
 
 use std::convert::TryInto;
 
+use anyhow::{anyhow, Result};
 use indexmap::IndexMap;
 use log::trace;
-use proc_macro2::{Spacing, TokenTree};
+use proc_macro2::{Delimiter, Spacing, TokenTree};
 
 pub use proc_macro2::LineColumn;
 use std::path::{Path, PathBuf};
@@ -33,6 +34,11 @@ impl Documentation {
         self.index.is_empty()
     }
 
+    /// Number of files.
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = (&PathBuf, &Vec<LiteralSet>)> {
         self.index.iter()
     }
@@ -55,6 +61,14 @@ impl Documentation {
         self
     }
 
+    /// Add the literal sets of the file at `path`, after any existing ones.
+    pub fn extend(&mut self, path: PathBuf, mut literal_sets: Vec<LiteralSet>) {
+        self.index
+            .entry(path)
+            .or_insert_with(|| Vec::with_capacity(literal_sets.len()))
+            .append(&mut literal_sets);
+    }
+
     pub fn combine(mut docs: Vec<Documentation>) -> Documentation {
         if let Some(first) = docs.pop() {
             docs.into_iter().fold(first, |mut first, other| {
@@ -70,8 +84,7 @@ impl Documentation {
     ///
     /// Only works if the file is processed line by line, otherwise
     /// requires a adjacency list.
-    pub fn append_literal(&mut self, path: &Path, literal: proc_macro2::Literal) {
-        let literal = TrimmedLiteral::from(literal);
+    pub fn append_literal(&mut self, path: &Path, literal: TrimmedLiteral) {
         match self.index.entry(path.to_owned()) {
             indexmap::map::Entry::Occupied(occupied) => {
                 let v = occupied.into_mut();
@@ -97,14 +110,64 @@ impl Documentation {
         }
     }
 
-    /// Helper function to parse a path stream and associated the found literals to `path`
-    fn parse_token_tree<P: AsRef<Path>>(&mut self, path: P, stream: proc_macro2::TokenStream) {
+    /// Parse the source `content` of the file at `path` and collect all
+    /// comments which are covered by the configured `comment_scope`, as well
+    /// as the string arguments of the configured `attributes`.
+    pub fn load_from_str<P: AsRef<Path>>(path: P, content: &str, config: &Config) -> Result<Self> {
+        let path: &Path = path.as_ref();
+        let content = strip_bom(content);
+        let stream = syn::parse_str::<proc_macro2::TokenStream>(content)
+            .map_err(|e| anyhow!("File {} has syntax errors", path.display()).context(e))?;
+
+        let mut literals = Vec::with_capacity(64);
+        Self::collect_doc_literals(stream, config.attributes.as_slice(), &mut literals);
+        if config.comment_scope == CommentScope::All {
+            literals.extend(plain_comment_literals(content)?);
+            // adjacency is only detected if the literals are appended in order of appearance
+            literals.sort_by_key(|literal: &TrimmedLiteral| {
+                let start = literal.span().start();
+                (start.line, start.column)
+            });
+        }
+
+        let mut documentation = Documentation::new();
+        for literal in literals {
+            documentation.append_literal(path, literal);
+        }
+        Ok(documentation)
+    }
+
+    /// Load a standalone markdown or plain text document, every line of
+    /// `content` becomes a literal of the given `variant`.
+    pub fn load_lines_from_str<P: AsRef<Path>>(
+        path: P,
+        content: &str,
+        variant: CommentVariant,
+    ) -> Result<Self> {
         let path: &Path = path.as_ref();
+        let literals = line_literals(strip_bom(content), variant)
+            .map_err(|e| anyhow!("Failed to load {}", path.display()).context(e))?;
+        let mut documentation = Documentation::new();
+        for literal in literals {
+            documentation.append_literal(path, literal);
+        }
+        Ok(documentation)
+    }
 
+    /// Helper function to parse a token stream and collect all doc comment literals,
+    /// as well as the string arguments of `attributes`.
+    fn collect_doc_literals(
+        stream: proc_macro2::TokenStream,
+        attributes: &[String],
+        acc: &mut Vec<TrimmedLiteral>,
+    ) {
         let mut iter = stream.into_iter();
+        // if the previous token was a `#` or `#!`, so the next bracket group is an attribute
+        let mut attribute_follows = false;
         while let Some(tree) = iter.next() {
             match tree {
                 TokenTree::Ident(ident) => {
+                    attribute_follows = false;
                     // if we find an identifier
                     // which is doc
                     if ident != "doc" {
@@ -139,18 +202,293 @@ impl Documentation {
                             <Span as TryInto<Range>>::try_into(Span::from(literal.span())),
                             literal
                         );
-                        self.append_literal(path, literal);
+                        // the parser desugars a `///` comment to `#[doc = r" .."]`, where all
+                        // tokens share the span of the whole comment, while tokens written out
+                        // never overlap, so the literal of a `#[doc = ".."]` follows the `doc`
+                        let desugared = ident.span().start() == literal.span().start()
+                            && ident.span().end() == literal.span().end();
+                        let variant = if desugared {
+                            CommentVariant::TripleSlash
+                        } else {
+                            CommentVariant::DocAttribute
+                        };
+                        acc.push(TrimmedLiteral::from((literal, variant)));
                     } else {
                         continue;
                     }
                 }
+                TokenTree::Punct(punct) => {
+                    attribute_follows =
+                        punct.as_char() == '#' || (attribute_follows && punct.as_char() == '!');
+                }
                 TokenTree::Group(group) => {
-                    self.parse_token_tree(path, group.stream());
+                    if attribute_follows && group.delimiter() == Delimiter::Bracket {
+                        Self::collect_attribute_literals(group.stream(), attributes, acc);
+                    }
+                    attribute_follows = false;
+                    Self::collect_doc_literals(group.stream(), attributes, acc);
+                }
+                _ => {
+                    attribute_follows = false;
                 }
-                _ => {}
             };
         }
     }
+
+    /// Collect the string arguments of an attribute, where `stream` is the content of
+    /// the brackets, if the attribute name is contained in `attributes`.
+    ///
+    /// Covers `name("..")`, `name(note = "..")` as well as `name = ".."`.
+    fn collect_attribute_literals(
+        stream: proc_macro2::TokenStream,
+        attributes: &[String],
+        acc: &mut Vec<TrimmedLiteral>,
+    ) {
+        let is_string = |literal: &proc_macro2::Literal| literal.to_string().starts_with('"');
+        let mut iter = stream.into_iter();
+        match iter.next() {
+            Some(TokenTree::Ident(ident)) if attributes.iter().any(|name| ident == name) => {}
+            _ => return,
+        }
+        match iter.next() {
+            Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Parenthesis => {
+                let mut previous: Option<TokenTree> = None;
+                let mut key: Option<String> = None;
+                for tree in group.stream() {
+                    match &tree {
+                        TokenTree::Literal(literal) if is_string(literal) => {
+                            // positional arguments or `note = ".."`
+                            let assigned = match previous {
+                                Some(TokenTree::Punct(ref punct)) => punct.as_char() == '=',
+                                _ => false,
+                            };
+                            if !assigned || key.as_deref() == Some("note") {
+                                acc.push(TrimmedLiteral::from((
+                                    literal.clone(),
+                                    CommentVariant::Attribute,
+                                )));
+                            }
+                        }
+                        TokenTree::Ident(ident) => key = Some(ident.to_string()),
+                        TokenTree::Punct(punct) if punct.as_char() == ',' => key = None,
+                        _ => {}
+                    }
+                    previous = Some(tree);
+                }
+            }
+            Some(TokenTree::Punct(punct)) if punct.as_char() == '=' => {
+                if let Some(TokenTree::Literal(literal)) = iter.next() {
+                    if is_string(&literal) {
+                        acc.push(TrimmedLiteral::from((literal, CommentVariant::Attribute)));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Helper function to parse a path stream and associated the found literals to `path`
+    fn parse_token_tree<P: AsRef<Path>>(&mut self, path: P, stream: proc_macro2::TokenStream) {
+        let path: &Path = path.as_ref();
+
+        let mut literals = Vec::with_capacity(64);
+        Self::collect_doc_literals(stream, &[], &mut literals);
+        for literal in literals {
+            self.append_literal(path, literal);
+        }
+    }
+}
+
+/// Strip a leading utf8 byte order mark, the spans of all literals are
+/// relative to the content following it.
+pub(crate) fn strip_bom(content: &str) -> &str {
+    content.strip_prefix('\u{feff}').unwrap_or(content)
+}
+
+/// Find all regular `//` comments in `source`, doc comments are skipped.
+///
+/// Returns the 1-indexed line, the 0-indexed column of the leading `//`
+/// in characters and the content following the `//`.
+fn find_plain_comments(source: &str) -> Vec<(usize, usize, String)> {
+    let chars = source.chars().collect::<Vec<char>>();
+    let n = chars.len();
+    let at = |idx: usize| chars.get(idx).copied();
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+    // raw strings may be prefixed by a `b`, anything else is part of an identifier
+    let starts_token = |idx: usize| match idx {
+        0 => true,
+        1 => !is_ident(chars[0]) || chars[0] == 'b',
+        idx => !is_ident(chars[idx - 1]) || (chars[idx - 1] == 'b' && !is_ident(chars[idx - 2])),
+    };
+    let line_column = |idx: usize| {
+        let line = chars[..idx].iter().filter(|c| **c == '\n').count() + 1;
+        let column = chars[..idx]
+            .iter()
+            .rev()
+            .take_while(|c| **c != '\n')
+            .count();
+        (line, column)
+    };
+
+    let mut acc = Vec::with_capacity(16);
+    let mut idx = 0usize;
+    while idx < n {
+        match (chars[idx], at(idx + 1)) {
+            ('/', Some('/')) => {
+                let end = chars[idx..]
+                    .iter()
+                    .position(|c| *c == '\n')
+                    .map(|pos| idx + pos)
+                    .unwrap_or(n);
+                let is_doc = match (at(idx + 2), at(idx + 3)) {
+                    (Some('!'), _) => true,
+                    // `////` is a regular comment again
+                    (Some('/'), Some('/')) => false,
+                    (Some('/'), _) => true,
+                    _ => false,
+                };
+                if !is_doc {
+                    let (line, column) = line_column(idx);
+                    let content = chars[(idx + 2)..end].iter().collect::<String>();
+                    acc.push((line, column, content.trim_end_matches('\r').to_owned()));
+                }
+                idx = end;
+            }
+            ('/', Some('*')) => {
+                // block comments may be nested
+                let mut depth = 0usize;
+                while idx < n {
+                    match (chars[idx], at(idx + 1)) {
+                        ('/', Some('*')) => {
+                            depth += 1;
+                            idx += 2;
+                        }
+                        ('*', Some('/')) => {
+                            depth -= 1;
+                            idx += 2;
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                        _ => idx += 1,
+                    }
+                }
+            }
+            ('"', _) => {
+                idx += 1;
+                while idx < n {
+                    match chars[idx] {
+                        '\\' => idx += 2,
+                        '"' => {
+                            idx += 1;
+                            break;
+                        }
+                        _ => idx += 1,
+                    }
+                }
+            }
+            ('r', _) if starts_token(idx) => {
+                let hashes = chars[(idx + 1)..].iter().take_while(|c| **c == '#').count();
+                if at(idx + 1 + hashes) == Some('"') {
+                    idx += 2 + hashes;
+                    while idx < n {
+                        let closing = chars[idx] == '"'
+                            && chars[(idx + 1)..].iter().take_while(|c| **c == '#').count()
+                                >= hashes;
+                        if closing {
+                            idx += 1 + hashes;
+                            break;
+                        }
+                        idx += 1;
+                    }
+                } else {
+                    idx += 1;
+                }
+            }
+            ('\'', Some('\\')) => {
+                // escaped char literal such as `'\n'` or `'\u{1F980}'`
+                idx = chars[(idx + 2)..]
+                    .iter()
+                    .position(|c| *c == '\'')
+                    .map(|pos| idx + 2 + pos + 1)
+                    .unwrap_or(n);
+            }
+            ('\'', Some(_)) if at(idx + 2) == Some('\'') => {
+                // plain char literal, otherwise it is a lifetime
+                idx += 3;
+            }
+            _ => idx += 1,
+        }
+    }
+    acc
+}
+
+/// Convert all regular `//` comments in `source` into literals.
+fn plain_comment_literals(source: &str) -> Result<Vec<TrimmedLiteral>> {
+    // the opening quote replaces the second slash, so the content
+    // columns of the literal match the ones of the comment
+    let comments = find_plain_comments(source)
+        .into_iter()
+        .map(|(line, column, content)| (line, column + 1, content));
+    shadow_literals(comments, CommentVariant::DoubleSlash)
+        .map_err(|e| anyhow!("Failed to parse extracted comments").context(e))
+}
+
+/// Convert every line of a standalone document into a literal.
+fn line_literals(source: &str, variant: CommentVariant) -> Result<Vec<TrimmedLiteral>> {
+    let lines = source
+        .lines()
+        .enumerate()
+        .map(|(lineno, content)| (lineno + 1, 0, content.to_owned()));
+    shadow_literals(lines, variant)
+}
+
+/// Create literals by parsing a shadow source, which only contains string
+/// literals with the given content, where the opening quote of each literal
+/// is placed at the given 1-indexed line and 0-indexed column.
+///
+/// The items must be ordered by line and there must be at most one per line.
+/// The escapes only exist within the shadow source, the literals render the
+/// content as found in the document, so the columns are not shifted.
+fn shadow_literals(
+    items: impl IntoIterator<Item = (usize, usize, String)>,
+    variant: CommentVariant,
+) -> Result<Vec<TrimmedLiteral>> {
+    let mut shadow = String::with_capacity(4096);
+    let mut contents = Vec::with_capacity(64);
+    let mut current_line = 1usize;
+    for (line, column, content) in items {
+        while current_line < line {
+            shadow.push('\n');
+            current_line += 1;
+        }
+        shadow.push_str(&" ".repeat(column));
+        shadow.push('"');
+        for c in content.chars() {
+            if c == '"' || c == '\\' {
+                shadow.push('\\');
+            }
+            shadow.push(c);
+        }
+        shadow.push('"');
+        contents.push(content);
+    }
+
+    let stream = syn::parse_str::<proc_macro2::TokenStream>(&shadow)
+        .map_err(|e| anyhow!("Failed to parse shadow source").context(e))?;
+    let literals = stream
+        .into_iter()
+        .filter_map(|tree| {
+            if let TokenTree::Literal(literal) = tree {
+                Some(TrimmedLiteral::from((literal, variant)))
+            } else {
+                None
+            }
+        })
+        .zip(contents.iter())
+        .map(|(literal, content)| literal.with_content(content))
+        .collect();
+    Ok(literals)
 }
 
 impl<P> From<(P, proc_macro2::TokenStream)> for Documentation
@@ -167,8 +505,26 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use log::warn;
     use std::convert::From;
 
+    #[test]
+    fn unescaped_lines() {
+        const TEXT: &str = "He said \"hi\" to C:\\teh.\n";
+        let docs = Documentation::load_lines_from_str("notes.txt", TEXT, CommentVariant::PlainText)
+            .expect("Must load");
+        let (_path, literal_sets) = docs.iter().next().expect("Must contain the document");
+        let plain = literal_sets[0].erase_markdown();
+        assert_eq!(plain.as_str(), TEXT.trim_end());
+
+        // the columns are the ones within the file
+        let start = plain.as_str().find("teh").unwrap();
+        let spans = plain.linear_range_to_spans(start..start + 3);
+        let (literal, span) = spans.first().expect("Must map to a literal");
+        assert_eq!(literal.variant.file_column(span.start.column), start);
+        assert_eq!(literal.variant.file_column(span.end.column), start + 2);
+    }
+
     #[test]
     fn parse_and_construct() {
         let _ = env_logger::from_env(
@@ -248,8 +604,7 @@ mod tests {
                     warn!("Using default configuration!");
                     Config::default()
                 });
-                let suggestion_set = crate::checker::check(&docs, &config)
-                    .expect("Must not fail to extract suggestions");
+                let suggestion_set = crate::testcase::check(&docs, &config);
                 let (_, suggestions) = suggestion_set
                     .into_iter()
                     .next()
@@ -259,6 +614,354 @@ mod tests {
         };
     }
 
+    #[test]
+    fn plain_comments() {
+        const SOURCE: &str = r##"/// Doc.
+//! Inner doc.
+//// Four is plain.
+fn x() -> &'static str { // trailing
+    let _c = '"';
+    let _s = "// not a comment";
+    let _r = r#"// neither "// is this"#;
+    /* // nor this */
+    "x"
+}
+"##;
+        let comments = find_plain_comments(SOURCE);
+        assert_eq!(
+            comments,
+            vec![
+                (3, 0, "// Four is plain.".to_owned()),
+                (4, 25, " trailing".to_owned()),
+            ]
+        );
+    }
+
+    #[cfg(feature = "hunspell")]
+    #[test]
+    fn comment_scope() {
+        let _ = env_logger::from_env(
+            env_logger::Env::new().filter_or("CARGO_SPELLCHECK", "cargo_spellcheck=trace"),
+        )
+        .is_test(true)
+        .try_init();
+
+        const SOURCE: &str = r#"/// A documented strcut.
+struct X;
+
+// Implementation detial.
+struct Y;
+"#;
+
+        let path = PathBuf::from("/tmp/virtual");
+        let count = |scope: CommentScope| {
+            let mut config = crate::config::Config::default();
+            config.comment_scope = scope;
+            let docs = Documentation::load_from_str(&path, SOURCE, &config).expect("Must parse");
+            let suggestion_set = crate::testcase::check(&docs, &config);
+            suggestion_set.count()
+        };
+        assert_eq!(count(CommentScope::Doc), 1);
+        assert_eq!(count(CommentScope::All), 2);
+    }
+
+    #[cfg(feature = "hunspell")]
+    #[test]
+    fn end2end_autolink() {
+        const SOURCE: &str = r#"/// Visit <https://exmaple.com/pathh> for more detials.
+struct X;
+"#;
+
+        let path = PathBuf::from("/tmp/virtual");
+        let config = crate::config::Config::default();
+        let docs =
+            Documentation::load_from_str(&path, SOURCE, &config).expect("Must parse just fine");
+        let suggestion_set = crate::testcase::check(&docs, &config);
+        let (_, suggestions) = suggestion_set
+            .iter()
+            .next()
+            .expect("Must contain exactly one file");
+        assert_eq!(suggestions.len(), 1);
+        let suggestion = &suggestions[0];
+        let range: Range = suggestion.span.try_into().expect("Must be a single line");
+        let s = suggestion.literal.as_ref().as_untrimmed_str();
+        assert_eq!(&s[range], "detials");
+    }
+
+    #[cfg(feature = "hunspell")]
+    #[test]
+    fn end2end_skip_digits() {
+        const SOURCE: &str = r#"/// Decodes utf8 and base64 with a tpyo.
+struct X;
+"#;
+
+        let path = PathBuf::from("/tmp/virtual");
+        let docs = Documentation::load_from_str(&path, SOURCE, &Config::default())
+            .expect("Must parse just fine");
+        let count = |skip_digits: SkipDigits| {
+            let mut config = crate::config::Config::default();
+            config.hunspell.as_mut().unwrap().skip_digits = skip_digits;
+            let suggestion_set = crate::testcase::check(&docs, &config);
+            suggestion_set.count()
+        };
+        assert_eq!(count(SkipDigits::Mixed), 1);
+        assert_eq!(count(SkipDigits::Off), 3);
+    }
+
+    #[cfg(feature = "hunspell")]
+    #[test]
+    fn end2end_empty() {
+        let mut config = crate::config::Config::default();
+        config.comment_scope = CommentScope::All;
+        for content in &["", "   ", "\n"] {
+            let sources = vec![
+                content.to_string(),
+                format!("///{}\nstruct X;", content),
+                format!("//!{}", content),
+                format!("///{}\n///{}\nstruct X;", content, content),
+            ];
+            for source in sources {
+                let docs = Documentation::load_from_str("/tmp/virtual", &source, &config)
+                    .expect("Must parse just fine");
+                let suggestion_set = crate::testcase::check(&docs, &config);
+                assert_eq!(suggestion_set.count(), 0);
+            }
+            for variant in &[
+                CommentVariant::CommonMark,
+                CommentVariant::PlainText,
+                CommentVariant::ReStructuredText,
+            ] {
+                let docs = Documentation::load_lines_from_str("/tmp/virtual.md", content, *variant)
+                    .expect("Must load just fine");
+                let suggestion_set = crate::testcase::check(&docs, &config);
+                assert_eq!(suggestion_set.count(), 0);
+            }
+        }
+    }
+
+    #[cfg(feature = "hunspell")]
+    #[test]
+    fn end2end_attributes() {
+        const SOURCE: &str = r#"#[derive(Debug, Error)]
+pub enum Error {
+    #[error("somthing went wrong with {field} and {0:?}")]
+    Wrong { field: usize },
+    #[deprecated(since = "0.1.0", note = "use the othr one")]
+    #[error(transparent)]
+    Other(#[from] std::io::Error),
+    #[must_use = "the reslt must be used"]
+    Fine,
+}
+"#;
+
+        let path = PathBuf::from("/tmp/virtual");
+        let mut config = crate::config::Config::default();
+        let docs = Documentation::load_from_str(&path, SOURCE, &config).expect("Must parse");
+        assert!(docs.is_empty());
+
+        config.attributes = vec![
+            "error".to_owned(),
+            "deprecated".to_owned(),
+            "must_use".to_owned(),
+        ];
+        let docs = Documentation::load_from_str(&path, SOURCE, &config).expect("Must parse");
+        let suggestion_set = crate::testcase::check(&docs, &config);
+        let (_, suggestions) = suggestion_set
+            .iter()
+            .next()
+            .expect("Must contain exactly one file");
+        // placeholders such as `{field}` are not checked
+        let flagged = suggestions
+            .iter()
+            .map(|suggestion| {
+                let literal = suggestion.literal.as_ref();
+                let range = suggestion.span.relative_to(literal.span()).unwrap();
+                (
+                    literal.as_untrimmed_str()[range].to_owned(),
+                    suggestion.span.start.line,
+                    suggestion
+                        .literal
+                        .variant()
+                        .file_column(suggestion.span.start.column),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            flagged,
+            vec![
+                ("somthing".to_owned(), 3, 13),
+                ("othr".to_owned(), 5, 50),
+                ("reslt".to_owned(), 8, 22),
+            ]
+        );
+    }
+
+    #[cfg(feature = "hunspell")]
+    #[test]
+    fn end2end_attributes_multiline() {
+        const SOURCE: &str = r#"#[derive(Debug, Error)]
+pub enum Error {
+    #[error("failed to read \
+    teh file")]
+    Read,
+}
+"#;
+
+        let path = PathBuf::from("/tmp/virtual");
+        let mut config = crate::config::Config::default();
+        config.attributes = vec!["error".to_owned()];
+        let docs = Documentation::load_from_str(&path, SOURCE, &config).expect("Must parse");
+        let suggestion_set = crate::testcase::check(&docs, &config);
+        let (_, suggestions) = suggestion_set
+            .iter()
+            .next()
+            .expect("Must contain exactly one file");
+        // the span points to the second line of the literal
+        let flagged = suggestions
+            .iter()
+            .map(|suggestion| {
+                let line = SOURCE.lines().nth(suggestion.span.start.line - 1).unwrap();
+                let start = suggestion
+                    .literal
+                    .variant()
+                    .file_column(suggestion.span.start.column);
+                let end = suggestion
+                    .literal
+                    .variant()
+                    .file_column(suggestion.span.end.column);
+                (
+                    suggestion.span.start.line,
+                    suggestion.span.end.line,
+                    line[start..=end].to_owned(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(flagged, vec![(4, 4, "teh".to_owned())]);
+        // shows the line of the literal the mistake is in
+        assert!(suggestions[0].to_string().contains("    teh file\n"));
+    }
+
+    #[cfg(feature = "hunspell")]
+    #[test]
+    fn end2end_doc_attribute() {
+        const SOURCE: &str = r##"#![doc = "A crate with teh docs."]
+
+/// The first.
+#[doc = "Returns \"teh\" value.\n"]
+#[doc = r#"Does not panik."#]
+pub fn first() {}
+"##;
+
+        let path = PathBuf::from("/tmp/virtual");
+        let config = crate::config::Config::default();
+        let docs = Documentation::load_from_str(&path, SOURCE, &config).expect("Must parse");
+        let suggestion_set = crate::testcase::check(&docs, &config);
+        let (_, suggestions) = suggestion_set
+            .iter()
+            .next()
+            .expect("Must contain exactly one file");
+        let flagged = suggestions
+            .iter()
+            .map(|suggestion| {
+                let literal = suggestion.literal.as_ref();
+                let range = suggestion.span.relative_to(literal.span()).unwrap();
+                let line = SOURCE.lines().nth(suggestion.span.start.line - 1).unwrap();
+                let column = suggestion
+                    .literal
+                    .variant()
+                    .file_column(suggestion.span.start.column);
+                (
+                    literal.as_untrimmed_str()[range].to_owned(),
+                    suggestion.span.start.line,
+                    column,
+                    line[column..].chars().take(5).collect::<String>(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            flagged,
+            vec![
+                ("teh".to_owned(), 1, 23, "teh d".to_owned()),
+                ("teh".to_owned(), 4, 19, "teh\\\"".to_owned()),
+                ("panik".to_owned(), 5, 20, "panik".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn doc_attribute_escapes() {
+        const SOURCE: &str = r#"#[doc = "First.\nSecond\tthird \u{fc}ber \"fourth\"."]
+struct X;
+"#;
+        let docs =
+            Documentation::load_from_str("lib.rs", SOURCE, &Config::default()).expect("Must parse");
+        let (_path, literal_sets) = crate::testcase::first(docs.iter());
+        assert_eq!(literal_sets.len(), 1);
+        let literal_set = &literal_sets[0];
+        let content = literal_set.to_string();
+        assert_eq!(content, "First.\nSecond\tthird über \"fourth\".");
+
+        // the spans cover the words as written within the literal
+        let line = SOURCE.lines().next().unwrap();
+        for (word, written) in vec![
+            ("First", "First"),
+            ("Second", "Second"),
+            ("third", "third"),
+            ("über", "\\u{fc}ber"),
+            ("\"fourth\"", "\\\"fourth\\\""),
+        ] {
+            let start = content.find(word).unwrap();
+            let spans = literal_set.linear_range_to_spans(start..start + word.len());
+            assert_eq!(spans.len(), 1);
+            let (literal, span) = spans[0];
+            assert_eq!(literal.variant, CommentVariant::DocAttribute);
+            assert_eq!(span.start.line, 1);
+            let start = literal.variant.file_column(span.start.column);
+            let end = literal.variant.file_column(span.end.column);
+            assert_eq!(&line[start..=end], written);
+        }
+
+        // the plain text is checked with the escapes resolved
+        assert_eq!(
+            literal_set.erase_markdown().as_str(),
+            "First.\nSecond\tthird über \"fourth\"."
+        );
+    }
+
+    #[test]
+    fn doc_attribute_multiline() {
+        const SOURCE: &str = r#"#[doc = "First line \
+    and teh second."]
+#[doc = "Third line
+with anothr one."]
+struct X;
+"#;
+        let docs =
+            Documentation::load_from_str("lib.rs", SOURCE, &Config::default()).expect("Must parse");
+        let (_path, literal_sets) = crate::testcase::first(docs.iter());
+        assert_eq!(literal_sets.len(), 1);
+        let literal_set = &literal_sets[0];
+        let content = literal_set.to_string();
+        assert_eq!(
+            content,
+            "First line and teh second.\nThird line\nwith anothr one."
+        );
+
+        // a line continuation or newline within the literal moves the span to the following line
+        for (word, line, column) in vec![("teh", 2, 8), ("anothr", 4, 5), ("Third", 3, 9)] {
+            let start = content.find(word).unwrap();
+            let spans = literal_set.linear_range_to_spans(start..start + word.len());
+            assert_eq!(spans.len(), 1);
+            let (literal, span) = spans[0];
+            assert_eq!(span.start.line, line);
+            assert_eq!(span.end.line, line);
+            let start = literal.variant.file_column(span.start.column);
+            let end = literal.variant.file_column(span.end.column);
+            assert_eq!(start, column);
+            assert_eq!(&SOURCE.lines().nth(line - 1).unwrap()[start..=end], word);
+        }
+    }
+
     end2end_file!(one, "../demo/src/nested/justone.rs", 1);
     end2end_file!(two, "../demo/src/nested/justtwo.rs", 2);
 
@@ -292,7 +995,7 @@ Erronbeous bold uetchkp"#;
         let path = PathBuf::from("/tmp/virtual");
         let docs = crate::documentation::Documentation::from((&path, stream));
 
-        let suggestion_set = dbg!(crate::checker::check(&docs, &config)).expect("Must not error");
+        let suggestion_set = dbg!(crate::testcase::check(&docs, &config));
         let (path2, literal_set) = docs.iter().next().expect("Must contain exactly one");
         assert_eq!(&path, path2);
 
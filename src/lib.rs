@@ -0,0 +1,91 @@
+//! Spell and grammar checks of the documentation of rust projects, as well
+//! as of markdown, reStructuredText and plain text documents.
+//!
+//! Besides the `cargo-spellcheck` binary, the checks can be embedded, i.e.
+//! by editor plugins or build scripts, without anything being printed:
+//!
+//! ```rust,no_run
+//! use cargo_spellcheck::{Config, Documentation};
+//!
+//! const SOURCE: &str = "/// A tpyo.\nstruct X;";
+//!
+//! let config = Config::default();
+//! let documentation = Documentation::load_from_str("lib.rs", SOURCE, &config)?;
+//! let suggestions =
+//!     cargo_spellcheck::check(&documentation, &config, |_path| Some(SOURCE.to_owned()))?;
+//! for (path, suggestions) in suggestions.iter() {
+//!     for suggestion in suggestions {
+//!         println!("{}:{} {:?}", path.display(), suggestion.span.start.line, suggestion.replacements);
+//!     }
+//! }
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+
+mod config;
+mod documentation;
+mod literalset;
+mod span;
+
+pub mod action;
+pub mod checker;
+mod color;
+pub mod diff;
+pub mod directive;
+mod markdown;
+pub mod patterns;
+pub mod progress;
+pub mod report;
+mod suggestion;
+pub mod traverse;
+
+#[cfg(test)]
+mod testcase;
+
+pub use self::action::*;
+pub use self::color::ColorChoice;
+pub use self::config::{
+    Backup, CommentScope, Config, DocumentKind, ExitConfig, HunspellConfig, LanguageToolConfig,
+    NlpruleConfig, RepetitionConfig, SkipDigits, WordlistConfig,
+};
+pub use self::documentation::*;
+pub use self::literalset::*;
+pub use self::markdown::*;
+pub use self::report::OutputFormat;
+pub use self::span::*;
+pub use self::suggestion::*;
+
+/// Check `documentation` with all checkers enabled in `config`, without
+/// printing anything.
+///
+/// Suggestions silenced by a `spellcheck:` directive within the content of
+/// their file as provided by `source`, i.e. `std::fs::read_to_string` for
+/// files on disk, or matching any of the `ignore_patterns` are dropped.
+pub fn check<'a, F>(
+    documentation: &'a Documentation,
+    config: &Config,
+    source: F,
+) -> anyhow::Result<SuggestionSet<'a>>
+where
+    F: FnMut(&std::path::Path) -> Option<String>,
+{
+    check_with(documentation, config, None, &mut |_path| (), source)
+}
+
+/// Same as `check`, but only files which changed since they were stored in
+/// the `cache` are checked, and every file is passed to `progress` once per
+/// detector, see `checker::check`.
+pub fn check_with<'a, F>(
+    documentation: &'a Documentation,
+    config: &Config,
+    cache: Option<&checker::Cache>,
+    progress: &mut dyn FnMut(&std::path::Path),
+    source: F,
+) -> anyhow::Result<SuggestionSet<'a>>
+where
+    F: FnMut(&std::path::Path) -> Option<String>,
+{
+    let mut suggestions = checker::check(documentation, config, cache, progress)?;
+    directive::retain_with(&mut suggestions, source);
+    patterns::IgnorePatterns::new(&config.ignore_patterns)?.retain(&mut suggestions);
+    Ok(suggestions)
+}
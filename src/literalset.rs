@@ -1,4 +1,4 @@
-use crate::markdown::PlainOverlay;
+use crate::markdown::{PlainOverlay, Rendering};
 use crate::{LineColumn, Span};
 
 use log::trace;
@@ -40,6 +40,9 @@ impl<'l> TrimmedLiteralRef<'l> {
     pub fn as_ref(&self) -> &TrimmedLiteral {
         self.reference
     }
+    pub fn variant(&self) -> CommentVariant {
+        self.reference.variant
+    }
 
     #[allow(unused)]
     pub(crate) fn display(&self, highlight: Range) -> TrimmedLiteralDisplay {
@@ -53,11 +56,64 @@ impl<'l> fmt::Debug for TrimmedLiteralRef<'l> {
     }
 }
 
+/// Describes where the literal originates from and hence how its span
+/// relates to the file content.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum CommentVariant {
+    /// A `///` or `//!` doc comment, the span covers the comment prefix too.
+    TripleSlash,
+    /// A `#[doc = ".."]` or `#![doc = ".."]` attribute, the span covers the
+    /// string literal only, which is checked with all escapes resolved.
+    DocAttribute,
+    /// A regular `//` comment, the span starts right before the comment content.
+    DoubleSlash,
+    /// A line of a standalone markdown document, such as a `README.md`.
+    CommonMark,
+    /// A line of a standalone plain text document, which is checked as is.
+    PlainText,
+    /// A line of a standalone reStructuredText document, such as a `README.rst`.
+    ReStructuredText,
+    /// A string argument of an attribute, such as `#[error("..")]`, which is
+    /// checked as is, except for format placeholders such as `{field}`.
+    Attribute,
+}
+
+impl CommentVariant {
+    /// Convert a column of the literal span to the column within the file,
+    /// where the literal content starts after `pre`.
+    pub fn file_column(&self, column: usize) -> usize {
+        match self {
+            // `///` is two characters longer than the opening quote of the rendered literal
+            Self::TripleSlash => column + 2,
+            Self::DoubleSlash | Self::DocAttribute | Self::Attribute => column,
+            // the content of a line starts at column 0, the opening quote is not part of the file
+            Self::CommonMark | Self::PlainText | Self::ReStructuredText => column.saturating_sub(1),
+        }
+    }
+
+    /// If the literal content is markdown, which is true for all but plain text
+    /// and reStructuredText documents.
+    pub fn is_markdown(&self) -> bool {
+        match self {
+            Self::PlainText | Self::ReStructuredText | Self::Attribute => false,
+            _ => true,
+        }
+    }
+}
+
+impl Default for CommentVariant {
+    fn default() -> Self {
+        Self::TripleSlash
+    }
+}
+
 /// A literal with meta info where the first and list whitespace may be found.
 #[derive(Clone)]
 pub struct TrimmedLiteral {
     /// The literal which this annotates to.
     pub literal: proc_macro2::Literal,
+    /// The kind of comment the literal was extracted from.
+    pub variant: CommentVariant,
     /// the complete rendered string including post and pre.
     pub rendered: String,
     /// Whitespace prefix len + 1
@@ -67,6 +123,112 @@ pub struct TrimmedLiteral {
     /// Length of rendered **minus** `pre` and `post`.
     /// If the literal is all empty, `pre` and `post` become `0`, and `len` covers the full length of `rendered`.
     pub len: usize,
+    /// The content with all escapes resolved, if it contains any, while
+    /// `rendered` remains as written, so spans still point into the literal.
+    pub unescaped: Option<Unescaped>,
+}
+
+/// A single escape sequence, such as `\n` or `\u{e4}`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Escape {
+    /// Range of the resolved character within the unescaped content, empty
+    /// for a line continuation.
+    pub content: Range,
+    /// End of the escape sequence within the content as written.
+    pub written_end: usize,
+}
+
+/// The unescaped content of a string literal.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Unescaped {
+    pub content: String,
+    /// All escapes, in order.
+    pub escapes: Vec<Escape>,
+}
+
+impl Unescaped {
+    /// Resolve all escapes of the content of a non-raw string literal, as
+    /// written between the quotes.
+    ///
+    /// Returns `None` if there is nothing to resolve.
+    fn new(written: &str) -> Option<Self> {
+        if !written.contains('\\') {
+            return None;
+        }
+        let mut content = String::with_capacity(written.len());
+        let mut escapes = Vec::with_capacity(8);
+        let mut chars = written.char_indices().peekable();
+        while let Some((_, c)) = chars.next() {
+            if c != '\\' {
+                content.push(c);
+                continue;
+            }
+            let start = content.len();
+            let resolved = match chars.next() {
+                Some((_, 'n')) => Some('\n'),
+                Some((_, 'r')) => Some('\r'),
+                Some((_, 't')) => Some('\t'),
+                Some((_, '0')) => Some('\0'),
+                Some((_, 'x')) => {
+                    let hex = chars.by_ref().take(2).map(|(_, c)| c).collect::<String>();
+                    u8::from_str_radix(&hex, 16).ok().map(char::from)
+                }
+                Some((_, 'u')) => {
+                    let hex = chars
+                        .by_ref()
+                        .map(|(_, c)| c)
+                        .take_while(|c| *c != '}')
+                        .filter(|c| *c != '{' && *c != '_')
+                        .collect::<String>();
+                    u32::from_str_radix(&hex, 16)
+                        .ok()
+                        .and_then(std::char::from_u32)
+                }
+                // a line continuation skips the newline and all leading whitespace
+                Some((_, '\n')) => {
+                    while chars.peek().map_or(false, |(_, c)| c.is_whitespace()) {
+                        chars.next();
+                    }
+                    None
+                }
+                // `\\`, `\"` and `\'`
+                Some((_, c)) => Some(c),
+                None => None,
+            };
+            content.extend(resolved);
+            escapes.push(Escape {
+                content: start..content.len(),
+                written_end: chars.peek().map_or(written.len(), |(idx, _)| *idx),
+            });
+        }
+        Some(Self { content, escapes })
+    }
+
+    /// Convert the `offset` of a range start within the unescaped content to
+    /// the offset within the content as written.
+    fn written_start(&self, offset: usize) -> usize {
+        self.escapes
+            .iter()
+            .take_while(|escape| escape.content.end <= offset)
+            .last()
+            .map_or(offset, |escape| {
+                escape.written_end + (offset - escape.content.end)
+            })
+    }
+
+    /// Convert the exclusive `offset` of a range end within the unescaped
+    /// content to the offset within the content as written, so an escape
+    /// is always covered as a whole.
+    fn written_end(&self, offset: usize) -> usize {
+        self.escapes
+            .iter()
+            .take_while(|escape| escape.content.start < offset)
+            .filter(|escape| escape.content.end <= offset)
+            .last()
+            .map_or(offset, |escape| {
+                escape.written_end + (offset - escape.content.end)
+            })
+    }
 }
 
 impl std::cmp::PartialEq for TrimmedLiteral {
@@ -83,6 +245,9 @@ impl std::cmp::PartialEq for TrimmedLiteral {
         if self.len() != other.len() {
             return false;
         }
+        if self.variant != other.variant {
+            return false;
+        }
         if self.literal.span().start() != other.literal.span().start() {
             return false;
         }
@@ -102,28 +267,50 @@ impl std::hash::Hash for TrimmedLiteral {
         self.pre.hash(hasher);
         self.post.hash(hasher);
         self.len.hash(hasher);
+        self.variant.hash(hasher);
         Span::from(self.literal.span()).hash(hasher);
     }
 }
 
 impl From<proc_macro2::Literal> for TrimmedLiteral {
     fn from(literal: proc_macro2::Literal) -> Self {
+        Self::from((literal, CommentVariant::default()))
+    }
+}
+
+impl From<(proc_macro2::Literal, CommentVariant)> for TrimmedLiteral {
+    fn from((literal, variant): (proc_macro2::Literal, CommentVariant)) -> Self {
         let rendered = literal.to_string();
         let scrap = |c: &'_ char| -> bool { c.is_whitespace() };
-        let pre = rendered.chars().take_while(scrap).count() + 1;
-        let post = rendered.chars().rev().take_while(scrap).count() + 1;
+        // raw strings such as `r#".."#` are delimited by the hashes too
+        let hashes = rendered
+            .strip_prefix('r')
+            .map(|raw| raw.chars().take_while(|c| *c == '#').count());
+        let pre =
+            rendered.chars().take_while(scrap).count() + 1 + hashes.map_or(0, |hashes| hashes + 1);
+        let post = rendered.chars().rev().take_while(scrap).count() + 1 + hashes.unwrap_or(0);
 
         let (len, pre, post) = match rendered.len() {
             len if len >= pre + post => (len - pre - post, pre, post),
             len => (len, 0, 0),
         };
 
+        // only the escapes of written out doc attributes are resolved by the
+        // compiler, while the content of a `///` comment is taken verbatim
+        let unescaped = if variant == CommentVariant::DocAttribute && hashes.is_none() {
+            Unescaped::new(&rendered[pre..pre + len])
+        } else {
+            None
+        };
+
         Self {
             len,
             literal,
+            variant,
             rendered,
             pre,
             post,
+            unescaped,
         }
     }
 }
@@ -136,10 +323,106 @@ impl std::ops::Deref for TrimmedLiteral {
 }
 
 impl TrimmedLiteral {
+    /// Replace the rendered literal with the unescaped `content`, for a
+    /// literal of a shadow source, which is only escaped to be parsable.
+    pub(crate) fn with_content(mut self, content: &str) -> Self {
+        self.rendered = format!("\"{}\"", content);
+        self.pre = 1;
+        self.post = 1;
+        self.len = content.len();
+        self.unescaped = None;
+        self
+    }
+
+    /// The content which is checked, with all escapes resolved.
     pub fn as_str(&self) -> &str {
+        match self.unescaped {
+            Some(ref unescaped) => unescaped.content.as_str(),
+            None => self.as_written_str(),
+        }
+    }
+
+    /// The content as written within the source, which is what the span
+    /// of the literal covers.
+    pub fn as_written_str(&self) -> &str {
         &self.rendered.as_str()[self.pre..(self.pre + self.len)]
     }
 
+    /// Convert the `offset` of a range start within `as_str` to the offset
+    /// within `as_written_str`.
+    pub fn written_start(&self, offset: usize) -> usize {
+        self.unescaped
+            .as_ref()
+            .map_or(offset, |unescaped| unescaped.written_start(offset))
+    }
+
+    /// Convert the exclusive `offset` of a range end within `as_str` to the
+    /// offset within `as_written_str`.
+    pub fn written_end(&self, offset: usize) -> usize {
+        self.unescaped
+            .as_ref()
+            .map_or(offset, |unescaped| unescaped.written_end(offset))
+    }
+
+    /// The position within the file of the `offset` within `as_written_str`,
+    /// relative to the start of the literal span.
+    ///
+    /// The written content of a literal may span multiple lines, i.e. with a
+    /// line continuation `\` or a literal newline.
+    fn written_line_column(&self, offset: usize) -> LineColumn {
+        let start = self.span().start();
+        let preceding = &self.rendered[..self.pre + offset];
+        match preceding.rfind('\n') {
+            Some(newline) => LineColumn {
+                line: start.line + preceding.matches('\n').count(),
+                column: preceding[newline + 1..].chars().count(),
+            },
+            // add the padding again, to make for a sane global span
+            None => LineColumn {
+                line: start.line,
+                column: start.column + self.pre + offset,
+            },
+        }
+    }
+
+    /// The range within `as_untrimmed_str` covered by `span`, the inverse of
+    /// the spans created by `find_coverage`.
+    pub fn untrimmed_range(&self, span: &Span) -> Option<Range> {
+        let start = self.untrimmed_offset(span.start)?;
+        let end = self.untrimmed_offset(LineColumn {
+            line: span.end.line,
+            column: span.end.column + 1,
+        })?;
+        if start < end {
+            Some(start..end)
+        } else {
+            None
+        }
+    }
+
+    /// The byte offset within `as_untrimmed_str` of a position within the file.
+    fn untrimmed_offset(&self, position: LineColumn) -> Option<usize> {
+        let start = self.span().start();
+        let offset = match position.line.checked_sub(start.line)? {
+            0 => position.column.checked_sub(start.column)?,
+            // the columns of any further line are those of the file
+            nth => {
+                let line_start = self.rendered.match_indices('\n').nth(nth - 1)?.0 + 1;
+                let line = self.rendered[line_start..].split('\n').next()?;
+                line.char_indices()
+                    .map(|(offset, _c)| offset)
+                    .chain(std::iter::once(line.len()))
+                    .nth(position.column)
+                    .map(|offset| line_start + offset)?
+            }
+        };
+        if offset <= self.rendered.len() {
+            Some(offset)
+        } else {
+            None
+        }
+    }
+
     pub fn as_untrimmed_str(&self) -> &str {
         &self.rendered.as_str()
     }
@@ -203,7 +486,6 @@ fn find_coverage<'a>(
             // so we have to account for that with the line length
             let len = literal.as_str().len() + 1; // account for the introduced newline
 
-            assert_eq!(literal.span().start().line, literal.span().end().line);
             state = match state {
                 LookingFor::Start => {
                     if offset >= len {
@@ -212,11 +494,7 @@ fn find_coverage<'a>(
                         LookingFor::Start
                     } else {
                         state = LookingFor::End {
-                            start: LineColumn {
-                                line: literal.span().start().line,
-                                // add the padding again, to make for a sane global span
-                                column: literal.span().start().column + offset + literal.pre,
-                            },
+                            start: literal.written_line_column(literal.written_start(offset)),
                         };
                         // the new offset we are looking for
                         offset += length;
@@ -232,14 +510,12 @@ fn find_coverage<'a>(
                         // offset += 1; // additional \n introduced when combining literals
                         state
                     } else {
+                        let end = literal.written_line_column(literal.written_end(offset));
                         let end = LineColumn {
-                            // @todo assumes start and end are on the same line for the literal
-                            line: literal.span().start().line,
-                            // add the padding again, to make for a sane global span
+                            line: end.line,
                             // substract -1 since line column are inclusive and offset += length yields exclusive
-                            column: literal.span().start().column + offset + literal.pre - 1,
+                            column: end.column.saturating_sub(1),
                         };
-                        assert_eq!(start.line, end.line);
                         // if start and end column are equiv, this is a one character match
                         return Some((acc, start, end));
                     }
@@ -278,6 +554,21 @@ impl LiteralSet {
         PlainOverlay::erase_markdown(self)
     }
 
+    /// Same as `erase_markdown`, with the adjustments of `rendering` applied.
+    pub fn erase_markdown_with(&self, rendering: &Rendering) -> PlainOverlay {
+        PlainOverlay::erase_markdown_with(self, rendering)
+    }
+
+    /// One overlay per markdown block, i.e. for grammar checks.
+    pub fn erase_markdown_chunks(&self, rendering: &Rendering) -> Vec<PlainOverlay> {
+        PlainOverlay::erase_markdown_chunks(self, rendering)
+    }
+
+    /// An overlay of the inline code spans only.
+    pub fn inline_code(&self) -> PlainOverlay {
+        PlainOverlay::inline_code(self)
+    }
+
     /// Overwrite the actual literal content with fixed content.
     ///
     /// Commonly this means with suggestions applied, content can
@@ -290,17 +581,23 @@ impl LiteralSet {
     ///
     /// Returns literl within the Err variant if not adjacent
     pub fn add_adjacent(&mut self, literal: TrimmedLiteral) -> Result<(), TrimmedLiteral> {
-        let previous_line = literal.span().end().line;
-        if previous_line == self.coverage.1 + 1 {
-            self.coverage.1 += 1;
+        // attribute strings are independent messages, never merge them with anything
+        let attribute = |literal: &TrimmedLiteral| literal.variant == CommentVariant::Attribute;
+        if attribute(&literal) || self.literals.iter().any(attribute) {
+            return Err(literal);
+        }
+
+        // a literal may span multiple lines, i.e. with a line continuation
+        let (start_line, end_line) = (literal.span().start().line, literal.span().end().line);
+        if start_line == self.coverage.1 + 1 {
+            self.coverage.1 = end_line;
             let _ = self.literals.push(literal);
             return Ok(());
         }
 
-        let next_line = literal.span().start().line;
-        if next_line + 1 == self.coverage.0 {
-            let _ = self.literals.push(literal);
-            self.coverage.1 -= 1;
+        if end_line + 1 == self.coverage.0 {
+            self.coverage.0 = start_line;
+            self.literals.insert(0, literal);
             return Ok(());
         }
 
@@ -593,4 +890,32 @@ struct Vikings;
 
     test_raw!(raw_extract_0, " livelyness", " yyy" ; 2..6, "ivel");
     test_raw!(raw_extract_1, " + 12 + x0" ; 9..10, "0");
+
+    #[test]
+    fn unescape() {
+        assert_eq!(Unescaped::new("No escapes."), None);
+
+        const WRITTEN: &str = "A\\x41\\t\\u{1F600} and \\\n    continued.";
+        let unescaped = Unescaped::new(WRITTEN).expect("Must contain escapes");
+        assert_eq!(unescaped.content, "AA\t😀 and continued.");
+        // `continued`
+        let start = unescaped.content.find('c').unwrap();
+        let end = start + "continued".len();
+        assert_eq!(
+            &WRITTEN[unescaped.written_start(start)..unescaped.written_end(end)],
+            "continued"
+        );
+        // the escapes are covered as a whole
+        let emoji = unescaped.content.find('😀').unwrap();
+        assert_eq!(
+            &WRITTEN[unescaped.written_start(1)..unescaped.written_end(emoji + 4)],
+            "\\x41\\t\\u{1F600}"
+        );
+        // the line continuation is not part of the preceding word
+        let and = unescaped.content.find("and").unwrap();
+        assert_eq!(
+            &WRITTEN[unescaped.written_start(and)..unescaped.written_end(and + 3)],
+            "and"
+        );
+    }
 }
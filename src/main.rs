@@ -1,37 +1,26 @@
-mod config;
-mod documentation;
-mod literalset;
-mod span;
-
-mod action;
-mod checker;
-mod markdown;
-mod suggestion;
-mod traverse;
-
-pub use self::action::*;
-pub use self::config::{Config, HunspellConfig, LanguageToolConfig};
-pub use self::documentation::*;
-pub use self::literalset::*;
-pub use self::markdown::*;
-pub use self::span::*;
-pub use self::suggestion::*;
+use cargo_spellcheck::*;
 
 use docopt::Docopt;
 
 use log::{info, trace, warn};
 use serde::Deserialize;
 
+use std::io::Read;
 use std::path::PathBuf;
 
 const USAGE: &str = r#"
 Spellcheck all your doc comments
 
 Usage:
-    cargo-spellcheck [(-v...|-q)] check [--cfg=<cfg>] [--checkers=<checkers>] [[--recursive] <paths>... ]
-    cargo-spellcheck [(-v...|-q)] fix [--cfg=<cfg>] [--interactive] [--checkers=<checkers>] [[--recursive] <paths>... ]
-    cargo-spellcheck [(-v...|-q)] config (--user|--stdout|--cfg=<cfg>) [--force]
-    cargo-spellcheck [(-v...|-q)] [--cfg=<cfg>] [--fix [--interactive]] [--checkers=<checkers>] [[--recursive] <paths>... ]
+    cargo-spellcheck [(-v...|-q)] [--color=<when>] check [--cfg=<cfg>] [--checkers=<checkers>] [--format=<format>] [--no-cache] [--diff=<range>] [--include=<glob>...] [--exclude=<glob>...] [--no-ignore] [[--recursive] <paths>... ]
+    cargo-spellcheck [(-v...|-q)] [--color=<when>] check [--cfg=<cfg>] [--checkers=<checkers>] [--format=<format>] [--no-cache] --stdin [--stdin-kind=<kind>]
+    cargo-spellcheck [(-v...|-q)] [--color=<when>] check [--cfg=<cfg>] [--checkers=<checkers>] [--format=<format>] [--no-cache] --commit-msg=<file>
+    cargo-spellcheck [(-v...|-q)] [--color=<when>] fix [--cfg=<cfg>] [--interactive|--replace-with-first|--dry-run] [--checkers=<checkers>] [--no-cache] [--diff=<range>] [--include=<glob>...] [--exclude=<glob>...] [--no-ignore] [[--recursive] <paths>... ]
+    cargo-spellcheck [(-v...|-q)] [--color=<when>] fix [--cfg=<cfg>] [--replace-with-first|--dry-run] [--checkers=<checkers>] [--no-cache] --stdin [--stdin-kind=<kind>]
+    cargo-spellcheck [(-v...|-q)] [--color=<when>] config (--user|--stdout|--cfg=<cfg>) [--force]
+    cargo-spellcheck [(-v...|-q)] [--color=<when>] --dump-accepted [--cfg=<cfg>] [--checkers=<checkers>]
+    cargo-spellcheck [(-v...|-q)] [--color=<when>] [--cfg=<cfg>] [--fix [--interactive|--replace-with-first|--dry-run]] [--checkers=<checkers>] [--format=<format>] [--no-cache] [--diff=<range>] [--include=<glob>...] [--exclude=<glob>...] [--no-ignore] [[--recursive] <paths>... ]
+    cargo-spellcheck [(-v...|-q)] [--color=<when>] [--cfg=<cfg>] [--fix [--replace-with-first|--dry-run]] [--checkers=<checkers>] [--format=<format>] [--no-cache] --stdin [--stdin-kind=<kind>]
     cargo-spellcheck --help
     cargo-spellcheck --version
 
@@ -41,16 +30,44 @@ Options:
 
   --fix                   Synonym to running the `fix` subcommand.
   -i --interactive        Interactively apply spelling and grammer fixes.
+  --replace-with-first    Blindly apply the first replacement of every suggestion
+                          which has one, without any further restrictions.
+  --dry-run               Print a unified diff of the fixes instead of applying them.
   -r --recursive          If a path is provided, if recursion into subdirectories is desired.
   --checkers=<checkers>   Calculate the intersection between
                           configured by config file and the ones provided on commandline.
+  --format=<format>       Output format of the check results, one of `human`, `junit`,
+                          `checkstyle`, `json`, `sarif` or `github`, the latter being
+                          the default within GitHub Actions.
+  --diff=<range>          Only report findings in lines changed by `git diff <range>`,
+                          i.e. `origin/main...HEAD` for a branch or `HEAD` for the working tree.
+  --include=<glob>        Only check discovered files matching the glob, in addition to
+                          the configured `include` globs. May be given multiple times.
+  --exclude=<glob>        Never check discovered files matching the glob, in addition to
+                          the configured `exclude` globs. Overrules `--include`.
+  --no-ignore             Also check files within directories which are ignored by
+                          `.gitignore` or `.ignore` files.
+  --stdin                 Check the document read from stdin, which is passed through
+                          to stdout, the results are printed to stderr. Fixes are
+                          written to stdout instead.
+  --stdin-kind=<kind>     Kind of the document read from stdin, one of `rust`,
+                          `markdown`, `rst` or `text`. [default: rust]
+  --commit-msg=<file>     Check the commit message file passed to a `commit-msg` hook
+                          as plain text, without the comment lines and the diff
+                          appended by git.
+  --no-cache              Check all files, instead of reusing the cached results
+                          of files which did not change since the last run.
   -f --force              Overwrite any existing configuration file. [default=false]
   -c --cfg=<cfg>          Use a non default configuration file.
                           Passing a directory will attempt to open `cargo_spellcheck.toml` in that directory.
   --user                  Write the configuration file to the default user configuration directory.
   --stdout                Print the configuration file to stdout and exit.
+  --dump-accepted         Print all dictionaries, extra words, ignored patterns and
+                          heuristics which let a word pass and exit.
   -v --verbose            Verbosity level.
   -q --quiet              Silences all printed messages. Overrules `-v`.
+  --color=<when>          Color the output, one of `auto`, `always` or `never`.
+                          `auto` colors a terminal unless `NO_COLOR` is set.
 
 "#;
 
@@ -59,6 +76,8 @@ struct Args {
     arg_paths: Vec<PathBuf>,
     flag_fix: bool,
     flag_interactive: bool,
+    flag_replace_with_first: bool,
+    flag_dry_run: bool,
     flag_recursive: bool,
     flag_verbose: usize,
     flag_quiet: bool,
@@ -66,9 +85,20 @@ struct Args {
     flag_help: bool,
     flag_checkers: Option<String>,
     flag_cfg: Option<PathBuf>,
+    flag_format: Option<OutputFormat>,
+    flag_color: Option<ColorChoice>,
     flag_force: bool,
     flag_user: bool,
     flag_stdout: bool,
+    flag_dump_accepted: bool,
+    flag_no_cache: bool,
+    flag_diff: Option<String>,
+    flag_include: Vec<String>,
+    flag_exclude: Vec<String>,
+    flag_no_ignore: bool,
+    flag_stdin: bool,
+    flag_stdin_kind: Option<DocumentKind>,
+    flag_commit_msg: Option<PathBuf>,
     cmd_fix: bool,
     cmd_check: bool,
     cmd_config: bool,
@@ -125,6 +155,8 @@ fn main() -> anyhow::Result<()> {
         .filter_level(verbosity)
         .init();
 
+    args.flag_color.unwrap_or_default().apply();
+
     if args.flag_version {
         println!("cargo-spellcheck {}", env!("CARGO_PKG_VERSION"));
         return Ok(());
@@ -152,6 +184,21 @@ fn main() -> anyhow::Result<()> {
                     warn!("Languagetool was never configured.")
                 }
             }
+            if !checkers.contains(&"nlprule".to_owned()) {
+                if !config.nlprule.take().is_some() {
+                    warn!("Nlprule was never configured.")
+                }
+            }
+            if !checkers.contains(&"repetition".to_owned()) {
+                if !config.repetition.take().is_some() {
+                    warn!("Repetition was never configured.")
+                }
+            }
+            if !checkers.contains(&"wordlist".to_owned()) {
+                if !config.wordlist.take().is_some() {
+                    warn!("Wordlist was never configured.")
+                }
+            }
         }
     };
 
@@ -210,24 +257,104 @@ fn main() -> anyhow::Result<()> {
     };
 
     checkers(&mut config);
+    config.include.extend(args.flag_include.iter().cloned());
+    config.exclude.extend(args.flag_exclude.iter().cloned());
+    config.no_ignore |= args.flag_no_ignore;
+
+    if args.flag_dump_accepted {
+        print!("{}", config.dump_accepted()?);
+        return Ok(());
+    }
 
     // extract operation mode
     let action = if args.flag_interactive {
         Action::Interactive
+    } else if args.flag_dry_run {
+        Action::DryRun
+    } else if args.flag_replace_with_first {
+        Action::ReplaceWithFirst
     } else if args.cmd_fix || args.flag_fix {
         Action::Fix
     } else {
         // check
-        Action::Check
+        Action::Check(args.flag_format.unwrap_or_else(OutputFormat::detect))
     };
 
     trace!("Executing: {:?} with {:?}", action, &config);
 
+    let cache = if args.flag_no_cache {
+        None
+    } else {
+        match Config::cache_dir().and_then(checker::Cache::new) {
+            Ok(cache) => Some(cache),
+            Err(e) => {
+                warn!("Checking without cache, due to: {}", e);
+                None
+            }
+        }
+    };
+
+    if args.flag_stdin {
+        let kind = args.flag_stdin_kind.unwrap_or(DocumentKind::Rust);
+        let mut content = String::with_capacity(4096);
+        std::io::stdin()
+            .read_to_string(&mut content)
+            .map_err(|e| anyhow::anyhow!("Failed to read stdin").context(e))?;
+        let path = PathBuf::from("<stdin>");
+        let documentation = traverse::load_str(&path, content.as_str(), kind, &config)?;
+        let suggestion_set = check_with(
+            &documentation,
+            &config,
+            cache.as_ref(),
+            &mut |_path| (),
+            |_path| Some(content.clone()),
+        )?;
+        return action.run_stdin(
+            &path,
+            content.as_str(),
+            suggestion_set,
+            &config,
+            args.flag_quiet,
+        );
+    }
+
+    if let Some(path) = args.flag_commit_msg.as_ref() {
+        let documentation = traverse::load_commit_message(path)?;
+        // the comment lines are blanked out in place, so the file still
+        // holds the directives at the lines of the findings
+        let suggestion_set = check_with(
+            &documentation,
+            &config,
+            cache.as_ref(),
+            &mut |_path| (),
+            |path| std::fs::read_to_string(path).ok(),
+        )?;
+        return action.run(suggestion_set, &config, args.flag_quiet);
+    }
+
     let combined = traverse::collect(args.arg_paths, args.flag_recursive, &config)?;
 
-    let suggestion_set = checker::check(&combined, &config)?;
+    // the results are only printed once all files are checked, until then
+    // the progress is shown, but not for fixes which print nothing at all
+    let progress = progress::Progress::new(
+        combined.len() * checker::detectors(&config).count(),
+        !args.flag_quiet && matches!(action, Action::Check(_) | Action::Interactive),
+    );
+    let mut suggestion_set = check_with(
+        &combined,
+        &config,
+        cache.as_ref(),
+        &mut |_path| progress.inc(),
+        |path| std::fs::read_to_string(path).ok(),
+    )?;
+    // must be gone before the interactive mode takes over the terminal
+    progress.finish();
+
+    if let Some(range) = args.flag_diff.as_ref() {
+        diff::ChangedLines::from_git(range)?.retain(&mut suggestion_set)?;
+    }
 
-    action.run(suggestion_set, &config)
+    action.run(suggestion_set, &config, args.flag_quiet)
 }
 
 #[cfg(test)]
@@ -259,9 +386,60 @@ mod tests {
             "cargo-spellcheck fix --interactive -r file.rs",
             "cargo-spellcheck -q fix --interactive Cargo.toml",
             "cargo spellcheck -v fix --interactive Cargo.toml",
+            "cargo spellcheck check --format=junit",
+            "cargo spellcheck --format=human -r src",
+            "cargo-spellcheck check --format=json",
+            "cargo-spellcheck check --format=checkstyle",
+            "cargo-spellcheck check --format=github",
+            "cargo-spellcheck check --format=sarif --diff=HEAD",
+            "cargo-spellcheck --dump-accepted",
+            "cargo-spellcheck fix --replace-with-first",
+            "cargo-spellcheck fix --dry-run src/lib.rs",
+            "cargo-spellcheck --fix --dry-run",
+            "cargo-spellcheck --fix --replace-with-first -r src",
+            "cargo-spellcheck spellcheck --dump-accepted --cfg=spellcheck.toml",
+            "cargo-spellcheck --no-cache",
+            "cargo-spellcheck check --no-cache -r src",
+            "cargo-spellcheck fix --interactive --no-cache",
+            "cargo-spellcheck --diff=origin/main...HEAD",
+            "cargo-spellcheck --color=never check",
+            "cargo-spellcheck -v --color=always fix --interactive",
+            "cargo-spellcheck check --diff=HEAD --format=junit",
+            "cargo-spellcheck fix --interactive --diff=HEAD~3",
+            "cargo-spellcheck check --stdin",
+            "cargo-spellcheck spellcheck --stdin",
+            "cargo-spellcheck --fix --stdin --stdin-kind=text",
+            "cargo-spellcheck check --stdin --stdin-kind=markdown --format=json",
+            "cargo-spellcheck fix --replace-with-first --stdin --stdin-kind=rst",
+            "cargo-spellcheck --include=src/**/*.rs --exclude=*.md",
+            "cargo-spellcheck check --exclude=target/** --exclude=vendor/** -r src",
+            "cargo-spellcheck fix --interactive --include=*.md",
+            "cargo-spellcheck check --no-ignore -r .",
+            "cargo-spellcheck --no-ignore --exclude=target/**",
+            "cargo-spellcheck check --commit-msg=.git/COMMIT_EDITMSG",
+            "cargo-spellcheck -q check --format=github --commit-msg=.git/COMMIT_EDITMSG",
         ];
         for command in commands {
             assert!(parse_args(commandline_to_iter(command)).is_ok());
         }
     }
+
+    #[test]
+    fn format() {
+        let args = parse_args(commandline_to_iter(
+            "cargo-spellcheck check --format=github",
+        ))
+        .unwrap();
+        assert_eq!(args.flag_format, Some(OutputFormat::GitHub));
+    }
+
+    #[test]
+    fn globs() {
+        let args = parse_args(commandline_to_iter(
+            "cargo-spellcheck check --include=*.md --include=src/** --exclude=README.md",
+        ))
+        .unwrap();
+        assert_eq!(args.flag_include, vec!["*.md", "src/**"]);
+        assert_eq!(args.flag_exclude, vec!["README.md"]);
+    }
 }
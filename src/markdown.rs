@@ -6,11 +6,35 @@ use super::*;
 use crate::Span;
 
 use log::trace;
-use pulldown_cmark::{Event, Options, Parser, Tag};
+use pulldown_cmark::{Event, LinkType, Options, Parser, Tag};
 
 use crate::literalset::{LiteralSet, Range};
 
 use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+mod rst;
+
+/// Adjustments of the plain text, i.e. for grammar checkers, none of which
+/// are applied by default.
+///
+/// Shared by the configurations of all checkers, which flatten it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Rendering {
+    /// Render a soft break as a single space instead of a newline, so a
+    /// sentence wrapped across lines remains a single sentence.
+    pub soft_break_as_space: bool,
+    /// Replace inline code with this word instead of dropping it, so the
+    /// sentence remains grammatical. Suggestions within it are dropped.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code_placeholder: Option<String>,
+    /// Also check the title of links and images, the anchor and alt text
+    /// are always checked while the url never is.
+    pub link_titles: bool,
+    /// Skip inline `$...$` and display `$$...$$` math, as rendered by KaTeX.
+    pub skip_math: bool,
+}
 
 /// A plain representation of markdown riddled set of trimmed literals.
 #[derive(Clone)]
@@ -21,6 +45,236 @@ pub struct PlainOverlay<'a> {
     // key: plain string range
     // value: the corresponding areas in the full markdown
     mapping: IndexMap<Range, Range>,
+    // plain ranges standing in for something else entirely, i.e. inline code
+    placeholders: Vec<Range>,
+}
+
+/// Length of the marker of a GitHub flavored callout, i.e. `[!WARNING]`,
+/// if `s` starts with one.
+fn callout_marker(s: &str) -> Option<usize> {
+    const KINDS: &[&str] = &["NOTE", "TIP", "IMPORTANT", "WARNING", "CAUTION"];
+    let kind = s.strip_prefix("[!")?;
+    let len = kind.find(']')?;
+    if KINDS
+        .iter()
+        .any(|known| known.eq_ignore_ascii_case(&kind[..len]))
+    {
+        Some("[!]".len() + len)
+    } else {
+        None
+    }
+}
+
+/// Length of a leading YAML front matter block as used by static site
+/// generators, i.e. `---\nlayout: post\n---\n`, `0` if there is none.
+///
+/// A leading horizontal rule is not followed by a `key:` line and closing fence.
+fn front_matter(markdown: &str) -> usize {
+    let mut lines = markdown.split_inclusive('\n');
+    let mut len = match lines.next() {
+        Some(line) if line.ends_with('\n') && line.trim_end() == "---" => line.len(),
+        _ => return 0,
+    };
+    for (idx, line) in lines.enumerate() {
+        len += line.len();
+        let line = line.trim_end();
+        if idx == 0 {
+            if line.starts_with(char::is_whitespace) || !line.contains(':') {
+                return 0;
+            }
+        } else if line == "---" || line == "..." {
+            return len;
+        }
+    }
+    0
+}
+
+/// The lowercase name of a single html tag and if it is a closing one, `None`
+/// for comments, multiple tags and other markup.
+fn html_tag(html: &str) -> Option<(String, bool)> {
+    let tag = html.trim().strip_prefix('<')?;
+    if tag.contains('<') {
+        return None;
+    }
+    let (tag, closing) = match tag.strip_prefix('/') {
+        Some(tag) => (tag, true),
+        None => (tag, false),
+    };
+    let name = tag
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric())
+        .collect::<String>();
+    if name.is_empty() {
+        None
+    } else {
+        Some((name.to_ascii_lowercase(), closing))
+    }
+}
+
+/// Ranges of inline `$...$` and display `$$...$$` math, including the delimiters.
+///
+/// An inline opening `$` must not be followed by whitespace and the closing
+/// one on the same line must neither be preceded by whitespace nor followed
+/// by a digit, so amounts such as `$5 or $10` are not considered math. Escaped
+/// dollars and code spans are skipped.
+fn math_ranges(markdown: &str) -> Vec<Range> {
+    let bytes = markdown.as_bytes();
+    let mut ranges = Vec::new();
+    let mut idx = 0usize;
+    while idx < bytes.len() {
+        match bytes[idx] {
+            b'\\' => idx += 2,
+            b'`' => {
+                let run = bytes[idx..].iter().take_while(|b| **b == b'`').count();
+                let fence = &markdown[idx..idx + run];
+                idx += run;
+                if let Some(end) = markdown[idx..].find(fence) {
+                    idx += end + run;
+                }
+            }
+            b'$' if bytes.get(idx + 1) == Some(&b'$') => match markdown[idx + 2..].find("$$") {
+                Some(end) => {
+                    let end = idx + 2 + end + 2;
+                    ranges.push(idx..end);
+                    idx = end;
+                }
+                None => idx += 2,
+            },
+            b'$' => {
+                let start = idx;
+                idx += 1;
+                let opens = markdown[idx..]
+                    .chars()
+                    .next()
+                    .map_or(false, |c| !c.is_whitespace());
+                if !opens {
+                    continue;
+                }
+                let line = markdown[idx..]
+                    .find('\n')
+                    .map_or(markdown.len(), |end| idx + end);
+                let closing = markdown[idx..line].char_indices().find(|(offset, c)| {
+                    let at = idx + offset;
+                    *c == '$'
+                        && !markdown[..at].ends_with(char::is_whitespace)
+                        && !markdown[..at].ends_with('\\')
+                        && !markdown[at + 1..].starts_with(|c: char| c.is_ascii_digit())
+                });
+                if let Some((offset, _)) = closing {
+                    idx += offset + 1;
+                    ranges.push(start..idx);
+                }
+            }
+            _ => idx += 1,
+        }
+    }
+    ranges
+}
+
+/// Raw ranges of the titles of all lines looking like a reference definition,
+/// i.e. `[id]: https://ahoi.io "Title"`, after `offset`.
+///
+/// The label and the destination are never checked, neither are titles
+/// continued on the next line.
+fn reference_titles(markdown: &str, offset: usize) -> Vec<Range> {
+    lazy_static::lazy_static! {
+        static ref DEFINITION: regex::Regex = regex::Regex::new(
+            r#"(?m)^ {0,3}\[[^\]]+\]:[ \t]*(?:<[^>]*>|\S+)[ \t]+(?:"([^"]*)"|'([^']*)'|\(([^)]*)\))[ \t]*$"#
+        )
+        .expect("Reference definition regex is valid. qed");
+    }
+    DEFINITION
+        .captures_iter(&markdown[offset..])
+        .filter_map(|captures| (1..=3).find_map(|idx| captures.get(idx)))
+        .filter(|title| !title.as_str().is_empty())
+        .map(|title| (offset + title.start())..(offset + title.end()))
+        .collect()
+}
+
+/// If a fenced code block with the given info string, i.e. `rust,no_run`, is rust.
+fn is_rust_fence(kind: &pulldown_cmark::CodeBlockKind) -> bool {
+    match kind {
+        pulldown_cmark::CodeBlockKind::Fenced(info) => {
+            info.split(',').any(|attribute| attribute.trim() == "rust")
+        }
+        pulldown_cmark::CodeBlockKind::Indented => false,
+    }
+}
+
+/// Length of the char literal `s` starts with, i.e. `'"'`, `'\''` or
+/// `'\u{22}'`, `None` for anything else such as a lifetime.
+fn char_literal(s: &str) -> Option<usize> {
+    let rest = s.strip_prefix('\'')?;
+    let len = if rest.starts_with('\\') {
+        // the escaped character is ascii, the literal ends with the next quote
+        2 + rest.get(2..)?.find('\'')?
+    } else {
+        let c = rest.chars().next()?;
+        if !rest[c.len_utf8()..].starts_with('\'') {
+            return None;
+        }
+        c.len_utf8()
+    };
+    Some(1 + len + 1)
+}
+
+/// Ranges of the content of all line comments in a piece of rust `code`,
+/// excluding the `//`, `///` or `//!` prefix and the following space.
+fn line_comments(code: &str) -> Vec<Range> {
+    let mut comments = Vec::new();
+    let mut offset = 0usize;
+    for line in code.split('\n') {
+        let start = offset;
+        offset += line.len() + 1;
+        // a `//` within a string literal, i.e. an url, is not a comment
+        let mut in_string = false;
+        let mut escaped = false;
+        let mut previous = None;
+        let mut found = None;
+        let mut chars = line.char_indices().peekable();
+        while let Some((idx, c)) = chars.next() {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' if in_string => escaped = true,
+                '"' => in_string = !in_string,
+                '/' if !in_string && previous == Some('/') => {
+                    found = Some(idx + 1);
+                    break;
+                }
+                '\'' if !in_string => {
+                    // a quote within a char literal does not start a string
+                    if let Some(len) = char_literal(&line[idx..]) {
+                        while chars.peek().map_or(false, |(next, _c)| *next < idx + len) {
+                            let _ = chars.next();
+                        }
+                    }
+                }
+                _ => {}
+            }
+            previous = Some(c);
+        }
+        if let Some(mut begin) = found {
+            let rest = &line[begin..];
+            if rest.starts_with('/') || rest.starts_with('!') {
+                begin += 1;
+            }
+            if line[begin..].starts_with(' ') {
+                begin += 1;
+            }
+            let end = line.trim_end().len().max(begin);
+            if begin < end {
+                comments.push((start + begin)..(start + end));
+            }
+        }
+    }
+    comments
+}
+
+/// If the literals are the lines of a markdown file rather than a doc comment.
+fn is_markdown_file(literals: &[&TrimmedLiteral]) -> bool {
+    literals
+        .iter()
+        .all(|literal| literal.variant == CommentVariant::CommonMark)
 }
 
 impl<'a> PlainOverlay<'a> {
@@ -28,6 +282,13 @@ impl<'a> PlainOverlay<'a> {
         // map the range within the plain data,
         // which is fed to the checker,
         // back to the repr with markdown modifiers
+        //
+        // an empty string yields an empty plain range, which would
+        // collide with the key of the next non-empty chunk and
+        // overwrite its mapping, so there is nothing to track
+        if s.is_empty() {
+            return;
+        }
         let _ = mapping.insert(
             Range {
                 start: plain.len(),
@@ -38,6 +299,77 @@ impl<'a> PlainOverlay<'a> {
         plain.push_str(&s);
     }
 
+    /// Track the `title` of a link or image spanning `link` in the raw markdown.
+    ///
+    /// Titles of reference links are part of the definition and escaped
+    /// titles differ from the raw text, neither of which are tracked.
+    fn track_title(
+        title: &str,
+        markdown: &str,
+        link: Range,
+        plain: &mut String,
+        mapping: &mut IndexMap<Range, Range>,
+    ) {
+        if title.is_empty() {
+            return;
+        }
+        let idx = if let Some(idx) = markdown[link.clone()].rfind(title) {
+            idx
+        } else {
+            trace!("Title >{}< not found within the link {:?}", title, &link);
+            return;
+        };
+        if !plain.is_empty() && !plain.ends_with(char::is_whitespace) {
+            plain.push(' ');
+        }
+        let start = link.start + idx;
+        Self::track(title, start..start + title.len(), plain, mapping);
+    }
+
+    /// Track the parts of the text `s` at `raw` which are not covered by
+    /// any of the `excluded` ranges.
+    ///
+    /// Text which differs from the raw markdown, i.e. due to escapes, can not
+    /// be split and is dropped if it overlaps.
+    fn track_outside(
+        s: &str,
+        markdown: &str,
+        raw: Range,
+        excluded: &[Range],
+        plain: &mut String,
+        mapping: &mut IndexMap<Range, Range>,
+    ) {
+        if s != &markdown[raw.clone()] {
+            return;
+        }
+        let mut start = raw.start;
+        for excluded in excluded
+            .iter()
+            .filter(|excluded| excluded.start < raw.end && raw.start < excluded.end)
+        {
+            if start < excluded.start {
+                Self::track(
+                    &markdown[start..excluded.start],
+                    start..excluded.start,
+                    plain,
+                    mapping,
+                );
+            }
+            start = start.max(excluded.end);
+        }
+        if start < raw.end {
+            Self::track(&markdown[start..raw.end], start..raw.end, plain, mapping);
+        }
+    }
+
+    /// Separate what follows like a paragraph, unless there is nothing yet.
+    fn paragraph_break(plain: &mut String) {
+        let trailing = plain.chars().rev().take_while(|c| *c == '\n').count();
+        if !plain.is_empty() && trailing < 2 {
+            Self::newlines(plain, 2 - trailing);
+        }
+    }
+
     fn newlines(plain: &mut String, n: usize) {
         for _ in 0..n {
             plain.push('\n');
@@ -45,74 +377,222 @@ impl<'a> PlainOverlay<'a> {
     }
 
     /// ranges are mapped `plain -> raw`
-    fn extract_plain_with_mapping(markdown: &str) -> (String, IndexMap<Range, Range>) {
+    #[cfg(test)]
+    fn extract_plain_with_mapping(
+        markdown: &str,
+        rendering: &Rendering,
+    ) -> (String, IndexMap<Range, Range>) {
+        let (plain, mapping, _blocks, _placeholders) =
+            Self::extract_plain_with_blocks(markdown, rendering, true);
+        (plain, mapping)
+    }
+
+    /// Same as `extract_plain_with_mapping`, additionally yields the end
+    /// of each block such as a paragraph, list item or heading within the
+    /// plain text and the plain ranges of all code placeholders.
+    ///
+    /// Only markdown files may start with a front matter, `file` is `false`
+    /// for doc comments.
+    fn extract_plain_with_blocks(
+        markdown: &str,
+        rendering: &Rendering,
+        file: bool,
+    ) -> (String, IndexMap<Range, Range>, Vec<usize>, Vec<Range>) {
+        let mut blocks = Vec::with_capacity(16);
+        let mut placeholders = Vec::new();
         let mut plain = String::with_capacity(markdown.len());
         let mut mapping = indexmap::IndexMap::with_capacity(128);
 
-        let parser = Parser::new_ext(markdown, Options::all());
-
-        let rust_fence =
-            pulldown_cmark::CodeBlockKind::Fenced(pulldown_cmark::CowStr::Borrowed("rust"));
+        // the front matter is not prose, offsets remain relative to `markdown`
+        let front_matter = if file { front_matter(markdown) } else { 0 };
+        let parser = Parser::new_ext(&markdown[front_matter..], Options::all());
 
         let mut code_block = false;
+        // the comments of rust code blocks are checked
+        let mut rust_code_block = false;
+        // autolinks such as `<https://ahoi.io>` only contain the url as text,
+        // which must not end up in the plain text
+        let mut autolink = false;
+        // a block quote may be a callout such as `> [!NOTE]`
+        let mut block_quote = false;
+        // end of the callout marker, which is not prose
+        let mut callout_marker_end = 0usize;
+        let math = if rendering.skip_math {
+            math_ranges(&markdown[front_matter..])
+                .into_iter()
+                .map(|range| (range.start + front_matter)..(range.end + front_matter))
+                .collect::<Vec<_>>()
+        } else {
+            Vec::new()
+        };
+        // inline html tags are stripped, the text within is checked unless it is code
+        let mut html_code = 0usize;
+        // inline code is never prose, whichever block it is nested in
+        let mut code_spans = Vec::<Range>::with_capacity(16);
 
+        // reference definitions do not yield any events, but the blocks
+        // containing lines which look alike do
+        let mut depth = 0usize;
+        let mut top_level = Vec::with_capacity(32);
         for (event, offset) in parser.into_offset_iter() {
+            let offset = (offset.start + front_matter)..(offset.end + front_matter);
             trace!("Parsing event ({:?}): {:?}", &offset, &event);
+            if depth == 0 {
+                top_level.push(offset.clone());
+            }
+            match event {
+                Event::Start(_) => depth += 1,
+                Event::End(_) => depth = depth.saturating_sub(1),
+                _ => {}
+            }
             match event {
                 Event::Start(tag) => {
-                    // @todo check links
+                    if let Tag::Paragraph = tag {
+                        if block_quote {
+                            if let Some(len) = callout_marker(&markdown[offset.start..]) {
+                                callout_marker_end = offset.start + len;
+                            }
+                        }
+                    }
+                    block_quote = tag == Tag::BlockQuote;
                     match tag {
+                        Tag::Link(link_type, _url, _title) => {
+                            autolink =
+                                link_type == LinkType::Autolink || link_type == LinkType::Email;
+                        }
                         Tag::CodeBlock(fenced) => {
                             code_block = true;
-
-                            if fenced == rust_fence {
-                                // @todo validate as if it was another document entity
-                            }
+                            rust_code_block = is_rust_fence(&fenced);
                         }
-
                         _ => {}
                     }
                 }
                 Event::End(tag) => {
+                    let block = matches!(
+                        tag,
+                        Tag::Paragraph
+                            | Tag::Heading(_)
+                            | Tag::Item
+                            | Tag::CodeBlock(_)
+                            | Tag::Table(_)
+                            | Tag::BlockQuote
+                    );
                     match tag {
                         Tag::Link(_link_type, _url, title) => {
-                            // @todo check links
-                            Self::track(&title, offset, &mut plain, &mut mapping);
+                            // the anchor text was tracked as text already
+                            if autolink {
+                                autolink = false;
+                            } else if rendering.link_titles {
+                                Self::track_title(
+                                    &title,
+                                    markdown,
+                                    offset,
+                                    &mut plain,
+                                    &mut mapping,
+                                );
+                            }
                         }
                         Tag::Image(_link_type, _url, title) => {
-                            Self::track(&title, offset, &mut plain, &mut mapping);
+                            // the alt text was tracked as text already
+                            if rendering.link_titles {
+                                Self::track_title(
+                                    &title,
+                                    markdown,
+                                    offset,
+                                    &mut plain,
+                                    &mut mapping,
+                                );
+                            }
                         }
                         Tag::Heading(_n) => {
                             Self::newlines(&mut plain, 2);
                         }
-                        Tag::CodeBlock(fenced) => {
+                        Tag::CodeBlock(_fenced) => {
                             code_block = false;
-
-                            if fenced == rust_fence {
-                                // @todo validate as if it was another document entity
+                            if rust_code_block && plain.ends_with('\n') {
+                                // each comment is followed by a newline already
+                                if !plain.ends_with("\n\n") {
+                                    Self::newlines(&mut plain, 1);
+                                }
                             }
+                            rust_code_block = false;
                         }
                         Tag::Paragraph => Self::newlines(&mut plain, 2),
+                        // separated like a paragraph, whatever the quote ends with
+                        Tag::BlockQuote => Self::paragraph_break(&mut plain),
+                        // tight list items lack a paragraph
+                        Tag::Item if !plain.ends_with('\n') => Self::newlines(&mut plain, 1),
+                        // cells are separated, so their sentences do not run into each other,
+                        // the pipes are never part of the text
+                        Tag::TableCell | Tag::TableHead | Tag::TableRow | Tag::Table(_) => {
+                            Self::newlines(&mut plain, 1)
+                        }
                         _ => {}
                     }
+                    if block {
+                        blocks.push(plain.len());
+                        // an unclosed inline tag must not swallow the following blocks
+                        html_code = 0;
+                    }
                 }
                 Event::Text(s) => {
-                    if code_block {
+                    if rust_code_block {
+                        // the raw code, the text may lack the indentation
+                        let code = &markdown[offset.clone()];
+                        for comment in line_comments(code) {
+                            let raw = (offset.start + comment.start)..(offset.start + comment.end);
+                            Self::track(&code[comment], raw, &mut plain, &mut mapping);
+                            Self::newlines(&mut plain, 1);
+                        }
+                    } else if code_block
+                        || autolink
+                        || html_code > 0
+                        || offset.end <= callout_marker_end
+                    {
+                    } else if math
+                        .iter()
+                        .any(|math| math.start < offset.end && offset.start < math.end)
+                    {
+                        Self::track_outside(&s, markdown, offset, &math, &mut plain, &mut mapping);
                     } else {
                         Self::track(&s, offset, &mut plain, &mut mapping);
                     }
                 }
                 Event::Code(_s) => {
-                    // @todo extract comments from the doc comment and in the distant
-                    // future potentially also check var names with leviatan distance
-                    // to wordbook entries, and only complain if there are sane suggestions
+                    // identifiers are checked separately, see `inline_code`
+                    code_spans.push(offset.clone());
+                    if let Some(placeholder) = rendering.code_placeholder.as_ref() {
+                        placeholders.push(plain.len()..plain.len() + placeholder.len());
+                        Self::track(placeholder, offset, &mut plain, &mut mapping);
+                    }
                 }
-                Event::Html(_s) => {}
+                Event::Html(s) => match html_tag(&s) {
+                    Some((name, closing))
+                        if ["code", "kbd", "samp", "pre"].contains(&name.as_str()) =>
+                    {
+                        if closing {
+                            html_code = html_code.saturating_sub(1);
+                        } else {
+                            html_code += 1;
+                        }
+                    }
+                    Some((name, false)) if name == "br" => Self::newlines(&mut plain, 1),
+                    _ => {}
+                },
                 Event::FootnoteReference(_s) => {
                     // @todo handle footnotes
                 }
                 Event::SoftBreak => {
-                    Self::newlines(&mut plain, 1);
+                    if offset.start == callout_marker_end {
+                    } else if rendering.soft_break_as_space {
+                        // a single character, mapped to the newline itself
+                        let newline = markdown[offset.clone()]
+                            .find('\n')
+                            .map_or(offset.start, |idx| offset.start + idx);
+                        Self::track(" ", newline..newline + 1, &mut plain, &mut mapping);
+                    } else {
+                        Self::newlines(&mut plain, 1);
+                    }
                 }
                 Event::HardBreak => {
                     Self::newlines(&mut plain, 2);
@@ -124,67 +604,286 @@ impl<'a> PlainOverlay<'a> {
             }
         }
 
+        if rendering.link_titles {
+            for title in reference_titles(markdown, front_matter) {
+                if top_level
+                    .iter()
+                    .any(|block| block.start <= title.start && title.end <= block.end)
+                {
+                    continue;
+                }
+                Self::paragraph_break(&mut plain);
+                Self::track(&markdown[title.clone()], title, &mut plain, &mut mapping);
+            }
+        }
+
+        // only a placeholder may stand in for inline code, as a whole, anything
+        // else overlapping it is not prose and must never be pointed at
+        mapping.retain(|plain: &Range, raw: &mut Range| {
+            let leaked = code_spans
+                .iter()
+                .any(|code| raw != code && raw.start < code.end && code.start < raw.end);
+            if leaked {
+                trace!("Dropping {:?} -> {:?} overlapping inline code", plain, raw);
+            }
+            !leaked
+        });
+
         // the parser yields single lines as a paragraph, for which we add trailing newlines
         // which are pointless and clutter the test strings, so track and remove them
+        // note that for empty or whitespace only documents, there is nothing
+        // tracked and `plain` is empty
         let trailing_newlines = plain.chars().rev().take_while(|x| *x == '\n').count();
-        if trailing_newlines <= plain.len() {
-            plain.truncate(plain.len() - trailing_newlines)
-        }
-        if let Some((mut plain_range, raw_range)) = mapping.pop() {
+        plain.truncate(plain.len() - trailing_newlines);
+        if let Some((mut plain_range, mut raw_range)) = mapping.pop() {
             if plain_range.end > plain.len() {
+                // shrink both ranges alike, so the linear offset remains valid
+                let excess = plain_range.end - plain.len();
                 plain_range.end = plain.len();
+                raw_range.end = raw_range.end.saturating_sub(excess);
+            }
+            // drop the mapping entirely if nothing remains
+            if plain_range.start < plain_range.end {
+                mapping.insert(plain_range, raw_range);
+            }
+        }
+        (plain, mapping, blocks, placeholders)
+    }
+
+    /// The contents of all inline code spans, one per line, ranges are mapped `plain -> raw`.
+    fn extract_inline_code(markdown: &str, file: bool) -> (String, IndexMap<Range, Range>) {
+        let mut plain = String::with_capacity(64);
+        let mut mapping = IndexMap::with_capacity(8);
+        let front_matter = if file { front_matter(markdown) } else { 0 };
+        let parser = Parser::new_ext(&markdown[front_matter..], Options::all());
+        for (event, offset) in parser.into_offset_iter() {
+            if let Event::Code(code) = event {
+                let offset = (offset.start + front_matter)..(offset.end + front_matter);
+                // the content without the backticks and the padding space
+                if let Some(idx) = markdown[offset.clone()].find(code.as_ref()) {
+                    let start = offset.start + idx;
+                    Self::track(&code, start..start + code.len(), &mut plain, &mut mapping);
+                    Self::newlines(&mut plain, 1);
+                }
+            }
+        }
+        (plain, mapping)
+    }
+
+    /// Strip all format placeholders such as `{field}` or `{0:?}`, as well as
+    /// the escaped braces `{{` and `}}`, ranges are mapped `plain -> raw`.
+    fn extract_plain_without_placeholders(text: &str) -> (String, IndexMap<Range, Range>) {
+        let mut plain = String::with_capacity(text.len());
+        let mut mapping = IndexMap::with_capacity(8);
+        let mut start = 0usize;
+        let mut iter = text.char_indices().peekable();
+        while let Some((idx, c)) = iter.next() {
+            let end = match c {
+                '{' | '}' if iter.peek().map(|(_idx, next)| *next) == Some(c) => {
+                    let _ = iter.next();
+                    idx + 2
+                }
+                '{' => {
+                    // skip until the closing brace
+                    match iter.by_ref().find(|(_idx, c)| *c == '}') {
+                        Some((closing, _)) => closing + 1,
+                        None => text.len(),
+                    }
+                }
+                _ => continue,
+            };
+            if start < idx {
+                Self::track(&text[start..idx], start..idx, &mut plain, &mut mapping);
             }
-            assert!(plain_range.start <= plain_range.end);
-            mapping.insert(plain_range, raw_range);
+            start = end;
+        }
+        if start < text.len() {
+            Self::track(&text[start..], start..text.len(), &mut plain, &mut mapping);
         }
         (plain, mapping)
     }
 
-    // @todo consider returning a Vec<PlainOverlay<'a>> to account for list items
-    // or other chunked information which might not pass a grammar check as a whole
     pub fn erase_markdown(literal_set: &'a LiteralSet) -> Self {
-        let markdown = literal_set.to_string();
+        Self::erase_markdown_with(literal_set, &Rendering::default())
+    }
+
+    /// Same as `erase_markdown`, with the adjustments of `rendering` applied.
+    pub fn erase_markdown_with(literal_set: &'a LiteralSet, rendering: &Rendering) -> Self {
+        Self::erase(literal_set, rendering).0
+    }
 
-        let (plain, mapping) = Self::extract_plain_with_mapping(markdown.as_str());
+    /// One overlay per block, such as a paragraph, list item or heading,
+    /// so list items or other chunked information are not checked as a
+    /// single run-on sentence. Each overlay maps back into `literal_set`.
+    ///
+    /// Documents other than markdown are not chunked.
+    pub fn erase_markdown_chunks(literal_set: &'a LiteralSet, rendering: &Rendering) -> Vec<Self> {
+        let (whole, blocks) = Self::erase(literal_set, rendering);
+        let mut chunks = Vec::with_capacity(blocks.len() + 1);
+        let mut start = 0usize;
+        for end in blocks
+            .into_iter()
+            .chain(std::iter::once(whole.plain.len()))
+            .map(|end| end.min(whole.plain.len()))
+        {
+            if end <= start {
+                continue;
+            }
+            let mapping = whole
+                .mapping
+                .iter()
+                .filter(|(plain, _raw)| start <= plain.start && plain.start < end)
+                .map(|(plain, raw)| ((plain.start - start)..(plain.end - start), raw.clone()))
+                .collect::<IndexMap<_, _>>();
+            let placeholders = whole
+                .placeholders
+                .iter()
+                .filter(|placeholder| start <= placeholder.start && placeholder.start < end)
+                .map(|placeholder| (placeholder.start - start)..(placeholder.end - start))
+                .collect::<Vec<_>>();
+            // whitespace only, nothing to check
+            if !mapping.is_empty() {
+                chunks.push(Self {
+                    raw: literal_set,
+                    plain: whole.plain[start..end].to_owned(),
+                    mapping,
+                    placeholders,
+                });
+            }
+            start = end;
+        }
+        chunks
+    }
+
+    /// An overlay of the inline code of markdown, empty for other documents.
+    pub fn inline_code(literal_set: &'a LiteralSet) -> Self {
+        let literals = literal_set.literals();
+        let is_markdown = literals.iter().any(|literal| literal.variant.is_markdown());
+        let (plain, mapping) = if is_markdown {
+            Self::extract_inline_code(
+                literal_set.to_string().as_str(),
+                is_markdown_file(&literals),
+            )
+        } else {
+            (String::new(), IndexMap::new())
+        };
         Self {
             raw: literal_set,
             plain,
             mapping,
+            placeholders: Vec::new(),
         }
     }
 
+    /// The overlay and the ends of its blocks, which are only known for markdown.
+    fn erase(literal_set: &'a LiteralSet, rendering: &Rendering) -> (Self, Vec<usize>) {
+        let markdown = literal_set.to_string();
+
+        let literals = literal_set.literals();
+        let is_rst = literals
+            .iter()
+            .any(|literal| literal.variant == CommentVariant::ReStructuredText);
+        let is_markdown = literals.iter().any(|literal| literal.variant.is_markdown());
+        let is_attribute = literals
+            .iter()
+            .any(|literal| literal.variant == CommentVariant::Attribute);
+        let (plain, mapping, blocks, placeholders) = if is_rst {
+            let (plain, mapping) = rst::extract_plain_with_mapping(markdown.as_str());
+            (plain, mapping, Vec::new(), Vec::new())
+        } else if is_markdown {
+            Self::extract_plain_with_blocks(
+                markdown.as_str(),
+                rendering,
+                is_markdown_file(&literals),
+            )
+        } else if is_attribute {
+            let (plain, mapping) = Self::extract_plain_without_placeholders(markdown.as_str());
+            (plain, mapping, Vec::new(), Vec::new())
+        } else {
+            // plain text documents are checked as is
+            let mut mapping = IndexMap::with_capacity(1);
+            mapping.insert(0..markdown.len(), 0..markdown.len());
+            (markdown, mapping, Vec::new(), Vec::new())
+        };
+        let overlay = Self {
+            raw: literal_set,
+            plain,
+            mapping,
+            placeholders,
+        };
+        (overlay, blocks)
+    }
+
     /// Since most checkers will operate on the plain data, an indirection to map plain to markdown
     /// and back to literals and spans
     pub fn linear_range_to_spans(&self, plain_range: Range) -> Vec<(&'a TrimmedLiteral, Span)> {
-        use core::cmp::min;
+        use core::cmp::{max, min};
 
-        self.mapping
-            .iter()
+        // the plain ranges are sorted and do not overlap, so all ranges before
+        // the first one ending at or after the start can be skipped
+        let (mut lo, mut hi) = (0usize, self.mapping.len());
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            let (plain, _raw) = self
+                .mapping
+                .get_index(mid)
+                .expect("Index is in bounds. qed");
+            if plain.end < plain_range.start {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+
+        if plain_range.start >= plain_range.end {
+            trace!("linear range to spans: {:?} empty!", &plain_range);
+            return Vec::new();
+        }
+
+        // a range may cross several chunks, i.e. the lines of a doc comment,
+        // each chunk covered in parts is resolved on its own
+        (lo..self.mapping.len())
+            .filter_map(|idx| self.mapping.get_index(idx))
+            .take_while(|(plain, _raw)| plain.start < plain_range.end)
+            .filter(|(plain, _raw)| plain_range.start < plain.end)
             .filter(|(plain, _raw)| {
-                plain.start <= plain_range.start && plain_range.end <= plain.end
+                // there is nothing sensible to point at within the raw range
+                // a placeholder stands in for
+                let placeholder = self.placeholders.contains(plain);
+                if placeholder {
+                    trace!(
+                        "Ignoring range {:?} within placeholder {:?}",
+                        &plain_range,
+                        plain
+                    );
+                }
+                !placeholder
             })
             .fold(Vec::with_capacity(64), |mut acc, (plain, raw)| {
-                let offset = raw.start - plain.start;
-                assert_eq!(raw.end - plain.end, offset);
+                // the part of the chunk which is covered
+                let start = max(plain.start, plain_range.start);
+                let end = min(plain.end, plain_range.end);
+                // a soft break rendered as space maps to the newline between two
+                // lines, which is only ever part of a longer range
+                if (start, end) != (plain_range.start, plain_range.end)
+                    && self.plain[start..end].trim().is_empty()
+                {
+                    return acc;
+                }
+                // escapes and entities are shorter than their raw text
                 let extracted = Range {
-                    start: plain_range.start + offset,
-                    end: min(raw.end, plain_range.end + offset),
+                    start: min(raw.start + (start - plain.start), raw.end),
+                    end: min(raw.start + (end - plain.start), raw.end),
                 };
-                trace!(
-                    "convert (offset = {}):  convert reduced={:?} -> raw={:?}",
-                    offset,
-                    plain,
-                    raw
-                );
+                if extracted.start >= extracted.end {
+                    return acc;
+                }
+                trace!("convert reduced={:?} -> raw={:?}", plain, raw);
                 trace!("highlight:  {:?} -> {:?}", &plain_range, &extracted);
 
-                if extracted.start < extracted.end {
-                    let resolved = self.raw.linear_range_to_spans(extracted.clone());
-                    trace!("linear range to spans: {:?} -> {:?}", extracted, resolved);
-                    acc.extend(resolved.into_iter());
-                } else {
-                    warn!("linear range to spans: {:?} empty!", extracted);
-                }
+                let resolved = self.raw.linear_range_to_spans(extracted.clone());
+                trace!("linear range to spans: {:?} -> {:?}", extracted, resolved);
+                acc.extend(resolved.into_iter());
                 acc
             })
     }
@@ -306,7 +1005,8 @@ Extra ~pagaph~ paragraph.
 
 
 And a line, or a rule."##;
-        let (reduced, mapping) = PlainOverlay::extract_plain_with_mapping(MARKDOWN);
+        let (reduced, mapping) =
+            PlainOverlay::extract_plain_with_mapping(MARKDOWN, &Rendering::default());
 
         assert_eq!(dbg!(&reduced).as_str(), PLAIN);
         assert_eq!(dbg!(&mapping).len(), 19);
@@ -318,12 +1018,32 @@ And a line, or a rule."##;
         }
     }
 
+    #[test]
+    fn markdown_reduction_strikethrough() {
+        const MARKDOWN: &str = r#"Some ~~wrods~~ are **~~struck~~** through, ~single~ ones not."#;
+        const PLAIN: &str = r#"Some wrods are struck through, ~single~ ones not."#;
+
+        let (reduced, mapping) =
+            PlainOverlay::extract_plain_with_mapping(MARKDOWN, &Rendering::default());
+
+        assert_eq!(dbg!(&reduced).as_str(), PLAIN);
+        for (reduced_range, markdown_range) in mapping.iter() {
+            assert_eq!(
+                reduced[reduced_range.clone()],
+                MARKDOWN[markdown_range.clone()]
+            );
+        }
+        let wrods = reduced.find("wrods").unwrap();
+        assert_eq!(mapping.get(&(wrods..wrods + 5)), Some(&(7..12)));
+    }
+
     #[test]
     fn markdown_reduction_mapping_leading_space() {
         const MARKDOWN: &str = r#"  Some __underlined__ **bold** text."#;
         const PLAIN: &str = r#"Some underlined bold text."#;
 
-        let (reduced, mapping) = PlainOverlay::extract_plain_with_mapping(MARKDOWN);
+        let (reduced, mapping) =
+            PlainOverlay::extract_plain_with_mapping(MARKDOWN, &Rendering::default());
 
         assert_eq!(dbg!(&reduced).as_str(), PLAIN);
         assert_eq!(dbg!(&mapping).len(), 5);
@@ -335,6 +1055,644 @@ And a line, or a rule."##;
         }
     }
 
+    #[test]
+    fn markdown_reduction_autolink() {
+        const MARKDOWN: &str =
+            r#"Visit <https://exmaple.com/pathh> for detials, or ask <mail@ahoi.io>."#;
+        const PLAIN: &str = r#"Visit  for detials, or ask ."#;
+
+        let (reduced, mapping) =
+            PlainOverlay::extract_plain_with_mapping(MARKDOWN, &Rendering::default());
+
+        assert_eq!(dbg!(&reduced).as_str(), PLAIN);
+        assert_eq!(dbg!(&mapping).len(), 3);
+        for (reduced_range, markdown_range) in mapping.iter() {
+            assert_eq!(
+                reduced[reduced_range.clone()],
+                MARKDOWN[markdown_range.clone()]
+            );
+        }
+    }
+
+    #[test]
+    fn markdown_reduction_links() {
+        const MARKDOWN: &str =
+            r#"Please [clikc here](https://x "Teh title") or see ![a dgo](dog.png "Doggo")."#;
+
+        let (reduced, mapping) =
+            PlainOverlay::extract_plain_with_mapping(MARKDOWN, &Rendering::default());
+        assert_eq!(dbg!(&reduced).as_str(), "Please clikc here or see a dgo.");
+        let clikc = reduced.find("clikc").unwrap();
+        let (plain_range, markdown_range) = mapping
+            .iter()
+            .find(|(plain_range, _)| plain_range.contains(&clikc))
+            .expect("Anchor text must be mapped");
+        assert_eq!(&MARKDOWN[markdown_range.clone()], "clikc here");
+        assert_eq!(&reduced[plain_range.clone()], "clikc here");
+
+        let rendering = Rendering {
+            link_titles: true,
+            ..Rendering::default()
+        };
+        let (reduced, mapping) = PlainOverlay::extract_plain_with_mapping(MARKDOWN, &rendering);
+        assert_eq!(
+            dbg!(&reduced).as_str(),
+            "Please clikc here Teh title or see a dgo Doggo."
+        );
+        assert!(!reduced.contains("https"));
+        assert!(!reduced.contains("png"));
+        for (reduced_range, markdown_range) in mapping.iter() {
+            assert_eq!(
+                reduced[reduced_range.clone()],
+                MARKDOWN[markdown_range.clone()]
+            );
+        }
+    }
+
+    #[test]
+    fn markdown_reduction_table() {
+        const MARKDOWN: &str = r#"Options:
+
+| Name | Description |
+|------|-------------|
+| `a`  | Enables teh cache. |
+| b    | Done. |
+
+After."#;
+        const PLAIN: &str =
+            "Options:\n\nName\nDescription\n\n\nEnables teh cache.\n\nb\nDone.\n\n\nAfter.";
+
+        let (reduced, mapping) =
+            PlainOverlay::extract_plain_with_mapping(MARKDOWN, &Rendering::default());
+        assert_eq!(dbg!(&reduced).as_str(), PLAIN);
+        assert!(!reduced.contains('|'));
+        for (reduced_range, markdown_range) in mapping.iter() {
+            assert_eq!(
+                reduced[reduced_range.clone()],
+                MARKDOWN[markdown_range.clone()]
+            );
+        }
+
+        let teh = reduced.find("teh").unwrap();
+        let (plain_range, markdown_range) = mapping
+            .iter()
+            .find(|(plain_range, _)| plain_range.contains(&teh))
+            .expect("Cell text must be mapped");
+        let raw = markdown_range.start + (teh - plain_range.start);
+        assert_eq!(&MARKDOWN[raw..raw + 3], "teh");
+        assert_eq!(MARKDOWN[..raw].lines().count(), 5);
+    }
+
+    #[test]
+    fn markdown_reduction_front_matter() {
+        const MARKDOWN: &str =
+            "---\nlayout: post\npermalink: /blgo/\n---\n\nSome prose with a tpyo.\n";
+        let (reduced, mapping) =
+            PlainOverlay::extract_plain_with_mapping(MARKDOWN, &Rendering::default());
+        assert_eq!(dbg!(&reduced).as_str(), "Some prose with a tpyo.");
+        assert_eq!(mapping.len(), 1);
+        let (_plain, raw) = mapping.get_index(0).unwrap();
+        assert_eq!(&MARKDOWN[raw.clone()], "Some prose with a tpyo.");
+
+        assert_eq!(front_matter("---\ntitle: x\n...\nText"), 17);
+        // a leading horizontal rule
+        assert_eq!(front_matter("---\n\nText\n\n---\n"), 0);
+        assert_eq!(front_matter("---\nText\n---\n"), 0);
+        // never closed
+        assert_eq!(front_matter("---\ntitle: x\n\nText"), 0);
+        assert_eq!(front_matter("---"), 0);
+        // doc comments never have a front matter
+        let docs = Documentation::load_from_str(
+            "lib.rs",
+            "/// ---\n/// title: x\n/// ---\n/// Text\nstruct X;",
+            &Config::default(),
+        )
+        .expect("Must parse");
+        let (_path, literal_sets) = crate::testcase::first(docs.iter());
+        let plain = literal_sets[0].erase_markdown();
+        assert!(plain.as_str().contains("title: x"));
+        assert!(plain.as_str().contains("Text"));
+
+        let (reduced, _mapping) = PlainOverlay::extract_plain_with_mapping(
+            "---\n\nAfter the rule.",
+            &Rendering::default(),
+        );
+        assert_eq!(reduced.as_str(), "\nAfter the rule.");
+    }
+
+    #[test]
+    fn markdown_reduction_block_quote() {
+        const MARKDOWN: &str =
+            "Before.\n> A quoted\n> lien here.\n>\n> > Nested\n> > quote.\nAfter.";
+
+        let (reduced, mapping) =
+            PlainOverlay::extract_plain_with_mapping(MARKDOWN, &Rendering::default());
+        assert_eq!(
+            dbg!(&reduced).as_str(),
+            "Before.\n\nA quoted\nlien here.\n\nNested\nquote.\nAfter."
+        );
+        for (reduced_range, markdown_range) in mapping.iter() {
+            assert_eq!(
+                reduced[reduced_range.clone()],
+                MARKDOWN[markdown_range.clone()]
+            );
+        }
+        let lien = reduced.find("lien").unwrap();
+        let (plain_range, markdown_range) = mapping
+            .iter()
+            .find(|(plain_range, _)| plain_range.contains(&lien))
+            .expect("Quoted text must be mapped");
+        let raw = markdown_range.start + (lien - plain_range.start);
+        assert_eq!(raw, MARKDOWN.find("lien").unwrap());
+
+        // a quote ending in a list is still separated from what follows
+        let (reduced, _mapping) =
+            PlainOverlay::extract_plain_with_mapping("> - item\n\nAfter.", &Rendering::default());
+        assert_eq!(reduced.as_str(), "item\n\nAfter.");
+
+        let (reduced, _mapping) =
+            PlainOverlay::extract_plain_with_mapping("> > >\n> >", &Rendering::default());
+        assert_eq!(reduced.as_str(), "");
+    }
+
+    #[test]
+    fn markdown_reduction_html() {
+        const MARKDOWN: &str = "Some <span>inlined</span> text, <b>bold<i>er</i></b>.<br>Run <code>cargo tset</code> <kbd>Ctrl</kbd>+C";
+        const PLAIN: &str = "Some inlined text, bolder.\nRun  +C";
+
+        let (reduced, mapping) =
+            PlainOverlay::extract_plain_with_mapping(MARKDOWN, &Rendering::default());
+        assert_eq!(dbg!(&reduced).as_str(), PLAIN);
+        assert!(!reduced.contains('<'));
+        for (reduced_range, markdown_range) in mapping.iter() {
+            assert_eq!(
+                reduced[reduced_range.clone()],
+                MARKDOWN[markdown_range.clone()]
+            );
+        }
+        let inlined = reduced.find("inlined").unwrap();
+        assert_eq!(mapping.get(&(inlined..inlined + 7)), Some(&(11..18)));
+
+        // block level html is skipped entirely
+        let (reduced, _mapping) = PlainOverlay::extract_plain_with_mapping(
+            "<div>\nblock tpyo\n</div>\n\nAfter.",
+            &Rendering::default(),
+        );
+        assert_eq!(reduced.as_str(), "After.");
+
+        // an unclosed code tag ends with its paragraph
+        let (reduced, _mapping) = PlainOverlay::extract_plain_with_mapping(
+            "Run <code>cargo tset\n\nAfter the tag.",
+            &Rendering::default(),
+        );
+        assert_eq!(reduced.as_str(), "Run \n\nAfter the tag.");
+
+        assert_eq!(html_tag("<br/>"), Some(("br".to_owned(), false)));
+        assert_eq!(html_tag("</CODE>"), Some(("code".to_owned(), true)));
+        assert_eq!(html_tag("<!-- comment -->"), None);
+        assert_eq!(html_tag("<pre><code>x</code></pre>\n"), None);
+    }
+
+    #[test]
+    fn markdown_reduction_math() {
+        const MARKDOWN: &str = r#"Let $\alpha\beta$ be a tpyo, costs $5 or $10.
+
+$$
+\sum_{i} x_i
+$$
+
+With `$HOME` and $a_b$ as well as \$alpha$."#;
+
+        // not skipped by default
+        let (reduced, _mapping) =
+            PlainOverlay::extract_plain_with_mapping(MARKDOWN, &Rendering::default());
+        assert!(reduced.contains("alpha"));
+
+        let rendering = Rendering {
+            skip_math: true,
+            ..Rendering::default()
+        };
+        let (reduced, mapping) = PlainOverlay::extract_plain_with_mapping(MARKDOWN, &rendering);
+        assert_eq!(
+            dbg!(&reduced).as_str(),
+            "Let  be a tpyo, costs $5 or $10.\n\n\n\n\n\nWith  and  as well as $alpha$."
+        );
+        for (reduced_range, markdown_range) in mapping.iter() {
+            assert_eq!(
+                reduced[reduced_range.clone()],
+                MARKDOWN[markdown_range.clone()]
+            );
+        }
+        assert_eq!(math_ranges("$$x$$ and $y$"), vec![0..5, 10..13]);
+        assert!(math_ranges("$ 5 and $").is_empty());
+    }
+
+    #[test]
+    fn inline_code() {
+        const MARKDOWN: &str = "Call `calc_lenght` or `` a`b `` and\n\n```\nblock\n```";
+        let (code, mapping) = PlainOverlay::extract_inline_code(MARKDOWN, true);
+        assert_eq!(code.as_str(), "calc_lenght\na`b\n");
+        for (code_range, markdown_range) in mapping.iter() {
+            assert_eq!(code[code_range.clone()], MARKDOWN[markdown_range.clone()]);
+        }
+    }
+
+    #[test]
+    fn markdown_reduction_reference_definitions() {
+        const MARKDOWN: &str = r#"See [the docs][id] or [id].
+
+```md
+[code]: https://example.com "Not a tilte"
+```
+
+[id]: https://exmaple.com "Teh title"
+[unused]: <https://x.y> 'Other'
+"#;
+
+        let (reduced, mapping) =
+            PlainOverlay::extract_plain_with_mapping(MARKDOWN, &Rendering::default());
+        assert_eq!(dbg!(&reduced).as_str(), "See the docs or id.");
+        assert!(!reduced.contains("exmaple"));
+        assert!(!reduced.contains("unused"));
+        assert_eq!(mapping.len(), 5);
+
+        let rendering = Rendering {
+            link_titles: true,
+            ..Rendering::default()
+        };
+        let (reduced, mapping) = PlainOverlay::extract_plain_with_mapping(MARKDOWN, &rendering);
+        assert_eq!(
+            dbg!(&reduced).as_str(),
+            "See the docs or id.\n\nTeh title\n\nOther"
+        );
+        for (reduced_range, markdown_range) in mapping.iter() {
+            assert_eq!(
+                reduced[reduced_range.clone()],
+                MARKDOWN[markdown_range.clone()]
+            );
+        }
+    }
+
+    #[test]
+    fn markdown_reduction_empty() {
+        for markdown in &["", "   ", "\n", " \n\t\n  "] {
+            let (reduced, mapping) =
+                PlainOverlay::extract_plain_with_mapping(markdown, &Rendering::default());
+            assert_eq!(reduced.as_str(), "");
+            assert!(mapping.is_empty());
+        }
+    }
+
+    #[test]
+    fn callout() {
+        const MARKDOWN: &str = "Intro\n\n> [!WARNING]\n> Be carefull here.\n\n> [!Note] Inline tpyo\n\n> [!FOO] Unknown\n";
+        const PLAIN: &str = "Intro\n\nBe carefull here.\n\n Inline tpyo\n\n[!FOO] Unknown";
+
+        let (reduced, mapping) =
+            PlainOverlay::extract_plain_with_mapping(MARKDOWN, &Rendering::default());
+        assert_eq!(reduced.as_str(), PLAIN);
+
+        for word in &["carefull", "tpyo"] {
+            let start = reduced.find(word).unwrap();
+            let (plain_range, raw_range) = mapping
+                .iter()
+                .find(|(plain_range, _raw_range)| plain_range.contains(&start))
+                .expect("Body is mapped");
+            let raw_start = raw_range.start + start - plain_range.start;
+            assert_eq!(&MARKDOWN[raw_start..raw_start + word.len()], *word);
+        }
+        // the marker does not end up in the plain text, an unknown one does
+        assert!(!reduced.contains("WARNING"));
+        assert!(!reduced.contains("Note"));
+    }
+
+    #[test]
+    fn chunks() {
+        const MARKDOWN: &str = "# Heading\n\nA paragraph\nacross lines.\n\n- first item\n- second tpyo\n\n```\ncode\n```";
+        let docs =
+            Documentation::load_lines_from_str("chunks.md", MARKDOWN, CommentVariant::CommonMark)
+                .expect("Must load");
+        let (_path, literal_sets) = crate::testcase::first(docs.iter());
+        let chunks = literal_sets[0].erase_markdown_chunks(&Rendering::default());
+        let plains = chunks
+            .iter()
+            .map(|chunk| chunk.as_str().trim_end())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            plains,
+            vec![
+                "Heading",
+                "A paragraph\nacross lines.",
+                "first item",
+                "second tpyo"
+            ]
+        );
+
+        // each chunk maps back into the same literal set
+        let chunk = &chunks[3];
+        let start = chunk.as_str().find("tpyo").unwrap();
+        let spans = chunk.linear_range_to_spans(start..start + 4);
+        assert_eq!(spans.len(), 1);
+        let (_literal, span) = spans[0];
+        assert_eq!(span.start.line, 7);
+        assert_eq!(span.end.column - span.start.column, 3);
+
+        // the single overlay remains for pure spellchecking
+        let whole = literal_sets[0].erase_markdown();
+        assert!(whole.as_str().contains("first item\nsecond tpyo"));
+    }
+
+    #[test]
+    fn across_lines() {
+        let docs = Documentation::load_from_str(
+            "lib.rs",
+            "/// A sentence which is wrapped\n/// across *two* lines.\nstruct X;",
+            &Config::default(),
+        )
+        .expect("Must parse");
+        let (_path, literal_sets) = crate::testcase::first(docs.iter());
+        for rendering in &[
+            Rendering::default(),
+            Rendering {
+                soft_break_as_space: true,
+                ..Rendering::default()
+            },
+        ] {
+            let plain = literal_sets[0].erase_markdown_with(rendering);
+            let start = plain.as_str().find("wrapped").unwrap();
+            let end = plain.as_str().find(" lines").unwrap();
+            let spans = plain.linear_range_to_spans(start..end);
+            let covered = spans
+                .iter()
+                .map(|(_literal, span)| (span.start.line, span.start.column, span.end.column))
+                .collect::<Vec<_>>();
+            // `wrapped`, `across` and `two`, without the emphasis
+            assert_eq!(covered, vec![(1, 22, 28), (2, 2, 8), (2, 10, 12)]);
+        }
+    }
+
+    #[test]
+    fn plain_text() {
+        const TEXT: &str = "# Not a *title*\n\n  Keeps `all` of the tpyo [markup](x).";
+        let docs =
+            Documentation::load_lines_from_str("CHANGELOG.txt", TEXT, CommentVariant::PlainText)
+                .expect("Must load");
+        let (_path, literal_sets) = crate::testcase::first(docs.iter());
+        let plain = literal_sets[0].erase_markdown();
+        assert_eq!(plain.as_str(), TEXT);
+
+        let start = TEXT.find("tpyo").unwrap();
+        let spans = plain.linear_range_to_spans(start..start + 4);
+        assert_eq!(spans.len(), 1);
+        let (_literal, span) = spans[0];
+        assert_eq!(span.start.line, 3);
+        assert_eq!(span.end.line, 3);
+        assert_eq!(span.end.column - span.start.column, 3);
+    }
+
+    #[test]
+    fn lookup() {
+        // many mapping entries, one per emphasized word and the text in between
+        let markdown = (0..100)
+            .map(|n| format!("Word *w{}* and **b{}**.", n, n))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let docs = Documentation::load_lines_from_str(
+            "lookup.md",
+            markdown.as_str(),
+            CommentVariant::CommonMark,
+        )
+        .expect("Must load");
+        let (_path, literal_sets) = crate::testcase::first(docs.iter());
+        let plain = literal_sets[0].erase_markdown();
+        for n in 0..100 {
+            // followed by a unique suffix, so `w1` does not match `w10`
+            for (word, suffix) in &[(format!("w{}", n), " and"), (format!("b{}", n), ".")] {
+                let start = plain
+                    .as_str()
+                    .find(format!("{}{}", word, suffix).as_str())
+                    .unwrap();
+                let spans = plain.linear_range_to_spans(start..start + word.len());
+                assert_eq!(spans.len(), 1);
+                let (_literal, span) = spans[0];
+                assert_eq!(span.start.line, n + 1);
+                assert_eq!(span.end.column - span.start.column + 1, word.len());
+            }
+        }
+        // a range spanning two mapping entries is covered by both
+        let start = plain.as_str().find("Word w0").unwrap();
+        let spans = plain.linear_range_to_spans(start..start + 7);
+        assert_eq!(spans.len(), 2);
+        let width = |span: &Span| span.end.column - span.start.column + 1;
+        assert_eq!(width(&spans[0].1), "Word ".len());
+        assert_eq!(width(&spans[1].1), "w0".len());
+    }
+
+    #[test]
+    fn soft_break_as_space() {
+        const MARKDOWN: &str = "A sentence wrapped\nacross two lines.";
+
+        let (reduced, _mapping) =
+            PlainOverlay::extract_plain_with_mapping(MARKDOWN, &Rendering::default());
+        assert_eq!(reduced.as_str(), MARKDOWN);
+
+        let rendering = Rendering {
+            soft_break_as_space: true,
+            ..Default::default()
+        };
+        let (reduced, mapping) = PlainOverlay::extract_plain_with_mapping(MARKDOWN, &rendering);
+        assert_eq!(reduced.as_str(), "A sentence wrapped across two lines.");
+        assert_eq!(reduced.len(), MARKDOWN.len());
+        // the space is mapped to the newline
+        assert_eq!(mapping.get(&(18..19)), Some(&(18..19)));
+        for (reduced_range, markdown_range) in mapping.iter() {
+            assert_eq!(
+                reduced[reduced_range.clone()].trim(),
+                MARKDOWN[markdown_range.clone()].trim()
+            );
+        }
+    }
+
+    #[test]
+    fn inline_code_nested() {
+        const MARKDOWN: &str = r#"# The `fooo_bar` heading
+
+- An item with `baz_qux`
+- **Strong `teh`** and [`tpyo`](https://ahoi.io)
+  1. Nested `wiht` item
+
+> A quote with `xyzzy`.
+
+| Cell `plugh` |
+|---|
+"#;
+        let (reduced, mapping) =
+            PlainOverlay::extract_plain_with_mapping(MARKDOWN, &Rendering::default());
+        for code in &[
+            "fooo_bar", "baz_qux", "teh", "tpyo", "wiht", "xyzzy", "plugh",
+        ] {
+            assert!(
+                !reduced.contains(code),
+                "{} leaked into {:?}",
+                code,
+                reduced
+            );
+            let start = MARKDOWN.find(code).unwrap();
+            assert!(mapping
+                .values()
+                .all(|raw| raw.end <= start || start + code.len() <= raw.start));
+        }
+        assert!(reduced.contains("heading"));
+        assert!(reduced.contains("An item with"));
+        assert!(reduced.contains("Nested"));
+        assert!(reduced.contains("Cell"));
+    }
+
+    #[test]
+    fn code_placeholder() {
+        const MARKDOWN: &str = "Use the `foo_bar` function `x` here.";
+
+        let (reduced, _mapping) =
+            PlainOverlay::extract_plain_with_mapping(MARKDOWN, &Rendering::default());
+        assert_eq!(reduced.as_str(), "Use the  function  here.");
+
+        let rendering = Rendering {
+            code_placeholder: Some("code".to_owned()),
+            ..Default::default()
+        };
+        let (reduced, mapping) = PlainOverlay::extract_plain_with_mapping(MARKDOWN, &rendering);
+        assert_eq!(reduced.as_str(), "Use the code function code here.");
+        // the placeholder points at the code including the backticks
+        assert_eq!(mapping.get(&(8..12)), Some(&(8..17)));
+        assert_eq!(mapping.get(&(22..26)), Some(&(27..30)));
+
+        let docs = Documentation::load_lines_from_str(
+            "placeholder.md",
+            MARKDOWN,
+            CommentVariant::CommonMark,
+        )
+        .expect("Must load");
+        let (_path, literal_sets) = crate::testcase::first(docs.iter());
+        let plain = literal_sets[0].erase_markdown_with(&rendering);
+        assert_eq!(plain.as_str(), reduced.as_str());
+        // nothing to point at within the placeholder
+        assert!(plain.linear_range_to_spans(8..12).is_empty());
+        assert!(plain.linear_range_to_spans(22..26).is_empty());
+        // the text following a longer placeholder is still found
+        let start = reduced.find("here").unwrap();
+        let spans = plain.linear_range_to_spans(start..start + 4);
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].1.end.column - spans[0].1.start.column, 3);
+
+        // a placeholder as long as the code it replaces is still a placeholder
+        const SAME_LENGTH: &str = "Call `ab` now.";
+        let docs = Documentation::load_lines_from_str(
+            "placeholder.md",
+            SAME_LENGTH,
+            CommentVariant::CommonMark,
+        )
+        .expect("Must load");
+        let (_path, literal_sets) = crate::testcase::first(docs.iter());
+        let plain = literal_sets[0].erase_markdown_with(&rendering);
+        assert_eq!(plain.as_str(), "Call code now.");
+        assert!(plain.linear_range_to_spans(5..9).is_empty());
+        assert_eq!(plain.linear_range_to_spans(10..13).len(), 1);
+    }
+
+    #[test]
+    fn rust_fence_comments() {
+        const MARKDOWN: &str = r#"Example:
+
+```rust,no_run
+/// Documented with a tpyo.
+let url = "https://ahoi.io"; // a trailing commment
+let x = 1;
+```
+
+```sh
+# not rust // at all
+```
+
+Done."#;
+        const PLAIN: &str = "Example:\n\nDocumented with a tpyo.\na trailing commment\n\nDone.";
+
+        let (reduced, mapping) =
+            PlainOverlay::extract_plain_with_mapping(MARKDOWN, &Rendering::default());
+        assert_eq!(dbg!(&reduced).as_str(), PLAIN);
+        for (reduced_range, markdown_range) in mapping.iter() {
+            assert_eq!(
+                reduced[reduced_range.clone()],
+                MARKDOWN[markdown_range.clone()]
+            );
+        }
+        for word in &["tpyo", "commment"] {
+            let start = reduced.find(word).unwrap();
+            let (plain_range, raw_range) = mapping
+                .iter()
+                .find(|(plain_range, _raw_range)| plain_range.contains(&start))
+                .expect("Comment is mapped");
+            let raw_start = raw_range.start + start - plain_range.start;
+            assert_eq!(&MARKDOWN[raw_start..raw_start + word.len()], *word);
+        }
+    }
+
+    #[test]
+    fn comments_in_code() {
+        let code = "let a = \"//\"; // one\n//! two\n    //three \n/// \nlet b = '\\\\'; // four";
+        let comments = line_comments(code)
+            .into_iter()
+            .map(|range| &code[range])
+            .collect::<Vec<_>>();
+        assert_eq!(comments, vec!["one", "two", "three", "four"]);
+    }
+
+    #[test]
+    fn comments_after_char_literals() {
+        let code = "let q = '\"'; // one\nlet e = '\\''; // two\nlet u = '\\u{22}'; // three\nfn f<'a>(x: &'a str) {} // four";
+        let comments = line_comments(code)
+            .into_iter()
+            .map(|range| &code[range])
+            .collect::<Vec<_>>();
+        assert_eq!(comments, vec!["one", "two", "three", "four"]);
+    }
+
+    #[test]
+    fn track_empty() {
+        let mut plain = String::new();
+        let mut mapping = IndexMap::new();
+
+        PlainOverlay::track("", 0..4, &mut plain, &mut mapping);
+        PlainOverlay::track("abc", 4..7, &mut plain, &mut mapping);
+        PlainOverlay::track("", 7..9, &mut plain, &mut mapping);
+        PlainOverlay::track("de", 9..11, &mut plain, &mut mapping);
+
+        assert_eq!(plain.as_str(), "abcde");
+        assert_eq!(mapping.len(), 2);
+        assert_eq!(mapping.get(&(0..3)), Some(&(4..7)));
+        assert_eq!(mapping.get(&(3..5)), Some(&(9..11)));
+
+        // an empty link title must not swallow the mapping of the following text
+        let (reduced, mapping) =
+            PlainOverlay::extract_plain_with_mapping("[](x) text", &Rendering::default());
+        assert_eq!(reduced.as_str(), " text");
+        assert_eq!(mapping.len(), 1);
+        assert_eq!(mapping.get(&(0..5)), Some(&(5..10)));
+    }
+
+    #[test]
+    fn placeholders() {
+        const TEXT: &str = r#"Failed {field} with {0:?}, {{literal}} {"#;
+        const PLAIN: &str = r#"Failed  with , literal "#;
+
+        let (reduced, mapping) = PlainOverlay::extract_plain_without_placeholders(TEXT);
+
+        assert_eq!(dbg!(&reduced).as_str(), PLAIN);
+        assert_eq!(dbg!(&mapping).len(), 5);
+        for (reduced_range, text_range) in mapping.iter() {
+            assert_eq!(reduced[reduced_range.clone()], TEXT[text_range.clone()]);
+        }
+    }
+
     #[test]
     fn range_test() {
         let mut x = IndexMap::<Range, Range>::new();
@@ -0,0 +1,410 @@
+//! Erase reStructuredText syntax
+//!
+//! Directives, comments, literal blocks and section adornments are dropped
+//! entirely, as are roles, inline literals and substitutions. Of emphasis and
+//! hyperlink references only the text remains, analogous to markdown.
+
+use super::PlainOverlay;
+use crate::literalset::Range;
+
+use indexmap::IndexMap;
+use log::trace;
+
+/// Directives with prose content, which is checked.
+const ADMONITIONS: &[&str] = &[
+    "admonition",
+    "attention",
+    "caution",
+    "danger",
+    "error",
+    "hint",
+    "important",
+    "note",
+    "seealso",
+    "tip",
+    "warning",
+];
+
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+/// If `line` only consists of a repeated punctuation character, such as the
+/// `=====` underlining a section title or a `----` transition.
+fn is_adornment(line: &str) -> bool {
+    let mut chars = line.chars();
+    match chars.next() {
+        Some(first) if first.is_ascii_punctuation() && line.len() >= 3 => chars.all(|c| c == first),
+        _ => false,
+    }
+}
+
+/// Length of the bullet, enumeration or field list marker including the
+/// following whitespace, if `line` starts with one.
+fn marker(line: &str) -> Option<usize> {
+    let whitespace = |rest: &str| rest.len() - rest.trim_start().len();
+    if let Some(rest) = line
+        .strip_prefix("- ")
+        .or_else(|| line.strip_prefix("* "))
+        .or_else(|| line.strip_prefix("+ "))
+    {
+        return Some(line.len() - rest.len() + whitespace(rest));
+    }
+    // field list, i.e. `:param x: The value.`
+    if let Some(field) = line.strip_prefix(':') {
+        let end = field.find(": ").or_else(|| {
+            if field.ends_with(':') {
+                Some(field.len() - 1)
+            } else {
+                None
+            }
+        })?;
+        if end > 0 && !field[..end].contains('`') {
+            let rest = &field[end + 1..];
+            return Some(line.len() - rest.len() + whitespace(rest));
+        }
+        return None;
+    }
+    // enumeration, i.e. `1.`, `#.` or `a)`
+    let digits = line.chars().take_while(|c| c.is_ascii_digit()).count();
+    let len = match line.chars().next() {
+        Some(c) if digits == 0 && (c.is_ascii_alphabetic() || c == '#') => 1,
+        _ => digits,
+    };
+    let rest = &line[len..];
+    if len > 0 && len <= 3 && (rest.starts_with(". ") || rest.starts_with(") ")) {
+        let rest = &rest[1..];
+        return Some(line.len() - rest.len() + whitespace(rest));
+    }
+    None
+}
+
+/// If inline markup may start after `previous`.
+fn is_start_boundary(previous: Option<char>) -> bool {
+    match previous {
+        None => true,
+        Some(c) => c.is_whitespace() || "'\"([{<-/:".contains(c),
+    }
+}
+
+/// Index of `close` within `rest`, if `rest` starts with `open` and the
+/// enclosed text neither starts nor ends with whitespace.
+fn enclosed(rest: &str, open: &str, close: &str) -> Option<usize> {
+    let inner = rest.strip_prefix(open)?;
+    if inner.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let end = inner.find(close)?;
+    if end == 0 || inner[..end].ends_with(char::is_whitespace) {
+        return None;
+    }
+    Some(open.len() + end)
+}
+
+/// Length of a role such as `:func:` or `:py:func:`, if `rest` starts with one.
+fn role(rest: &str) -> Option<usize> {
+    let name = rest.strip_prefix(':')?;
+    let len = name
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || "-_.+:".contains(*c))
+        .count();
+    if len > 1 && name[..len].ends_with(':') {
+        Some(len + 1)
+    } else {
+        None
+    }
+}
+
+/// Markup starting at the beginning of `rest`, returns the length of the
+/// markup and the range of the text within, which remains.
+fn markup(rest: &str) -> Option<(usize, Option<Range>)> {
+    if rest.starts_with("``") {
+        // inline literal
+        let end = enclosed(rest, "``", "``")?;
+        return Some((end + 2, None));
+    }
+    if let Some(len) = role(rest) {
+        // role with prefix, i.e. :func:`foo`
+        let end = enclosed(&rest[len..], "`", "`")?;
+        return Some((len + end + 1, None));
+    }
+    if rest.starts_with("**") {
+        let end = enclosed(rest, "**", "**")?;
+        return Some((end + 2, Some(2..end)));
+    }
+    if rest.starts_with('*') {
+        let end = enclosed(rest, "*", "*")?;
+        return Some((end + 1, Some(1..end)));
+    }
+    if rest.starts_with('`') {
+        let end = enclosed(rest, "`", "`")?;
+        let after = &rest[end + 1..];
+        if let Some(len) = role(after) {
+            // role with suffix, i.e. `foo`:func:
+            return Some((end + 1 + len, None));
+        }
+        let underscores = after.chars().take_while(|c| *c == '_').count();
+        if underscores == 0 || underscores > 2 {
+            // interpreted text without a role, usually code
+            return Some((end + 1, None));
+        }
+        // hyperlink reference, i.e. `Rust <https://rust-lang.org>`_
+        let inner = &rest[1..end];
+        let text_end = match inner.rfind('<') {
+            Some(target) if inner.ends_with('>') => inner[..target].trim_end().len(),
+            _ => inner.len(),
+        };
+        return Some((end + 1 + underscores, Some(1..1 + text_end)));
+    }
+    if rest.starts_with('|') {
+        // substitution reference
+        let end = enclosed(rest, "|", "|")?;
+        let underscores = rest[end + 1..].chars().take_while(|c| *c == '_').count();
+        return Some((end + 1 + underscores.min(2), None));
+    }
+    if rest.starts_with('[') {
+        // footnote or citation reference
+        let end = enclosed(rest, "[", "]_")?;
+        return Some((end + 2, None));
+    }
+    None
+}
+
+/// Track the text of a single line starting at `offset` within the raw
+/// document, without any inline markup.
+fn inline(text: &str, offset: usize, plain: &mut String, mapping: &mut IndexMap<Range, Range>) {
+    let mut chunk = 0usize;
+    let mut previous = None;
+    let mut idx = 0usize;
+    while let Some(c) = text[idx..].chars().next() {
+        let found = if is_start_boundary(previous) {
+            markup(&text[idx..])
+        } else if c == '_' && previous.map_or(false, char::is_alphanumeric) {
+            // the underscores of a reference, i.e. `Rust_` or `Rust__`
+            let underscores = text[idx..].chars().take_while(|c| *c == '_').count();
+            let next = text[idx + underscores..].chars().next();
+            if underscores <= 2 && next.map_or(true, |c| !c.is_alphanumeric()) {
+                Some((underscores, None))
+            } else {
+                None
+            }
+        } else {
+            None
+        };
+        let (len, keep) = if let Some(found) = found {
+            found
+        } else {
+            previous = Some(c);
+            idx += c.len_utf8();
+            continue;
+        };
+        trace!("Inline markup: >{}<", &text[idx..idx + len]);
+        PlainOverlay::track(
+            &text[chunk..idx],
+            (offset + chunk)..(offset + idx),
+            plain,
+            mapping,
+        );
+        if let Some(keep) = keep {
+            let keep = (idx + keep.start)..(idx + keep.end);
+            PlainOverlay::track(
+                &text[keep.clone()],
+                (offset + keep.start)..(offset + keep.end),
+                plain,
+                mapping,
+            );
+        }
+        chunk = idx + len;
+        idx = chunk;
+        previous = text[..chunk].chars().last();
+    }
+    PlainOverlay::track(
+        &text[chunk..],
+        (offset + chunk)..(offset + text.len()),
+        plain,
+        mapping,
+    );
+}
+
+/// ranges are mapped `plain -> raw`
+pub(super) fn extract_plain_with_mapping(rst: &str) -> (String, IndexMap<Range, Range>) {
+    let mut plain = String::with_capacity(rst.len());
+    let mut mapping = IndexMap::with_capacity(128);
+
+    // lines indented deeper belong to a directive, comment or literal block
+    let mut skip_deeper_than: Option<usize> = None;
+    // if the current paragraph has any lines yet
+    let mut paragraph = false;
+
+    let mut offset = 0usize;
+    for line in rst.split('\n') {
+        let start = offset;
+        offset += line.len() + 1;
+        let line = line.trim_end();
+        let indent = indentation(line);
+        if line.is_empty() {
+            if paragraph {
+                PlainOverlay::newlines(&mut plain, 2);
+                paragraph = false;
+            }
+            continue;
+        }
+        if let Some(block_indent) = skip_deeper_than {
+            if indent > block_indent {
+                trace!("Skipping block line: >{}<", line);
+                continue;
+            }
+            skip_deeper_than = None;
+        }
+        let content = &line[indent..];
+        if is_adornment(content) {
+            if paragraph {
+                PlainOverlay::newlines(&mut plain, 2);
+                paragraph = false;
+            }
+            continue;
+        }
+        if content == ".." || content.starts_with(".. ") {
+            // explicit markup, a directive, comment, target or footnote
+            if paragraph {
+                PlainOverlay::newlines(&mut plain, 2);
+                paragraph = false;
+            }
+            let explicit = &content[2..];
+            match explicit.find("::") {
+                Some(end) if ADMONITIONS.contains(&explicit[..end].trim()) => {
+                    // the remainder of the line is already part of the content
+                    let argument = &explicit[end + 2..];
+                    let argument_start = line.len() - argument.trim_start().len();
+                    if argument_start < line.len() {
+                        inline(
+                            &line[argument_start..],
+                            start + argument_start,
+                            &mut plain,
+                            &mut mapping,
+                        );
+                        paragraph = true;
+                    }
+                }
+                _ => skip_deeper_than = Some(indent),
+            }
+            continue;
+        }
+
+        if paragraph {
+            PlainOverlay::newlines(&mut plain, 1);
+        }
+        paragraph = true;
+        let text_start = indent + marker(content).unwrap_or(0);
+        let mut text = &line[text_start..];
+        if let Some(stripped) = text.strip_suffix("::") {
+            // a literal block follows, `Example::` renders as `Example:`
+            text = if stripped.is_empty() || stripped.ends_with(char::is_whitespace) {
+                stripped.trim_end()
+            } else {
+                &text[..text.len() - 1]
+            };
+            skip_deeper_than = Some(indent);
+        }
+        inline(text, start + text_start, &mut plain, &mut mapping);
+    }
+
+    // same as for markdown, trailing newlines are pointless
+    let trailing_newlines = plain.chars().rev().take_while(|x| *x == '\n').count();
+    plain.truncate(plain.len() - trailing_newlines);
+    (plain, mapping)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rst_reduction_mapping() {
+        const RST: &str = r#"==============
+Title number 1
+==============
+
+Title number 2
+--------------
+
+.. code-block:: rust
+
+    let x = 777;
+    assert_eq!(x, 777);
+
+Some **extra** *formatting* with ``code`` and a :func:`role`
+or `Rust <https://rust-lang.org>`_ links_.
+
+- A bullet with |substitution| and a footnote [1]_.
+
+:param x: The value.
+
+An example::
+
+    let y = x;
+
+.. note:: Be carefull.
+
+   Really.
+
+.. This is a comment
+   spanning two lines.
+
+And the end."#;
+
+        const PLAIN: &str = r#"Title number 1
+
+Title number 2
+
+Some extra formatting with  and a 
+or Rust links.
+
+A bullet with  and a footnote .
+
+The value.
+
+An example:
+
+Be carefull.
+
+Really.
+
+And the end."#;
+
+        let (reduced, mapping) = extract_plain_with_mapping(RST);
+
+        assert_eq!(dbg!(&reduced).as_str(), PLAIN);
+        assert_eq!(dbg!(&mapping).len(), 20);
+        for (reduced_range, rst_range) in mapping.iter() {
+            assert_eq!(reduced[reduced_range.clone()], RST[rst_range.clone()]);
+        }
+    }
+
+    #[test]
+    fn rst_reduction_inline() {
+        for (rst, expected) in &[
+            ("2*3*4 is not emphasis", "2*3*4 is not emphasis"),
+            ("a | b and snake_case", "a | b and snake_case"),
+            ("See `x`:py:obj: or `default`.", "See  or ."),
+            ("Anonymous `link <x>`__ ok", "Anonymous link ok"),
+            ("A :py:func:`domain` role", "A  role"),
+            ("**bold** text", "bold text"),
+        ] {
+            let (reduced, mapping) = extract_plain_with_mapping(rst);
+            assert_eq!(reduced.as_str(), *expected);
+            for (reduced_range, rst_range) in mapping.iter() {
+                assert_eq!(reduced[reduced_range.clone()], rst[rst_range.clone()]);
+            }
+        }
+    }
+
+    #[test]
+    fn rst_reduction_empty() {
+        for rst in &["", "   ", "\n", " \n\t\n  ", ".. comment only\n"] {
+            let (reduced, mapping) = extract_plain_with_mapping(rst);
+            assert_eq!(reduced.as_str(), "");
+            assert!(mapping.is_empty());
+        }
+    }
+}
@@ -0,0 +1,167 @@
+//! Ignore whole classes of tokens, i.e. acronyms or identifiers, by matching
+//! the text flagged by a suggestion against regular expressions.
+//!
+//! The flagged text is a single word as split by the tokenizer, so it never
+//! contains any of the separators, i.e. `FOO-12` is flagged as `FOO` and `12`.
+
+use crate::SuggestionSet;
+
+use anyhow::{anyhow, Result};
+use log::debug;
+use regex::RegexSet;
+
+/// Compiled set of the configured `ignore_patterns`.
+#[derive(Debug, Clone)]
+pub struct IgnorePatterns {
+    set: RegexSet,
+}
+
+impl IgnorePatterns {
+    pub fn new(patterns: &[String]) -> Result<Self> {
+        let set = RegexSet::new(patterns)
+            .map_err(|e| anyhow!("Invalid ignore pattern in {:?}", patterns).context(e))?;
+        Ok(Self { set })
+    }
+
+    /// If the flagged `text` matches any of the patterns.
+    pub fn is_ignored(&self, text: &str) -> bool {
+        self.set.is_match(text)
+    }
+
+    /// Drop all suggestions whose flagged text matches any of the patterns.
+    pub fn retain(&self, suggestions: &mut SuggestionSet) {
+        if self.set.is_empty() {
+            return;
+        }
+        suggestions.retain(|suggestion| {
+            let covered = if let Some(covered) = crate::report::covered_text(suggestion) {
+                covered
+            } else {
+                return true;
+            };
+            let ignored = self.is_ignored(covered.as_str());
+            if ignored {
+                debug!("Dropping suggestion for >{}< due to a pattern", covered);
+            }
+            !ignored
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Config, Detector, Documentation, LineColumn, Span, Suggestion};
+
+    #[test]
+    fn acronyms_and_tickets() {
+        let config = Config::parse(
+            r#"
+ignore_patterns = ['^[A-Z]{2,}$', '^FOO-\d+$']
+"#,
+        )
+        .unwrap();
+        let patterns = IgnorePatterns::new(&config.ignore_patterns).unwrap();
+        assert!(patterns.is_ignored("HTTP"));
+        assert!(patterns.is_ignored("FOO-1234"));
+        assert!(!patterns.is_ignored("Http"));
+        assert!(!patterns.is_ignored("FOO-"));
+        assert!(!patterns.is_ignored("tpyo"));
+
+        const SOURCE: &str = "/// Fix HTTPS for FOO-12 in Https with a tpyo.\nstruct X;";
+        let docs =
+            Documentation::load_from_str("lib.rs", SOURCE, &Config::default()).expect("Must parse");
+        let (path, literal_sets) = crate::testcase::first(docs.iter());
+        let literal = literal_sets[0].literals()[0];
+        let mut set = SuggestionSet::new();
+        for word in &["HTTPS", "FOO-12", "Https", "tpyo"] {
+            // the span column is relative to the literal, which starts two
+            // characters before the content of the doc comment
+            let start = SOURCE.find(word).unwrap() - 2;
+            set.add(
+                path.clone(),
+                Suggestion {
+                    detector: Detector::Hunspell,
+                    severity: Detector::Hunspell.severity(),
+                    literal: literal.into(),
+                    span: Span {
+                        start: LineColumn {
+                            line: 1,
+                            column: start,
+                        },
+                        end: LineColumn {
+                            line: 1,
+                            column: start + word.len() - 1,
+                        },
+                    },
+                    path: path.clone(),
+                    replacements: Vec::new(),
+                    description: None,
+                },
+            );
+        }
+        let flagged = |set: &SuggestionSet| {
+            set.iter()
+                .flat_map(|(_path, suggestions)| suggestions.iter())
+                .filter_map(crate::report::covered_text)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(flagged(&set), vec!["HTTPS", "FOO-12", "Https", "tpyo"]);
+
+        patterns.retain(&mut set);
+        assert_eq!(flagged(&set), vec!["Https", "tpyo"]);
+    }
+
+    #[test]
+    fn flagged_words() {
+        let dir = crate::testcase::scratch_dir("patterns");
+        std::fs::write(
+            dir.join("deny.txt"),
+            "HTTPS\nsome_ident\nFOO\nHttps\ntpyo\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("spellcheck.toml"),
+            r#"
+ignore_patterns = ['^[A-Z]{2,}$', '^[a-z]+_[a-z_]+$', '^FOO-\d+$']
+
+[wordlist]
+deny = "deny.txt"
+"#,
+        )
+        .unwrap();
+        let config = Config::load_from(dir.join("spellcheck.toml")).unwrap();
+
+        let docs = Documentation::load_from_str(
+            "lib.rs",
+            "/// Fix HTTPS in some_ident for FOO-12 in Https with a tpyo.\nstruct X;",
+            &config,
+        )
+        .expect("Must parse");
+        let mut set = crate::testcase::check(&docs, &config);
+        let flagged = |set: &SuggestionSet| {
+            set.iter()
+                .flat_map(|(_path, suggestions)| suggestions.iter())
+                .filter_map(crate::report::covered_text)
+                .collect::<Vec<_>>()
+        };
+        // the ticket is split at the `-`, its pieces are flagged individually
+        assert_eq!(
+            flagged(&set),
+            vec!["HTTPS", "some_ident", "FOO", "Https", "tpyo"]
+        );
+
+        IgnorePatterns::new(&config.ignore_patterns)
+            .unwrap()
+            .retain(&mut set);
+        assert_eq!(flagged(&set), vec!["Https", "tpyo"]);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(IgnorePatterns::new(&["[a-z".to_owned()]).is_err());
+        assert!(IgnorePatterns::new(&[]).is_ok());
+    }
+}
@@ -0,0 +1,46 @@
+//! Progress of checking a large number of files.
+//!
+//! Shown on `stderr` only if it is a terminal, so neither piped output nor
+//! the machine readable formats are garbled.
+
+use indicatif::{ProgressBar, ProgressStyle};
+
+/// A progress bar of the files checked by each detector, which is a no-op
+/// if hidden.
+pub struct Progress {
+    bar: Option<ProgressBar>,
+}
+
+impl Progress {
+    /// Create a progress bar of `total` checks of a file, only shown if `enabled` and
+    /// `stderr` is a terminal.
+    pub fn new(total: usize, enabled: bool) -> Self {
+        let bar = if enabled && console::Term::stderr().is_term() {
+            let bar = ProgressBar::new(total as u64);
+            bar.set_style(
+                ProgressStyle::default_bar()
+                    .template("{spinner} Checking [{bar:40}] {percent}%")
+                    .progress_chars("=> "),
+            );
+            Some(bar)
+        } else {
+            None
+        };
+        Self { bar }
+    }
+
+    /// Advance by one file checked by one detector.
+    pub fn inc(&self) {
+        if let Some(ref bar) = self.bar {
+            bar.inc(1);
+        }
+    }
+
+    /// Remove the progress bar from the terminal, before anything else is
+    /// printed or the terminal is used otherwise, i.e. by the interactive mode.
+    pub fn finish(self) {
+        if let Some(bar) = self.bar {
+            bar.finish_and_clear();
+        }
+    }
+}
@@ -0,0 +1,167 @@
+//! Checkstyle XML output.
+//!
+//! Every file is represented by a `<file>` element containing one `<error>`
+//! element per suggestion, files without any suggestions are empty.
+
+use super::{location, message, xml_escape};
+use crate::SuggestionSet;
+
+use anyhow::Result;
+
+use std::io::Write;
+
+pub(super) fn write<W: Write>(suggestions_per_path: &SuggestionSet, mut sink: W) -> Result<()> {
+    writeln!(sink, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(sink, r#"<checkstyle version="4.3">"#)?;
+    for (path, suggestions) in suggestions_per_path.iter() {
+        let path = xml_escape(path.display().to_string().as_str());
+        if suggestions.is_empty() {
+            writeln!(sink, r#"  <file name="{}"/>"#, path)?;
+            continue;
+        }
+        writeln!(sink, r#"  <file name="{}">"#, path)?;
+        for suggestion in suggestions {
+            let (line, column) = location(suggestion);
+            writeln!(
+                sink,
+                r#"    <error line="{line}" column="{column}" severity="{severity}" message="{message}" source="cargo-spellcheck.{detector}"/>"#,
+                line = line,
+                column = column,
+                severity = suggestion.severity,
+                message = xml_escape(message(suggestion).as_str()),
+                detector = suggestion.detector,
+            )?;
+        }
+        writeln!(sink, "  </file>")?;
+    }
+    writeln!(sink, "</checkstyle>")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An element with its unescaped attributes.
+    #[derive(Debug, PartialEq)]
+    struct Element {
+        name: String,
+        attributes: Vec<(String, String)>,
+    }
+
+    fn unescape(s: &str) -> String {
+        let mut unescaped = String::with_capacity(s.len());
+        let mut rest = s;
+        while let Some(start) = rest.find('&') {
+            unescaped.push_str(&rest[..start]);
+            let end = rest[start..].find(';').expect("Entity must be terminated") + start;
+            unescaped.push(match &rest[start + 1..end] {
+                "amp" => '&',
+                "lt" => '<',
+                "gt" => '>',
+                "quot" => '"',
+                "apos" => '\'',
+                entity => panic!("Unknown entity {}", entity),
+            });
+            rest = &rest[end + 1..];
+        }
+        unescaped.push_str(rest);
+        unescaped
+    }
+
+    /// Parse the attributes of a tag such as `name="value" other="value"`.
+    fn attributes(mut s: &str) -> Vec<(String, String)> {
+        let mut attributes = Vec::new();
+        while !s.trim().is_empty() {
+            let eq = s.find("=\"").expect("Attribute must have a quoted value");
+            let name = s[..eq].trim().to_owned();
+            let value_len = s[eq + 2..].find('"').expect("Value must be terminated");
+            let value = &s[eq + 2..eq + 2 + value_len];
+            assert!(!value.contains('<'), "Unescaped < in {}", value);
+            attributes.push((name, unescape(value)));
+            s = &s[eq + 2 + value_len + 1..];
+        }
+        attributes
+    }
+
+    /// Verify the nesting of all tags and return all elements in order.
+    fn parse(xml: &str) -> Vec<Element> {
+        let mut elements = Vec::new();
+        let mut open = Vec::<String>::new();
+        let mut rest = xml;
+        while let Some(start) = rest.find('<') {
+            assert!(rest[..start].trim().is_empty(), "No text content expected");
+            let end = rest[start..].find('>').expect("Tag must be closed") + start;
+            let tag = &rest[start + 1..end];
+            rest = &rest[end + 1..];
+            if tag.starts_with('?') {
+                continue;
+            }
+            if let Some(name) = tag.strip_prefix('/') {
+                assert_eq!(open.pop().as_deref(), Some(name));
+                continue;
+            }
+            let (tag, empty) = match tag.strip_suffix('/') {
+                Some(tag) => (tag, true),
+                None => (tag, false),
+            };
+            let mut it = tag.splitn(2, ' ');
+            let name = it.next().unwrap().to_owned();
+            if !empty {
+                open.push(name.clone());
+            }
+            elements.push(Element {
+                name,
+                attributes: attributes(it.next().unwrap_or_default()),
+            });
+        }
+        assert!(rest.trim().is_empty());
+        assert!(open.is_empty(), "Unclosed elements {:?}", open);
+        elements
+    }
+
+    #[test]
+    fn well_formed() {
+        let docs = crate::testcase::documentation();
+        let set = crate::testcase::suggestion_set(&docs);
+        let mut sink = Vec::<u8>::with_capacity(1024);
+        write(&set, &mut sink).expect("Must write");
+        let xml = String::from_utf8(sink).unwrap();
+
+        let elements = parse(xml.as_str());
+        let names = elements
+            .iter()
+            .map(|element| element.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["checkstyle", "file", "error", "error", "file"]);
+        assert_eq!(
+            elements[1].attributes,
+            vec![("name".to_owned(), "src/lib.rs".to_owned())]
+        );
+        // misspellings are errors, grammar findings warnings
+        assert_eq!(
+            elements[2].attributes[2],
+            ("severity".to_owned(), "error".to_owned())
+        );
+        assert_eq!(
+            elements[3].attributes,
+            vec![
+                ("line".to_owned(), "1".to_owned()),
+                ("column".to_owned(), "17".to_owned()),
+                ("severity".to_owned(), "warning".to_owned()),
+                (
+                    "message".to_owned(),
+                    "Use \"and\" instead of <&>. `&` Replacements: and".to_owned()
+                ),
+                (
+                    "source".to_owned(),
+                    "cargo-spellcheck.LanguageTool".to_owned()
+                ),
+            ]
+        );
+        assert_eq!(
+            elements[4].attributes,
+            vec![("name".to_owned(), "src/clean.rs".to_owned())]
+        );
+    }
+}
@@ -0,0 +1,101 @@
+//! GitHub Actions workflow commands.
+//!
+//! Every suggestion is one `::warning file=..,line=..::message` line, which
+//! the runner turns into an annotation of the pull request diff, no upload
+//! of a report is required.
+
+use super::{end_location, location, message};
+use crate::{Severity, SuggestionSet};
+
+use anyhow::Result;
+
+use std::io::Write;
+
+/// The workflow command corresponding to `severity`.
+fn command(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Info => "notice",
+    }
+}
+
+/// Escape the message of a workflow command.
+fn escape_data(s: &str) -> String {
+    s.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escape the value of a property of a workflow command, which additionally
+/// must not contain the delimiters of properties.
+fn escape_property(s: &str) -> String {
+    escape_data(s).replace(':', "%3A").replace(',', "%2C")
+}
+
+pub(super) fn write<W: Write>(suggestions_per_path: &SuggestionSet, mut sink: W) -> Result<()> {
+    for (path, suggestions) in suggestions_per_path.iter() {
+        let path = escape_property(path.display().to_string().as_str());
+        for suggestion in suggestions {
+            let (line, column) = location(suggestion);
+            let (end_line, end_column) = end_location(suggestion);
+            writeln!(
+                sink,
+                "::{command} file={path},line={line},col={column},endLine={end_line},endColumn={end_column},title={title}::{message}",
+                command = command(suggestion.severity),
+                path = path,
+                line = line,
+                column = column,
+                end_line = end_line,
+                end_column = end_column,
+                title = escape_property(format!("spellcheck({})", suggestion.detector).as_str()),
+                message = escape_data(message(suggestion).as_str()),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands() {
+        let docs = crate::testcase::documentation();
+        let set = crate::testcase::suggestion_set(&docs);
+        let mut sink = Vec::<u8>::with_capacity(1024);
+        write(&set, &mut sink).expect("Must write");
+        let printed = String::from_utf8(sink).unwrap();
+        let lines = printed.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[0],
+            "::error file=src/lib.rs,line=1,col=7,endLine=1,endColumn=10,title=spellcheck(Hunspell)::Possible spelling mistake found. `tpyo` Replacements: typo, type"
+        );
+        assert!(lines[1].starts_with("::warning file=src/lib.rs,line=1,col=17,"));
+
+        // a command is a single line, whatever the message contains
+        let mut multiline = SuggestionSet::new();
+        for (path, suggestions) in set.iter() {
+            multiline.extend(
+                path.clone(),
+                suggestions.iter().cloned().map(|mut suggestion| {
+                    suggestion.description = Some("100% wrong,\nfor real: yes".to_owned());
+                    suggestion
+                }),
+            );
+        }
+        let mut sink = Vec::<u8>::with_capacity(1024);
+        write(&multiline, &mut sink).expect("Must write");
+        let printed = String::from_utf8(sink).unwrap();
+        assert_eq!(printed.lines().count(), 2);
+        assert!(printed.contains("::100%25 wrong,%0Afor real: yes `tpyo`"));
+    }
+
+    #[test]
+    fn properties() {
+        assert_eq!(escape_property("C:\\a,b%"), "C%3A\\a%2Cb%25");
+        assert_eq!(escape_data("a: b,\r\nc"), "a: b,%0D%0Ac");
+    }
+}
@@ -0,0 +1,131 @@
+//! JSON output.
+//!
+//! A single object for consumption by editors and custom tooling:
+//!
+//! ```json
+//! {
+//!   "findings": [
+//!     {
+//!       "path": "src/lib.rs",
+//!       "line": 1,
+//!       "column": 7,
+//!       "end": { "line": 1, "column": 10 },
+//!       "bytes": { "start": 6, "end": 10 },
+//!       "text": "tpyo",
+//!       "replacements": ["typo", "type"],
+//!       "detector": "Hunspell",
+//!       "severity": "error",
+//!       "description": "Possible spelling mistake found."
+//!     }
+//!   ],
+//!   "summary": {
+//!     "files": 1,
+//!     "suggestions": 1,
+//!     "detectors": { "Hunspell": 1 },
+//!     "severities": { "error": 1 }
+//!   }
+//! }
+//! ```
+//!
+//! The `findings` are ordered by file and span, lines and columns are
+//! 1-indexed and `end` is inclusive, while the byte range excludes its end.
+//! `bytes` is `null` if the file is not readable, `text` if the finding spans
+//! multiple literals. The `summary` holds the totals, as printed after a check.
+
+use super::{byte_offset, covered_text, end_location, location, Summary};
+use crate::{Severity, Suggestion, SuggestionSet};
+
+use anyhow::Result;
+use serde::Serialize;
+
+use std::io::Write;
+use std::path::Path;
+
+/// Line and column within the file, both 1-indexed.
+#[derive(Debug, Serialize)]
+struct Position {
+    line: usize,
+    column: usize,
+}
+
+/// Byte offsets within the file, the end is exclusive.
+#[derive(Debug, Serialize)]
+struct Bytes {
+    start: usize,
+    end: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct Finding<'a> {
+    path: &'a Path,
+    line: usize,
+    column: usize,
+    /// The last character covered, inclusive.
+    end: Position,
+    /// `None` if the file is not readable.
+    bytes: Option<Bytes>,
+    /// `None` if the covered text spans multiple literals.
+    text: Option<String>,
+    replacements: &'a [String],
+    detector: String,
+    severity: Severity,
+    description: Option<&'a str>,
+}
+
+#[derive(Debug, Serialize)]
+struct Report<'a> {
+    findings: Vec<Finding<'a>>,
+    summary: Summary,
+}
+
+fn finding<'a>(suggestion: &'a Suggestion, content: Option<&str>) -> Finding<'a> {
+    let (line, column) = location(suggestion);
+    let (end_line, end_column) = end_location(suggestion);
+    let end = Position {
+        line: end_line,
+        column: end_column,
+    };
+    let bytes = content.and_then(|content| {
+        Some(Bytes {
+            start: byte_offset(content, line, column - 1)?,
+            end: byte_offset(content, end.line, end.column)?,
+        })
+    });
+    Finding {
+        path: suggestion.path.as_path(),
+        line,
+        column,
+        end,
+        bytes,
+        text: covered_text(suggestion),
+        replacements: suggestion.replacements.as_slice(),
+        detector: suggestion.detector.to_string(),
+        severity: suggestion.severity,
+        description: suggestion.description.as_deref(),
+    }
+}
+
+/// Write all suggestions, `read` provides the file content to calculate the byte offsets.
+pub(super) fn write<W, R>(suggestions_per_path: &SuggestionSet, read: R, mut sink: W) -> Result<()>
+where
+    W: Write,
+    R: Fn(&Path) -> Option<String>,
+{
+    let mut findings = Vec::with_capacity(suggestions_per_path.count());
+    let mut contents = Vec::with_capacity(suggestions_per_path.len());
+    for (path, _suggestions) in suggestions_per_path.iter() {
+        contents.push(read(path));
+    }
+    for ((_path, suggestions), content) in suggestions_per_path.iter().zip(contents.iter()) {
+        for suggestion in suggestions {
+            findings.push(finding(suggestion, content.as_deref()));
+        }
+    }
+    let report = Report {
+        findings,
+        summary: Summary::of(suggestions_per_path),
+    };
+    serde_json::to_writer_pretty(&mut sink, &report)?;
+    writeln!(sink)?;
+    Ok(())
+}
@@ -0,0 +1,67 @@
+//! JUnit XML output.
+//!
+//! Every file is represented by a test suite, every suggestion by a failing
+//! test case. Files without any suggestions contain a single passing test case.
+
+use super::{location, message, xml_escape};
+use crate::SuggestionSet;
+
+use anyhow::Result;
+
+use std::io::Write;
+
+pub(super) fn write<W: Write>(suggestions_per_path: &SuggestionSet, mut sink: W) -> Result<()> {
+    let tests: usize = suggestions_per_path
+        .iter()
+        .map(|(_path, suggestions)| suggestions.len().max(1))
+        .sum();
+    let failures = suggestions_per_path.count();
+
+    writeln!(sink, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        sink,
+        r#"<testsuites name="cargo-spellcheck" tests="{}" failures="{}">"#,
+        tests, failures
+    )?;
+    for (path, suggestions) in suggestions_per_path.iter() {
+        let path = xml_escape(path.display().to_string().as_str());
+        writeln!(
+            sink,
+            r#"  <testsuite name="{path}" tests="{tests}" failures="{failures}">"#,
+            path = path,
+            tests = suggestions.len().max(1),
+            failures = suggestions.len(),
+        )?;
+        if suggestions.is_empty() {
+            writeln!(
+                sink,
+                r#"    <testcase name="spellcheck" classname="{}"/>"#,
+                path
+            )?;
+        }
+        for suggestion in suggestions {
+            let (line, column) = location(suggestion);
+            let message = xml_escape(message(suggestion).as_str());
+            writeln!(
+                sink,
+                r#"    <testcase name="{path}:{line}:{column}" classname="{path}">"#,
+                path = path,
+                line = line,
+                column = column,
+            )?;
+            writeln!(
+                sink,
+                r#"      <failure type="{detector}" message="{message}">{path}:{line}:{column}: {message}</failure>"#,
+                detector = suggestion.detector,
+                message = message,
+                path = path,
+                line = line,
+                column = column,
+            )?;
+            writeln!(sink, "    </testcase>")?;
+        }
+        writeln!(sink, "  </testsuite>")?;
+    }
+    writeln!(sink, "</testsuites>")?;
+    Ok(())
+}
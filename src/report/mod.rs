@@ -0,0 +1,617 @@
+//! Reporting of the check results in a particular output format.
+//!
+//! Besides the human readable default, a few machine readable formats
+//! are provided for integration with CI systems.
+
+use crate::{Range, Severity, Suggestion, SuggestionSet, DEFAULT_TAB_WIDTH};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::Write;
+use std::path::Path;
+
+mod checkstyle;
+mod github;
+mod json;
+mod junit;
+mod sarif;
+
+/// Output format of the check results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum OutputFormat {
+    /// Human readable and colored, printed to `stderr`.
+    Human,
+    /// JUnit XML, each file is a test suite and each suggestion a failing test case.
+    JUnit,
+    /// JSON, an array of all suggestions with their location and replacements.
+    Json,
+    /// SARIF 2.1.0, for code scanning tools, each detector is a rule.
+    Sarif,
+    /// Checkstyle XML, each file is a `<file>` and each suggestion an `<error>` element.
+    Checkstyle,
+    /// GitHub Actions workflow commands, each suggestion is an annotation.
+    GitHub,
+}
+
+impl Default for OutputFormat {
+    fn default() -> Self {
+        Self::Human
+    }
+}
+
+impl OutputFormat {
+    /// The format used if none is selected explicitly, workflow commands when
+    /// running within GitHub Actions, which sets `GITHUB_ACTIONS=true`.
+    pub fn detect() -> Self {
+        Self::detect_from(std::env::var("GITHUB_ACTIONS").ok().as_deref())
+    }
+
+    fn detect_from(github_actions: Option<&str>) -> Self {
+        match github_actions {
+            Some("true") => Self::GitHub,
+            _ => Self::default(),
+        }
+    }
+
+    /// Write all suggestions to `sink` in the selected format.
+    pub fn write<W: Write>(&self, suggestions_per_path: &SuggestionSet, mut sink: W) -> Result<()> {
+        match self {
+            Self::Human => human(suggestions_per_path, None, DEFAULT_TAB_WIDTH, &mut sink)?,
+            Self::JUnit => junit::write(suggestions_per_path, &mut sink)?,
+            Self::Json => json::write(suggestions_per_path, read, &mut sink)?,
+            Self::Sarif => sarif::write(suggestions_per_path, read, &mut sink)?,
+            Self::Checkstyle => checkstyle::write(suggestions_per_path, &mut sink)?,
+            Self::GitHub => github::write(suggestions_per_path, &mut sink)?,
+        }
+        sink.flush()?;
+        Ok(())
+    }
+}
+
+/// Write all suggestions human readable, the inline replacements are limited
+/// to `width` columns if given, otherwise all of them are listed.
+///
+/// The source lines of the readable files are shown with the span underlined,
+/// with tabs expanded to multiples of `tab_width`.
+pub(crate) fn human<W: Write>(
+    suggestions_per_path: &SuggestionSet,
+    width: Option<usize>,
+    tab_width: usize,
+    sink: W,
+) -> Result<()> {
+    human_with(suggestions_per_path, width, tab_width, read, sink)
+}
+
+/// `human` with `read` providing the file content.
+fn human_with<W, R>(
+    suggestions_per_path: &SuggestionSet,
+    width: Option<usize>,
+    tab_width: usize,
+    read: R,
+    mut sink: W,
+) -> Result<()>
+where
+    W: Write,
+    R: Fn(&Path) -> Option<String>,
+{
+    for (path, suggestions) in suggestions_per_path.iter() {
+        let content = read(path);
+        let lines = content
+            .as_deref()
+            .map(|content| {
+                crate::documentation::strip_bom(content)
+                    .lines()
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        for suggestion in suggestions {
+            let mut diagnostic = suggestion.diagnostic().tab_width(tab_width);
+            if let Some(width) = width {
+                diagnostic = diagnostic.width(width);
+            }
+            // the literal is shown instead, if the file is not readable
+            if let Some(line) = lines.get(suggestion.span.start.line.wrapping_sub(1)) {
+                diagnostic = diagnostic.source(line);
+            }
+            writeln!(sink, "{}", diagnostic)?;
+        }
+    }
+    sink.flush()?;
+    Ok(())
+}
+
+/// Content of the file at `path`, if readable, to calculate byte offsets.
+fn read(path: &Path) -> Option<String> {
+    std::fs::read_to_string(path).ok()
+}
+
+/// Line and column of the first character covered by `suggestion`
+/// within the file, both are 1-indexed.
+pub(crate) fn location(suggestion: &Suggestion) -> (usize, usize) {
+    let start = suggestion.span.start;
+    (
+        start.line,
+        suggestion.literal.variant().file_column(start.column) + 1,
+    )
+}
+
+/// Line and column of the last character covered by `suggestion`
+/// within the file, both are 1-indexed.
+pub(crate) fn end_location(suggestion: &Suggestion) -> (usize, usize) {
+    let end = suggestion.span.end;
+    (
+        end.line,
+        suggestion.literal.variant().file_column(end.column) + 1,
+    )
+}
+
+/// Byte offset of the character at the 0-indexed `column` of the 1-indexed `line`.
+///
+/// The column right after the last character of a line is valid too, any
+/// further one is not. A leading byte order mark is not a column of the first
+/// line, but the offset is within `content` as a whole, including it.
+pub(crate) fn byte_offset(content: &str, line: usize, column: usize) -> Option<usize> {
+    let stripped = crate::documentation::strip_bom(content);
+    let bom = content.len() - stripped.len();
+    let content = stripped;
+    let line_start = match line {
+        0 => return None,
+        1 => 0,
+        line => content.match_indices('\n').nth(line - 2)?.0 + 1,
+    };
+    let rest = content[line_start..].split('\n').next().unwrap_or_default();
+    rest.char_indices()
+        .map(|(offset, _c)| offset)
+        .chain(std::iter::once(rest.len()))
+        .nth(column)
+        .map(|offset| bom + line_start + offset)
+}
+
+/// The text covered by `suggestion`, if it is contained in a single literal.
+pub(crate) fn covered_text(suggestion: &Suggestion) -> Option<String> {
+    let literal = suggestion.literal.as_ref();
+    let range: Range = literal.untrimmed_range(&suggestion.span)?;
+    literal
+        .as_untrimmed_str()
+        .get(range)
+        .map(|covered| covered.to_owned())
+}
+
+/// Human readable message of `suggestion` including the replacements.
+pub(crate) fn message(suggestion: &Suggestion) -> String {
+    let mut message = suggestion
+        .description
+        .clone()
+        .unwrap_or_else(|| "Possible spelling mistake found.".to_owned());
+    if let Some(covered) = covered_text(suggestion) {
+        message = format!("{} `{}`", message, covered);
+    }
+    if !suggestion.replacements.is_empty() {
+        message = format!(
+            "{} Replacements: {}",
+            message,
+            suggestion.replacements.join(", ")
+        );
+    }
+    message
+}
+
+/// Totals of a check, per detector and per severity.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Summary {
+    /// Number of files checked, including those without any suggestions.
+    pub files: usize,
+    pub suggestions: usize,
+    pub detectors: BTreeMap<String, usize>,
+    pub severities: BTreeMap<Severity, usize>,
+}
+
+impl Summary {
+    pub fn of(suggestions_per_path: &SuggestionSet) -> Self {
+        let mut summary = Self {
+            files: suggestions_per_path.len(),
+            ..Self::default()
+        };
+        for (_path, suggestions) in suggestions_per_path.iter() {
+            for suggestion in suggestions {
+                summary.suggestions += 1;
+                *summary
+                    .detectors
+                    .entry(suggestion.detector.to_string())
+                    .or_default() += 1;
+                *summary.severities.entry(suggestion.severity).or_default() += 1;
+            }
+        }
+        summary
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            formatter,
+            "Checked {} file(s), found {} suggestion(s)",
+            self.files, self.suggestions
+        )?;
+        if self.suggestions == 0 {
+            return Ok(());
+        }
+        let detectors = self
+            .detectors
+            .iter()
+            .map(|(detector, count)| format!("{}: {}", detector, count))
+            .collect::<Vec<_>>();
+        // the most severe first
+        let severities = self
+            .severities
+            .iter()
+            .rev()
+            .map(|(severity, count)| format!("{}: {}", severity, count))
+            .collect::<Vec<_>>();
+        write!(
+            formatter,
+            " ({}; {})",
+            detectors.join(", "),
+            severities.join(", ")
+        )
+    }
+}
+
+/// Escape `s` for use as XML attribute value or text content.
+pub(crate) fn xml_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testcase::{documentation, suggestion_set, SOURCE};
+    use crate::{Config, Detector, Documentation, LineColumn, Span};
+    use std::path::PathBuf;
+
+    #[test]
+    fn location_and_message() {
+        let docs = documentation();
+        let set = suggestion_set(&docs);
+        let (_, suggestions) = set.iter().next().unwrap();
+        assert_eq!(location(&suggestions[0]), (1, 7));
+        assert_eq!(covered_text(&suggestions[0]).as_deref(), Some("tpyo"));
+        assert_eq!(
+            message(&suggestions[0]),
+            "Possible spelling mistake found. `tpyo` Replacements: typo, type"
+        );
+        assert_eq!(location(&suggestions[1]), (1, 17));
+        assert_eq!(covered_text(&suggestions[1]).as_deref(), Some("&"));
+    }
+
+    #[test]
+    fn offsets() {
+        let content = "/// Ä tpyo\n/// b\n";
+        assert_eq!(byte_offset(content, 1, 0), Some(0));
+        assert_eq!(byte_offset(content, 1, 6), Some(7));
+        assert_eq!(byte_offset(content, 1, 10), Some(11));
+        assert_eq!(byte_offset(content, 1, 11), None);
+        assert_eq!(byte_offset(content, 2, 4), Some(16));
+        assert_eq!(byte_offset(content, 3, 0), Some(18));
+        assert_eq!(byte_offset(content, 4, 0), None);
+
+        // the byte order mark precedes the first column
+        let content = "\u{feff}A tpyo\nB";
+        assert_eq!(byte_offset(content, 1, 2), Some(3 + 2));
+        assert_eq!(byte_offset(content, 1, 6), Some(3 + 6));
+        assert_eq!(byte_offset(content, 1, 7), None);
+        assert_eq!(byte_offset(content, 2, 0), Some(3 + 7));
+    }
+
+    #[test]
+    fn escape() {
+        assert_eq!(
+            xml_escape(r#"<a href="x">'&'</a>"#),
+            "&lt;a href=&quot;x&quot;&gt;&apos;&amp;&apos;&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn detect() {
+        assert_eq!(
+            OutputFormat::detect_from(Some("true")),
+            OutputFormat::GitHub
+        );
+        assert_eq!(
+            OutputFormat::detect_from(Some("false")),
+            OutputFormat::Human
+        );
+        assert_eq!(OutputFormat::detect_from(None), OutputFormat::Human);
+    }
+
+    #[test]
+    fn detector_label() {
+        let docs = documentation();
+        let all = suggestion_set(&docs);
+        for detector in enumflags2::BitFlags::<Detector>::all().iter() {
+            let mut set = SuggestionSet::new();
+            for (path, suggestions) in all.iter() {
+                set.extend(
+                    path.clone(),
+                    suggestions.iter().cloned().map(|mut suggestion| {
+                        suggestion.detector = detector;
+                        suggestion
+                    }),
+                );
+            }
+
+            let label = detector.to_string();
+            for format in &[
+                OutputFormat::Human,
+                OutputFormat::JUnit,
+                OutputFormat::Json,
+                OutputFormat::Sarif,
+                OutputFormat::Checkstyle,
+                OutputFormat::GitHub,
+            ] {
+                let mut sink = Vec::<u8>::with_capacity(1024);
+                format.write(&set, &mut sink).expect("Must write");
+                let output =
+                    console::strip_ansi_codes(&String::from_utf8_lossy(&sink)).into_owned();
+                let expected = match format {
+                    OutputFormat::Human => format!("spellcheck({})", label),
+                    _ => label.clone(),
+                };
+                assert!(
+                    output.contains(&expected),
+                    "{:?} output lacks {}",
+                    format,
+                    expected
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn fit_to_width() {
+        let docs = documentation();
+        let set = suggestion_set(&docs);
+        let (_, suggestions) = set.iter().next().unwrap();
+        let mut suggestion = suggestions[0].clone();
+        suggestion.replacements = vec!["typo", "type", "tipo", "taro", "tyro", "trypo"]
+            .into_iter()
+            .map(str::to_owned)
+            .collect();
+        let printed = |width: Option<usize>| {
+            let mut set = SuggestionSet::new();
+            set.add(suggestion.path.clone(), suggestion.clone());
+            let mut sink = Vec::<u8>::with_capacity(1024);
+            human_with(&set, width, DEFAULT_TAB_WIDTH, |_path| None, &mut sink).unwrap();
+            let printed = console::strip_ansi_codes(&String::from_utf8_lossy(&sink)).into_owned();
+            printed
+                .lines()
+                .find(|line| line.contains(" - "))
+                .unwrap()
+                .to_owned()
+        };
+
+        // piped output lists all of them
+        let all = printed(None);
+        assert!(all.ends_with(" - typo, type, tipo, taro, tyro, or trypo"));
+        assert_eq!(printed(Some(80)), all);
+
+        let narrow = printed(Some(30));
+        assert!(narrow.chars().count() <= 30, "{}", narrow);
+        assert!(narrow.ends_with(" - typo, type, …and 4 more"), "{}", narrow);
+
+        // at least one replacement is always shown
+        assert!(printed(Some(5)).ends_with(" - typo, …and 5 more"));
+    }
+
+    #[test]
+    fn source_underline() {
+        const INDENTED: &str = "\t/// Größe tpyo ünd so.\nstruct X;\n";
+        let docs = Documentation::load_from_str("src/lib.rs", INDENTED, &Config::default())
+            .expect("Must parse");
+        let (path, literal_sets) = docs.iter().next().unwrap();
+        let literal = literal_sets[0].literals()[0];
+        let mut set = SuggestionSet::new();
+        set.add(
+            path.clone(),
+            Suggestion {
+                detector: Detector::Hunspell,
+                severity: Detector::Hunspell.severity(),
+                path: path.clone(),
+                literal: literal.into(),
+                span: Span {
+                    start: LineColumn { line: 1, column: 9 },
+                    end: LineColumn {
+                        line: 1,
+                        column: 12,
+                    },
+                },
+                replacements: vec!["typo".to_owned()],
+                description: Some("Possible spelling mistake found.".to_owned()),
+            },
+        );
+        let mut sink = Vec::<u8>::with_capacity(1024);
+        human_with(
+            &set,
+            None,
+            DEFAULT_TAB_WIDTH,
+            |_path| Some(INDENTED.to_owned()),
+            &mut sink,
+        )
+        .unwrap();
+        let printed = console::strip_ansi_codes(&String::from_utf8_lossy(&sink)).into_owned();
+        // the tab is expanded and the carets are placed by characters, not bytes
+        assert_eq!(
+            printed,
+            r#"error: spellcheck(Hunspell)
+  --> src/lib.rs:1
+   |
+ 1 |     /// Größe tpyo ünd so.
+   |               ^^^^
+   | - typo
+   |
+   |   Possible spelling mistake found.
+   |
+
+"#
+        );
+    }
+
+    #[test]
+    fn summary() {
+        let docs = documentation();
+        let set = suggestion_set(&docs);
+        let summary = Summary::of(&set);
+        assert_eq!(summary.files, 2);
+        assert_eq!(summary.suggestions, 2);
+        assert_eq!(summary.detectors.get("Hunspell"), Some(&1));
+        assert_eq!(summary.detectors.get("LanguageTool"), Some(&1));
+        assert_eq!(summary.severities.get(&Severity::Error), Some(&1));
+        assert_eq!(summary.severities.get(&Severity::Warning), Some(&1));
+        assert_eq!(summary.severities.get(&Severity::Info), None);
+        assert_eq!(
+            summary.to_string(),
+            "Checked 2 file(s), found 2 suggestion(s) (Hunspell: 1, LanguageTool: 1; error: 1, warning: 1)"
+        );
+
+        let mut clean = SuggestionSet::new();
+        clean.extend(PathBuf::from("src/clean.rs"), Vec::new());
+        assert_eq!(
+            Summary::of(&clean).to_string(),
+            "Checked 1 file(s), found 0 suggestion(s)"
+        );
+    }
+
+    #[test]
+    fn golden_json() {
+        let docs = documentation();
+        let set = suggestion_set(&docs);
+        let mut sink = Vec::<u8>::with_capacity(1024);
+        json::write(
+            &set,
+            |path| {
+                if path == PathBuf::from("src/lib.rs") {
+                    Some(SOURCE.to_owned())
+                } else {
+                    None
+                }
+            },
+            &mut sink,
+        )
+        .expect("Must write");
+        assert_eq!(
+            String::from_utf8_lossy(sink.as_slice()),
+            include_str!("golden/findings.json")
+        );
+    }
+
+    #[test]
+    fn golden_sarif() {
+        let docs = documentation();
+        let set = suggestion_set(&docs);
+        let mut sink = Vec::<u8>::with_capacity(1024);
+        sarif::write(
+            &set,
+            |path| {
+                if path == PathBuf::from("src/lib.rs") {
+                    Some(SOURCE.to_owned())
+                } else {
+                    None
+                }
+            },
+            &mut sink,
+        )
+        .expect("Must write");
+        assert_eq!(
+            String::from_utf8_lossy(sink.as_slice()),
+            include_str!("golden/findings.sarif")
+        );
+    }
+
+    #[test]
+    fn golden_junit() {
+        let docs = documentation();
+        let set = suggestion_set(&docs);
+        let mut sink = Vec::<u8>::with_capacity(1024);
+        OutputFormat::JUnit
+            .write(&set, &mut sink)
+            .expect("Must write");
+        assert_eq!(
+            String::from_utf8_lossy(sink.as_slice()),
+            include_str!("golden/junit.xml")
+        );
+    }
+
+    #[test]
+    fn tab_stops() {
+        const TABBED: &str = "struct X;\t/// A tpyo.\nstruct Y;\n";
+        let docs = Documentation::load_from_str("src/lib.rs", TABBED, &Config::default())
+            .expect("Must parse");
+        let (path, literal_sets) = docs.iter().next().unwrap();
+        let literal = literal_sets[0].literals()[0];
+        let mut set = SuggestionSet::new();
+        set.add(
+            path.clone(),
+            Suggestion {
+                detector: Detector::Hunspell,
+                severity: Detector::Hunspell.severity(),
+                path: path.clone(),
+                literal: literal.into(),
+                span: Span {
+                    start: LineColumn {
+                        line: 1,
+                        column: 14,
+                    },
+                    end: LineColumn {
+                        line: 1,
+                        column: 17,
+                    },
+                },
+                replacements: vec!["typo".to_owned()],
+                description: None,
+            },
+        );
+        // the spans themselves remain in characters
+        assert_eq!(
+            covered_text(&set.iter().next().unwrap().1[0]).unwrap(),
+            "tpyo"
+        );
+
+        for &(tab_width, spaces) in &[(2usize, 1usize), (4, 3), (8, 7)] {
+            let mut sink = Vec::<u8>::with_capacity(1024);
+            human_with(
+                &set,
+                None,
+                tab_width,
+                |_path| Some(TABBED.to_owned()),
+                &mut sink,
+            )
+            .unwrap();
+            let printed = console::strip_ansi_codes(&String::from_utf8_lossy(&sink)).into_owned();
+            let lines = printed.lines().collect::<Vec<_>>();
+            let expanded = format!("struct X;{}/// A tpyo.", " ".repeat(spaces));
+            assert_eq!(lines[3], format!(" 1 | {}", expanded));
+            // the carets are right below the misspelled word
+            assert_eq!(
+                lines[4].find('^').unwrap(),
+                lines[3].find("tpyo").unwrap(),
+                "tab width {}",
+                tab_width
+            );
+            assert_eq!(lines[4].matches('^').count(), 4);
+        }
+    }
+}
@@ -0,0 +1,175 @@
+//! SARIF 2.1.0 output.
+//!
+//! A single run with one result per suggestion, the rule of each result is
+//! the detector which found it. Understood by code scanning tools, i.e. the
+//! one of GitHub, to annotate the affected lines inline.
+
+use super::{byte_offset, end_location, location, message};
+use crate::{Severity, Suggestion, SuggestionSet};
+
+use anyhow::Result;
+use serde::Serialize;
+
+use std::collections::BTreeSet;
+use std::io::Write;
+use std::path::Path;
+
+#[derive(Debug, Serialize)]
+struct Log<'a> {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: Vec<Run<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct Run<'a> {
+    tool: Tool,
+    results: Vec<SarifResult<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct Tool {
+    driver: Driver,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Driver {
+    name: &'static str,
+    information_uri: &'static str,
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug, Serialize)]
+struct Rule {
+    id: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult<'a> {
+    rule_id: String,
+    level: &'static str,
+    message: Message,
+    locations: Vec<Location<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct Message {
+    text: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Location<'a> {
+    physical_location: PhysicalLocation<'a>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PhysicalLocation<'a> {
+    artifact_location: ArtifactLocation<'a>,
+    region: Region,
+}
+
+#[derive(Debug, Serialize)]
+struct ArtifactLocation<'a> {
+    uri: &'a Path,
+}
+
+/// Lines and columns are 1-indexed, the end column is exclusive.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Region {
+    start_line: usize,
+    start_column: usize,
+    end_line: usize,
+    end_column: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    byte_offset: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    byte_length: Option<usize>,
+}
+
+/// The SARIF level corresponding to `severity`.
+fn level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Info => "note",
+    }
+}
+
+fn result<'a>(suggestion: &'a Suggestion, content: Option<&str>) -> SarifResult<'a> {
+    let (start_line, start_column) = location(suggestion);
+    let (end_line, end_column) = end_location(suggestion);
+    let bytes = content.and_then(|content| {
+        let start = byte_offset(content, start_line, start_column - 1)?;
+        let end = byte_offset(content, end_line, end_column)?;
+        Some((start, end - start))
+    });
+    SarifResult {
+        rule_id: suggestion.detector.to_string(),
+        level: level(suggestion.severity),
+        message: Message {
+            text: message(suggestion),
+        },
+        locations: vec![Location {
+            physical_location: PhysicalLocation {
+                artifact_location: ArtifactLocation {
+                    uri: suggestion.path.as_path(),
+                },
+                region: Region {
+                    start_line,
+                    start_column,
+                    end_line,
+                    end_column: end_column + 1,
+                    byte_offset: bytes.map(|(offset, _length)| offset),
+                    byte_length: bytes.map(|(_offset, length)| length),
+                },
+            },
+        }],
+    }
+}
+
+/// Write all suggestions, `read` provides the file content to calculate the byte regions.
+pub(super) fn write<W, R>(suggestions_per_path: &SuggestionSet, read: R, mut sink: W) -> Result<()>
+where
+    W: Write,
+    R: Fn(&Path) -> Option<String>,
+{
+    let mut rules = BTreeSet::new();
+    let mut contents = Vec::with_capacity(suggestions_per_path.len());
+    for (path, suggestions) in suggestions_per_path.iter() {
+        contents.push(read(path));
+        rules.extend(
+            suggestions
+                .iter()
+                .map(|suggestion| suggestion.detector.to_string()),
+        );
+    }
+    let mut results = Vec::with_capacity(suggestions_per_path.count());
+    for ((_path, suggestions), content) in suggestions_per_path.iter().zip(contents.iter()) {
+        for suggestion in suggestions {
+            results.push(result(suggestion, content.as_deref()));
+        }
+    }
+    let log = Log {
+        schema: "https://json.schemastore.org/sarif-2.1.0.json",
+        version: "2.1.0",
+        runs: vec![Run {
+            tool: Tool {
+                driver: Driver {
+                    name: "cargo-spellcheck",
+                    information_uri: "https://github.com/drahnr/cargo-spellcheck",
+                    rules: rules.into_iter().map(|id| Rule { id }).collect(),
+                },
+            },
+            results,
+        }],
+    };
+    serde_json::to_writer_pretty(&mut sink, &log)?;
+    writeln!(sink)?;
+    Ok(())
+}
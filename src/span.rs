@@ -56,8 +56,63 @@ impl Span {
 
     /// Check if `self` span covers provided `line` number, which is 1-indexed.
     pub fn covers_line(&self, line: usize) -> bool {
-        self.end.line <= line && line >= self.start.line
+        self.start.line <= line && line <= self.end.line
     }
+
+    /// The 1-indexed `(line, column)` of the first and the last character,
+    /// as commonly shown by editors.
+    ///
+    /// Columns count UTF-8 characters, not bytes, so `ö` is a single column.
+    pub fn to_line_column(&self) -> ((usize, usize), (usize, usize)) {
+        (
+            (self.start.line, self.start.column + 1),
+            (self.end.line, self.end.column + 1),
+        )
+    }
+
+    /// Create a span from the 1-indexed `(line, column)` of its first and
+    /// last character, the inverse of `to_line_column`.
+    ///
+    /// Columns count UTF-8 characters, not bytes.
+    pub fn from_line_column(start: (usize, usize), end: (usize, usize)) -> Result<Self> {
+        if start.0 == 0 || start.1 == 0 || end.0 == 0 || end.1 == 0 {
+            return Err(anyhow!(
+                "Lines and columns are 1-indexed, got {:?} to {:?}",
+                start,
+                end
+            ));
+        }
+        if (end.0, end.1) < (start.0, start.1) {
+            return Err(anyhow!("End {:?} precedes start {:?}", end, start));
+        }
+        Ok(Self {
+            start: LineColumn {
+                line: start.0,
+                column: start.1 - 1,
+            },
+            end: LineColumn {
+                line: end.0,
+                column: end.1 - 1,
+            },
+        })
+    }
+
+    /// The byte range covered within `line`, the content of the single
+    /// line `self` is on, for slicing it.
+    pub fn byte_range(&self, line: &str) -> Result<Range> {
+        let chars: Range = self.try_into()?;
+        Ok(byte_offset(line, chars.start)?..byte_offset(line, chars.end)?)
+    }
+}
+
+/// The byte offset of the character at `column` within `line`, the length
+/// of `line` for the column right past its last character.
+pub(crate) fn byte_offset(line: &str, column: usize) -> Result<usize> {
+    line.char_indices()
+        .map(|(offset, _c)| offset)
+        .chain(std::iter::once(line.len()))
+        .nth(column)
+        .ok_or_else(|| anyhow!("Column {} exceeds the line {:?}", column, line))
 }
 
 use std::convert::{From, TryInto};
@@ -149,4 +204,35 @@ mod tests {
         assert_eq!(&TEXT[range], "you!!");
         assert_eq!(span, (0usize, 3..8).try_into().unwrap());
     }
+
+    #[test]
+    fn line_column() {
+        // `ü` and `ß` are two bytes each, `ö` too
+        const LINE: &str = "Grüße aus Köln, wo Kölsch fließt.";
+        let start = LINE.find("Kölsch").unwrap();
+        // 1-indexed columns in characters
+        let span = Span::from_line_column((3, 20), (3, 25)).unwrap();
+        assert_eq!(span.start.column, 19);
+        assert_eq!(span.to_line_column(), ((3, 20), (3, 25)));
+        let range = span.byte_range(LINE).unwrap();
+        assert_eq!(range, start..start + "Kölsch".len());
+        assert_eq!(&LINE[range], "Kölsch");
+
+        // a single multibyte character and the one following it
+        let span = Span::from_line_column((1, 31), (1, 31)).unwrap();
+        assert_eq!(&LINE[span.byte_range(LINE).unwrap()], "ß");
+        let span = Span::from_line_column((1, 32), (1, 32)).unwrap();
+        assert_eq!(&LINE[span.byte_range(LINE).unwrap()], "t");
+        let span = Span::from_line_column((1, 3), (1, 3)).unwrap();
+        assert_eq!(&LINE[span.byte_range(LINE).unwrap()], "ü");
+
+        // out of bounds or invalid
+        let span = Span::from_line_column((1, 30), (1, 40)).unwrap();
+        assert!(span.byte_range(LINE).is_err());
+        assert!(Span::from_line_column((0, 1), (1, 1)).is_err());
+        assert!(Span::from_line_column((1, 0), (1, 1)).is_err());
+        assert!(Span::from_line_column((2, 1), (1, 5)).is_err());
+        let span = Span::from_line_column((1, 1), (2, 1)).unwrap();
+        assert!(span.byte_range(LINE).is_err());
+    }
 }
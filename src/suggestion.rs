@@ -18,13 +18,18 @@ use crate::TrimmedLiteralRef;
 
 use enumflags2::BitFlags;
 use log::error;
+use serde::{Deserialize, Serialize};
 
 /// Bitflag of available checkers by compilation / configuration.
-#[derive(Debug, Clone, Copy, BitFlags, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Copy, BitFlags, Eq, PartialEq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
 #[repr(u8)]
 pub enum Detector {
     Hunspell = 0b0001,
     LanguageTool = 0b0010,
+    Nlprule = 0b0100,
+    Repetition = 0b1000,
+    Wordlist = 0b1_0000,
 }
 
 // impl
@@ -33,16 +38,51 @@ pub enum Detector {
 //     match detector {
 //         Detector::Hunspell => cfg!(feature="hunspell"),
 //         Detector::LanguageTool => cfg!(feature="languagetool"),
+//         Detector::Nlprule => cfg!(feature="nlprule"),
+//         Detector::Repetition => true,
+//         Detector::Wordlist => true,
 //     }
 // }
 
+/// How severe a finding is, ordered from least to most severe.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Detector {
+    /// Misspellings are errors, grammar and style issues are warnings.
+    pub fn severity(&self) -> Severity {
+        match self {
+            Self::Hunspell | Self::Wordlist => Severity::Error,
+            Self::LanguageTool | Self::Nlprule | Self::Repetition => Severity::Warning,
+        }
+    }
+}
+
 use std::fmt;
 
+impl fmt::Display for Severity {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(match self {
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Error => "error",
+        })
+    }
+}
+
 impl fmt::Display for Detector {
     fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
         formatter.write_str(match self {
             Self::LanguageTool => "LanguageTool",
             Self::Hunspell => "Hunspell",
+            Self::Nlprule => "Nlprule",
+            Self::Repetition => "Repetition",
+            Self::Wordlist => "Wordlist",
         })
     }
 }
@@ -52,6 +92,8 @@ impl fmt::Display for Detector {
 pub struct Suggestion<'s> {
     /// Which checker suggested the change.
     pub detector: Detector,
+    /// How severe the finding is, usually the one of the `detector`.
+    pub severity: Severity,
     /// Reference to the file location the `span` and `literal` relate to.
     pub path: PathBuf,
     /// Literal we are referencing.
@@ -64,12 +106,153 @@ pub struct Suggestion<'s> {
     pub description: Option<String>,
 }
 
+/// Number of the leading `replacements` shown inline, such that the list
+/// fits within `width` characters including the ellipsis for the remaining
+/// ones. At least one is always shown, all of them without a `width`.
+fn fit_replacements(replacements: &[String], width: Option<usize>) -> usize {
+    let n = replacements.len();
+    let width = if let Some(width) = width {
+        width
+    } else {
+        return n;
+    };
+    let lengths = replacements
+        .iter()
+        .map(|replacement| replacement.chars().count())
+        .collect::<Vec<_>>();
+    // `a`, `a or b` respectively `a, b, or c`
+    let full = lengths.iter().sum::<usize>()
+        + match n {
+            0 | 1 => 0,
+            2 => 4,
+            n => 2 * (n - 1) + 3,
+        };
+    if full <= width {
+        return n;
+    }
+    (1..n)
+        .rev()
+        .find(|&shown| {
+            // `a, b, …and 3 more`
+            let more = format!(", …and {} more", n - shown).chars().count();
+            lengths[..shown].iter().sum::<usize>() + 2 * (shown - 1) + more <= width
+        })
+        .unwrap_or(1)
+}
+
+/// Number of columns a tab advances to the next tab stop, unless configured otherwise.
+pub const DEFAULT_TAB_WIDTH: usize = 4;
+
+/// Expand the tabs of `line` to spaces, up to the next multiple of `tab_width`.
+///
+/// Returns the expanded line, the display column of the character at
+/// `first` and the display width of the characters `first..=last`. Columns
+/// past the end of the line count as one each.
+fn underline(line: &str, first: usize, last: usize, tab_width: usize) -> (String, usize, usize) {
+    let tab_width = tab_width.max(1);
+    let mut expanded = String::with_capacity(line.len() + 16);
+    let mut offset = 0usize;
+    let mut size = 0usize;
+    let mut count = 0usize;
+    for (idx, c) in line.chars().enumerate() {
+        count += 1;
+        let column = offset + size;
+        let width = if c == '\t' {
+            tab_width - column % tab_width
+        } else {
+            1
+        };
+        if c == '\t' {
+            expanded.extend(std::iter::repeat(' ').take(width));
+        } else {
+            expanded.push(c);
+        }
+        if idx < first {
+            offset += width;
+        } else if idx <= last {
+            size += width;
+        }
+    }
+    offset += first.saturating_sub(count);
+    size += (last + 1).saturating_sub(first.max(count));
+    (expanded, offset, size)
+}
+
+/// A suggestion as printed for the user, with optional source context
+/// and width limit.
+pub struct Diagnostic<'a, 's> {
+    suggestion: &'a Suggestion<'s>,
+    width: Option<usize>,
+    source: Option<&'a str>,
+    tab_width: usize,
+}
+
+impl<'a, 's> Diagnostic<'a, 's> {
+    /// Limit the inline list of replacements to `width` columns, the ones
+    /// which do not fit are only counted.
+    pub fn width(mut self, width: usize) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    /// Show the file's source `line` the suggestion starts in, instead of
+    /// the literal content, with the span underlined.
+    pub fn source(mut self, line: &'a str) -> Self {
+        self.source = Some(line);
+        self
+    }
+
+    /// Expand tabs to the next multiple of `tab_width` columns, so the
+    /// underline lines up with the text.
+    pub fn tab_width(mut self, tab_width: usize) -> Self {
+        self.tab_width = tab_width;
+        self
+    }
+}
+
+impl<'s> Suggestion<'s> {
+    /// Prepare for printing with additional options.
+    pub fn diagnostic(&self) -> Diagnostic<'_, 's> {
+        Diagnostic {
+            suggestion: self,
+            width: None,
+            source: None,
+            tab_width: DEFAULT_TAB_WIDTH,
+        }
+    }
+}
+
+impl<'a, 's> fmt::Display for Diagnostic<'a, 's> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.suggestion
+            .render(formatter, self.width, self.source, self.tab_width)
+    }
+}
+
 impl<'s> fmt::Display for Suggestion<'s> {
     fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.render(formatter, None, None, DEFAULT_TAB_WIDTH)
+    }
+}
+
+impl<'s> Suggestion<'s> {
+    fn render(
+        &self,
+        formatter: &mut fmt::Formatter<'_>,
+        width: Option<usize>,
+        source: Option<&str>,
+        tab_width: usize,
+    ) -> fmt::Result {
         use console::Style;
+        use fmt::Display;
 
         let highlight = Style::new().bold().white();
         let error = Style::new().bold().red();
+        let level = match self.severity {
+            Severity::Error => error.clone(),
+            Severity::Warning => Style::new().bold().yellow(),
+            Severity::Info => Style::new().bold().cyan(),
+        };
         let arrow_marker = Style::new().blue();
         let context_marker = Style::new().bold().blue();
         let fix = Style::new().green();
@@ -78,7 +261,7 @@ impl<'s> fmt::Display for Suggestion<'s> {
         let line_number_digit_count = self.span.start.line.to_string().len();
         let indent = 3 + line_number_digit_count;
 
-        error.apply_to("error").fmt(formatter)?;
+        level.apply_to(self.severity).fmt(formatter)?;
         highlight
             .apply_to(format!(": spellcheck({})", &self.detector))
             .fmt(formatter)?;
@@ -106,101 +289,131 @@ impl<'s> fmt::Display for Suggestion<'s> {
             ))
             .fmt(formatter)?;
 
-        writeln!(formatter, " {}", self.literal.as_str())?;
-
-        // underline the relevant part with ^^^^^
-
-        // @todo this needs some more thought once multiline comments pop up
-        let mut marker_size = if self.span.end.line == self.span.start.line {
-            // column bounds are inclusive, so for a correct length we need to add + 1
-            self.span.end.column.saturating_sub(self.span.start.column) + 1
-        } else {
-            self.literal.len().saturating_sub(self.span.start.column)
-        };
-
-        use crate::literalset::Range;
-
-        let literal_span: Span = Span::from(self.literal.as_ref().literal.span());
-        let marker_range_relative: Range = self.span.relative_to(literal_span).expect("Must be ok");
-
-        // if the offset starts from 0, we still want to continue if the length
-        // of the marker is at least length 1
-        let offset = if self.literal.pre() <= marker_range_relative.start {
-            marker_range_relative.start - self.literal.pre()
-        } else {
-            error!("Reducing marker length! Please report a BUG!");
-            // reduce the marker size
-            marker_size -= marker_range_relative.start;
-            marker_size -= self.literal.pre();
-            0
-        };
-
-        if marker_size > 0 {
+        if let Some(line) = source {
+            // the span columns are relative to the literal
+            let variant = self.literal.variant();
+            let first = variant.file_column(self.span.start.column);
+            let last = if self.span.end.line == self.span.start.line {
+                variant.file_column(self.span.end.column)
+            } else {
+                line.chars().count().saturating_sub(1)
+            };
+            let (expanded, offset, size) = underline(line, first, last, tab_width);
+            writeln!(formatter, " {}", expanded)?;
             context_marker
                 .apply_to(format!("{:>width$}", "|", width = indent))
                 .fmt(formatter)?;
             help.apply_to(format!(" {:>offset$}", "", offset = offset))
                 .fmt(formatter)?;
-            help.apply_to(format!("{:^>size$}", "", size = marker_size))
+            help.apply_to(format!("{:^>size$}", "", size = size.max(1)))
                 .fmt(formatter)?;
             formatter.write_str("\n")?;
-            log::trace!(
-                "marker_size={} [{}|{}|{}] literal {{ {:?} .. {:?} }} >> {:?} <<",
-                marker_size,
-                self.literal.pre(),
-                self.literal.len(),
-                self.literal.post(),
-                self.span.start,
-                self.span.end,
-                self,
-            );
         } else {
-            log::warn!(
-                "marker_size={} [{}|{}|{}] literal {{ {:?} .. {:?} }} >> {:?} <<",
-                marker_size,
-                self.literal.pre(),
-                self.literal.len(),
-                self.literal.post(),
-                self.span.start,
-                self.span.end,
-                self,
-            );
+            use crate::literalset::Range;
+
+            let marker_range_relative: Range = self
+                .literal
+                .as_ref()
+                .untrimmed_range(&self.span)
+                .expect("Must be ok");
+
+            // the span covers the literal as written, not the unescaped content,
+            // which may span multiple lines, so show the line the span starts in
+            let untrimmed = self.literal.as_ref().as_untrimmed_str();
+            let written_end = self.literal.pre() + self.literal.len();
+            let line_start = untrimmed[..marker_range_relative.start]
+                .rfind('\n')
+                .map_or(self.literal.pre(), |newline| newline + 1);
+            let literal = untrimmed[line_start.min(written_end)..written_end]
+                .split('\n')
+                .next()
+                .unwrap_or_default();
+            let (expanded, _, _) = underline(literal, 0, 0, tab_width);
+            writeln!(formatter, " {}", expanded)?;
+
+            // underline the relevant part with ^^^^^
+
+            // @todo this needs some more thought once multiline comments pop up
+            let mut marker_size = if self.span.end.line == self.span.start.line {
+                // column bounds are inclusive, so for a correct length we need to add + 1
+                self.span.end.column.saturating_sub(self.span.start.column) + 1
+            } else {
+                self.literal.len().saturating_sub(self.span.start.column)
+            };
+
+            // if the offset starts from 0, we still want to continue if the length
+            // of the marker is at least length 1
+            let offset = if line_start <= marker_range_relative.start {
+                marker_range_relative.start - line_start
+            } else {
+                error!("Reducing marker length! Please report a BUG!");
+                // reduce the marker size
+                marker_size -= marker_range_relative.start;
+                marker_size -= self.literal.pre();
+                0
+            };
+
+            if marker_size > 0 {
+                // in display columns, with the tabs expanded
+                let (_, offset, marker_size) =
+                    underline(literal, offset, offset + marker_size - 1, tab_width);
+                context_marker
+                    .apply_to(format!("{:>width$}", "|", width = indent))
+                    .fmt(formatter)?;
+                help.apply_to(format!(" {:>offset$}", "", offset = offset))
+                    .fmt(formatter)?;
+                help.apply_to(format!("{:^>size$}", "", size = marker_size))
+                    .fmt(formatter)?;
+                formatter.write_str("\n")?;
+                log::trace!(
+                    "marker_size={} [{}|{}|{}] literal {{ {:?} .. {:?} }} >> {:?} <<",
+                    marker_size,
+                    self.literal.pre(),
+                    self.literal.len(),
+                    self.literal.post(),
+                    self.span.start,
+                    self.span.end,
+                    self,
+                );
+            } else {
+                log::warn!(
+                    "marker_size={} [{}|{}|{}] literal {{ {:?} .. {:?} }} >> {:?} <<",
+                    marker_size,
+                    self.literal.pre(),
+                    self.literal.len(),
+                    self.literal.post(),
+                    self.span.start,
+                    self.span.end,
+                    self,
+                );
+            }
         }
 
         context_marker
             .apply_to(format!("{:>width$}", "|", width = indent))
             .fmt(formatter)?;
 
-        let replacement = match self.replacements.len() {
-            0 => String::new(),
-            1 => format!(" - {}", fix.apply_to(&self.replacements[0])),
-            2 => format!(
-                " - {} or {}",
-                fix.apply_to(&self.replacements[0]).to_string(),
-                fix.apply_to(&self.replacements[1]).to_string()
-            ),
-            n if (n < 7) => {
-                let last = fix.apply_to(&self.replacements[n - 1]).to_string();
-                let joined = self.replacements[..n - 1]
-                    .iter()
-                    .map(|x| fix.apply_to(x).to_string())
-                    .collect::<Vec<String>>()
-                    .as_slice()
-                    .join(", ");
-                format!(" - {}, or {}", joined, last)
-            }
-            _n => {
-                let joined = self.replacements[..=6]
-                    .iter()
-                    .map(|x| fix.apply_to(x).to_string())
-                    .collect::<Vec<String>>()
-                    .as_slice()
-                    .join(", ");
-
-                let remaining = self.replacements.len() - 6;
-                let remaining = fix.apply_to(format!("{}", remaining)).to_string();
-                format!(" - {}, or one of {} others", joined, remaining)
+        let n = self.replacements.len();
+        let shown = fit_replacements(
+            &self.replacements,
+            width.map(|width| width.saturating_sub(indent + 3)),
+        );
+        let styled = self.replacements[..shown]
+            .iter()
+            .map(|x| fix.apply_to(x).to_string())
+            .collect::<Vec<String>>();
+        let replacement = match (n, styled.as_slice()) {
+            (0, _) => String::new(),
+            (1, [single]) => format!(" - {}", single),
+            (2, [first, second]) => format!(" - {} or {}", first, second),
+            (n, [joined @ .., last]) if n == shown => {
+                format!(" - {}, or {}", joined.join(", "), last)
             }
+            (n, shown) => format!(
+                " - {}, …and {} more",
+                shown.join(", "),
+                fix.apply_to(n - shown.len())
+            ),
         };
 
         error.apply_to(replacement).fmt(formatter)?;
@@ -229,14 +442,27 @@ impl<'s> fmt::Debug for Suggestion<'s> {
 
         let printable = TrimmedLiteralDisplay::from((
             self.literal,
-            self.span
-                .relative_to(self.literal.as_ref().literal.span())
-                .expect("Must be on the same line"),
+            self.literal
+                .as_ref()
+                .untrimmed_range(&self.span)
+                .expect("Must be within the literal"),
         ));
         write!(formatter, "({}, {:?})", &printable, printable.1)
     }
 }
 
+/// Description of `suggestion` annotated with its detector.
+fn provenance(suggestion: &Suggestion) -> String {
+    format!(
+        "{} ({})",
+        suggestion
+            .description
+            .as_deref()
+            .unwrap_or("Possible spelling mistake found."),
+        suggestion.detector
+    )
+}
+
 /// A set of suggestions across multiple files, clustered per file
 #[derive(Debug, Clone)]
 pub struct SuggestionSet<'s> {
@@ -330,6 +556,105 @@ impl<'s> SuggestionSet<'s> {
         })
     }
 
+    /// Sort the suggestions of every file by their span.
+    ///
+    /// The sort is stable, so suggestions of different detectors for the
+    /// same span retain their relative order.
+    pub fn sort(&mut self) {
+        self.per_file.values_mut().for_each(|suggestions| {
+            suggestions.sort_by_key(|suggestion| {
+                let span = suggestion.span;
+                (
+                    span.start.line,
+                    span.start.column,
+                    span.end.line,
+                    span.end.column,
+                )
+            })
+        });
+    }
+
+    /// Merge suggestions of different detectors for the same span into one.
+    ///
+    /// Distinct replacements are kept in order of appearance, the descriptions
+    /// are annotated with the detector they stem from. Suggestions which only
+    /// partially overlap are kept as they are, since the replacements of one
+    /// can not be applied to the span of the other. Expects sorted suggestions.
+    pub fn merge_same_span(&mut self) {
+        self.per_file.values_mut().for_each(|suggestions| {
+            let mut merged: Vec<Suggestion<'s>> = Vec::with_capacity(suggestions.len());
+            // if the description of the last merged suggestion carries its detector already
+            let mut annotated = false;
+            for suggestion in suggestions.drain(..) {
+                let kept = match merged.last_mut() {
+                    Some(last)
+                        if last.span == suggestion.span && last.literal == suggestion.literal =>
+                    {
+                        last
+                    }
+                    _ => {
+                        annotated = false;
+                        merged.push(suggestion);
+                        continue;
+                    }
+                };
+                if kept.detector != suggestion.detector || annotated {
+                    if !annotated {
+                        kept.description = Some(provenance(kept));
+                        annotated = true;
+                    }
+                    kept.description = Some(format!(
+                        "{} {}",
+                        kept.description.take().unwrap_or_default(),
+                        provenance(&suggestion)
+                    ));
+                }
+                for replacement in suggestion.replacements {
+                    if !kept.replacements.contains(&replacement) {
+                        kept.replacements.push(replacement);
+                    }
+                }
+            }
+            *suggestions = merged;
+        });
+    }
+
+    /// Only retain the suggestions for which `keep` returns `true`.
+    pub fn retain<F>(&mut self, mut keep: F)
+    where
+        F: FnMut(&Suggestion<'s>) -> bool,
+    {
+        self.per_file
+            .values_mut()
+            .for_each(|suggestions| suggestions.retain(|suggestion| keep(suggestion)));
+    }
+
+    /// Order the replacements of every suggestion by their distance to the
+    /// flagged text, so the closest one comes first. Equidistant ones retain
+    /// the order of the checker.
+    pub fn rank_replacements(&mut self) {
+        use crate::checker::ranking::damerau_distance;
+
+        self.per_file
+            .values_mut()
+            .flat_map(|suggestions| suggestions.iter_mut())
+            .for_each(|suggestion| {
+                if let Some(original) = crate::report::covered_text(suggestion) {
+                    suggestion
+                        .replacements
+                        .sort_by_cached_key(|replacement| damerau_distance(&original, replacement));
+                }
+            });
+    }
+
+    /// Only retain the first `max` replacements of every suggestion.
+    pub fn truncate_replacements(&mut self, max: usize) {
+        self.per_file
+            .values_mut()
+            .flat_map(|suggestions| suggestions.iter_mut())
+            .for_each(|suggestion| suggestion.replacements.truncate(max));
+    }
+
     /// Obtain the number of items in the set
     #[inline]
     pub fn len(&self) -> usize {
@@ -0,0 +1,86 @@
+//! Fixtures shared by the tests of all modules.
+
+use crate::{Config, Detector, Documentation, LineColumn, Span, Suggestion, SuggestionSet};
+
+use std::fs;
+use std::path::PathBuf;
+
+pub(crate) const SOURCE: &str = r#"/// A tpyo here & there.
+struct X;
+"#;
+
+/// Documentation of two files, the first one containing `SOURCE`, the second one clean.
+pub(crate) fn documentation() -> Documentation {
+    let mut docs =
+        Documentation::load_from_str("src/lib.rs", SOURCE, &Config::default()).expect("Must parse");
+    docs.join(
+        Documentation::load_from_str("src/clean.rs", "/// Clean.\nstruct Y;", &Config::default())
+            .expect("Must parse"),
+    );
+    docs
+}
+
+/// A mixed result set of `documentation()` with two suggestions in
+/// the first file and none in the second one.
+pub(crate) fn suggestion_set<'s>(docs: &'s Documentation) -> SuggestionSet<'s> {
+    let mut set = SuggestionSet::new();
+    let (path, literal_sets) = docs.iter().next().expect("Must contain a file");
+    let literal = literal_sets[0].literals()[0];
+    let span = |start: usize, end: usize| Span {
+        start: LineColumn {
+            line: 1,
+            column: start,
+        },
+        end: LineColumn {
+            line: 1,
+            column: end,
+        },
+    };
+    set.add(
+        path.clone(),
+        Suggestion {
+            detector: Detector::Hunspell,
+            severity: Detector::Hunspell.severity(),
+            path: path.clone(),
+            literal: literal.into(),
+            span: span(4, 7),
+            replacements: vec!["typo".to_owned(), "type".to_owned()],
+            description: Some("Possible spelling mistake found.".to_owned()),
+        },
+    );
+    set.add(
+        path.clone(),
+        Suggestion {
+            detector: Detector::LanguageTool,
+            severity: Detector::LanguageTool.severity(),
+            path: path.clone(),
+            literal: literal.into(),
+            span: span(14, 14),
+            replacements: vec!["and".to_owned()],
+            description: Some("Use \"and\" instead of <&>.".to_owned()),
+        },
+    );
+    let (path, _) = docs.iter().nth(1).expect("Must contain a second file");
+    set.extend(path.clone(), Vec::new());
+    set
+}
+
+/// The first file of either a `Documentation` or a `SuggestionSet`.
+pub(crate) fn first<'x, T>(mut files: impl Iterator<Item = (&'x PathBuf, T)>) -> (&'x PathBuf, T) {
+    files.next().expect("Must contain a file")
+}
+
+/// Check `docs` with all detectors enabled in `config`, without any cache.
+pub(crate) fn check<'a>(docs: &'a Documentation, config: &Config) -> SuggestionSet<'a> {
+    crate::checker::check(docs, config, None, &mut |_path| ()).expect("Must check")
+}
+
+/// Create an empty directory for tests which operate on the file system,
+/// which must be removed again by the test.
+pub(crate) fn scratch_dir(name: &str) -> PathBuf {
+    let dir =
+        std::env::temp_dir().join(format!("cargo-spellcheck-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).expect("Must be able to create a scratch dir");
+    dir
+}
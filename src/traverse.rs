@@ -12,6 +12,7 @@ use log::{trace, warn};
 use std::path::{Path, PathBuf};
 
 use anyhow::{anyhow, Error, Result};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
 
 fn cwd() -> Result<PathBuf> {
     std::env::current_dir().map_err(|_e| anyhow::anyhow!("Missing cwd!"))
@@ -104,13 +105,15 @@ impl Iterator for TraverseModulesIter {
     }
 }
 
-pub(crate) fn traverse(path: &Path) -> Result<impl Iterator<Item = Documentation>> {
+pub(crate) fn traverse<'c>(
+    path: &Path,
+    config: &'c Config,
+) -> Result<impl Iterator<Item = Documentation> + 'c> {
     let it = TraverseModulesIter::new(path)?
-        .filter_map(|path: PathBuf| -> Option<Documentation> {
-            fs::read_to_string(&path)
-                .ok()
-                .and_then(|content: String| syn::parse_str(&content).ok())
-                .map(|stream| Documentation::from((path, stream)))
+        .filter_map(move |path: PathBuf| -> Option<Documentation> {
+            fs::read_to_string(&path).ok().and_then(|content: String| {
+                Documentation::load_from_str(&path, content.as_str(), config).ok()
+            })
         })
         .filter(|documentation| !documentation.is_empty());
     Ok(it)
@@ -229,6 +232,8 @@ fn extract_modules_from_file<P: AsRef<Path>>(path: P) -> Result<Vec<PathBuf>> {
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum CheckItem {
     Markdown(PathBuf),
+    ReStructuredText(PathBuf),
+    Text(PathBuf),
     Source(PathBuf),
     ManifestDescription(String),
 }
@@ -283,13 +288,198 @@ fn extract_products<P: AsRef<Path>>(manifest_dir: P) -> Result<Vec<CheckItem>> {
     Ok(items)
 }
 
-/// Execute execute execute.
-pub(crate) fn collect(
+/// Load a standalone markdown or plain text document.
+fn load_document(path: &Path, variant: CommentVariant) -> Result<Documentation> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read {}", path.display()).context(e))?;
+    Documentation::load_lines_from_str(path, content.as_str(), variant)
+}
+
+/// Load `content` as a document of the given `kind`, such as input read
+/// from stdin, which has no extension to determine the kind from.
+pub fn load_str(
+    path: &Path,
+    content: &str,
+    kind: DocumentKind,
+    config: &Config,
+) -> Result<Documentation> {
+    match kind {
+        DocumentKind::Rust => Documentation::load_from_str(path, content, config),
+        DocumentKind::Markdown => {
+            Documentation::load_lines_from_str(path, content, CommentVariant::CommonMark)
+        }
+        DocumentKind::ReStructuredText => {
+            Documentation::load_lines_from_str(path, content, CommentVariant::ReStructuredText)
+        }
+        DocumentKind::Text => {
+            Documentation::load_lines_from_str(path, content, CommentVariant::PlainText)
+        }
+    }
+}
+
+/// The line `git commit --verbose` places above the diff, everything below
+/// is discarded by git.
+const COMMIT_MESSAGE_SCISSORS: &str = "# ------------------------ >8 ------------------------";
+
+/// Blank out everything of a commit message file which does not end up in
+/// the commit, the comment lines and the diff section.
+///
+/// Lines are kept in place, so the line numbers of the findings match the file.
+fn commit_message(content: &str) -> String {
+    let mut message = String::with_capacity(content.len());
+    for line in content.lines() {
+        if line == COMMIT_MESSAGE_SCISSORS || line.starts_with("diff --git ") {
+            break;
+        }
+        if !line.starts_with('#') {
+            message.push_str(line);
+        }
+        message.push('\n');
+    }
+    message
+}
+
+/// Load the commit message file at `path`, as passed to a `commit-msg` hook,
+/// which is checked as plain text.
+pub fn load_commit_message(path: &Path) -> Result<Documentation> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read {}", path.display()).context(e))?;
+    Documentation::load_lines_from_str(
+        path,
+        commit_message(content.as_str()).as_str(),
+        CommentVariant::PlainText,
+    )
+}
+
+/// Include and exclude globs selecting which of the discovered files are checked.
+///
+/// A glob matches either the file name or the path relative to the current
+/// directory. Excluding takes precedence over including.
+#[derive(Debug, Clone)]
+pub struct FileFilter {
+    cwd: PathBuf,
+    /// `None` includes all files.
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+}
+
+impl FileFilter {
+    pub fn new(cwd: PathBuf, include: &[String], exclude: &[String]) -> Result<Self> {
+        let build = |globs: &[String]| -> Result<GlobSet> {
+            let mut set = GlobSetBuilder::new();
+            for glob in globs {
+                set.add(
+                    GlobBuilder::new(glob)
+                        .literal_separator(true)
+                        .build()
+                        .map_err(|e| anyhow!("Invalid glob {}", glob).context(e))?,
+                );
+            }
+            set.build()
+                .map_err(|e| anyhow!("Failed to compile globs").context(e))
+        };
+        Ok(Self {
+            cwd,
+            include: if include.is_empty() {
+                None
+            } else {
+                Some(build(include)?)
+            },
+            exclude: build(exclude)?,
+        })
+    }
+
+    fn matches(&self, set: &GlobSet, path: &Path) -> bool {
+        set.is_match(path.strip_prefix(&self.cwd).unwrap_or(path))
+            || path.file_name().map_or(false, |name| set.is_match(name))
+    }
+
+    /// If the file at `path` is to be checked.
+    pub fn is_selected(&self, path: &Path) -> bool {
+        let included = self
+            .include
+            .as_ref()
+            .map_or(true, |include| self.matches(include, path));
+        let selected = included && !self.matches(&self.exclude, path);
+        if !selected {
+            trace!("Skipping {} as configured", path.display());
+        }
+        selected
+    }
+
+    fn is_item_selected(&self, item: &CheckItem) -> bool {
+        match item {
+            CheckItem::Markdown(path)
+            | CheckItem::ReStructuredText(path)
+            | CheckItem::Text(path)
+            | CheckItem::Source(path) => self.is_selected(path),
+            CheckItem::ManifestDescription(_) => true,
+        }
+    }
+}
+
+/// Find all files within `dir`, respecting `.gitignore` and `.ignore` files
+/// unless `no_ignore` is set. Hidden files are skipped either way.
+///
+/// With `parallel`, the directories are walked by multiple threads. The
+/// files are sorted either way, so the result does not depend on the order
+/// of the walk.
+fn discover(dir: &Path, parallel: bool, no_ignore: bool) -> Result<Vec<PathBuf>> {
+    let mut walker = ignore::WalkBuilder::new(dir);
+    walker
+        .parents(!no_ignore)
+        .ignore(!no_ignore)
+        .git_ignore(!no_ignore)
+        .git_global(!no_ignore)
+        .git_exclude(!no_ignore);
+    let mut files = if parallel {
+        let (tx, rx) = std::sync::mpsc::channel::<PathBuf>();
+        walker.build_parallel().run(|| {
+            let tx = tx.clone();
+            Box::new(move |entry| {
+                match entry {
+                    Ok(entry) if entry.file_type().map_or(false, |ft| ft.is_file()) => {
+                        let _ = tx.send(entry.into_path());
+                    }
+                    Ok(_) => {}
+                    Err(e) => warn!("Failed to walk {}: {}", dir.display(), e),
+                }
+                ignore::WalkState::Continue
+            })
+        });
+        drop(tx);
+        rx.into_iter().collect::<Vec<_>>()
+    } else {
+        walker
+            .build()
+            .filter_map(|entry| match entry {
+                Ok(entry) => Some(entry),
+                Err(e) => {
+                    warn!("Failed to walk {}: {}", dir.display(), e);
+                    None
+                }
+            })
+            .filter(|entry| entry.file_type().map_or(false, |ft| ft.is_file()))
+            .map(|entry| entry.into_path())
+            .collect::<Vec<_>>()
+    };
+    files.sort();
+    trace!("Discovered {} files in {}", files.len(), dir.display());
+    Ok(files)
+}
+
+/// Load the documentation of all `paths`, the whole project if none are given.
+///
+/// Files found within directories, listed in manifests or declared as modules
+/// are only checked if selected by the `include` and `exclude` globs, the
+/// explicitly given `paths` always are.
+pub fn collect(
     mut paths: Vec<PathBuf>,
     mut recurse: bool,
-    _config: &Config,
+    config: &Config,
 ) -> Result<Documentation> {
     let cwd = cwd()?;
+    let filter = FileFilter::new(cwd.clone(), &config.include, &config.exclude)?;
     // if there are no arguments, pretend to be told to check the whole project
     if paths.is_empty() {
         // @todo also traverse parent dirs
@@ -303,6 +493,9 @@ pub(crate) fn collect(
         Missing(PathBuf),
         Source(PathBuf),
         Markdown(PathBuf),
+        ReStructuredText(PathBuf),
+        Text(PathBuf),
+        Directory(PathBuf),
     }
 
     // convert all `Cargo.toml` manifest files to their respective product files
@@ -317,9 +510,22 @@ pub(crate) fn collect(
             };
             if let Ok(meta) = path.metadata() {
                 if meta.is_file() {
+                    match config.document_kind(&path) {
+                        Some(DocumentKind::Rust) => return Extraction::Source(path),
+                        Some(DocumentKind::Markdown) => return Extraction::Markdown(path),
+                        Some(DocumentKind::Text) => return Extraction::Text(path),
+                        Some(DocumentKind::ReStructuredText) => {
+                            return Extraction::ReStructuredText(path)
+                        }
+                        None => {}
+                    }
                     match path.file_name().map(|x| x.to_str()).flatten() {
                         Some(file_name) if file_name == "Cargo.toml" => Extraction::Manifest(path),
                         Some(file_name) if file_name.ends_with(".md") => Extraction::Markdown(path),
+                        Some(file_name) if file_name.ends_with(".rst") => {
+                            Extraction::ReStructuredText(path)
+                        }
+                        Some(file_name) if file_name.ends_with(".txt") => Extraction::Text(path),
                         _ => Extraction::Source(path),
                     }
                 } else if meta.is_dir() {
@@ -327,8 +533,7 @@ pub(crate) fn collect(
                     if cargo_toml.is_file() {
                         Extraction::Manifest(cargo_toml)
                     } else {
-                        // @todo should we just collect all .rs files here instead?
-                        Extraction::Missing(cargo_toml)
+                        Extraction::Directory(path)
                     }
                 } else {
                     Extraction::Missing(path)
@@ -341,7 +546,11 @@ pub(crate) fn collect(
             match tagged_path {
                 Extraction::Manifest(ref cargo_toml_path) => {
                     let manifest_list = extract_products(cargo_toml_path.parent().unwrap())?;
-                    acc.extend(manifest_list);
+                    acc.extend(
+                        manifest_list
+                            .into_iter()
+                            .filter(|item| filter.is_item_selected(item)),
+                    );
                 }
                 Extraction::Missing(ref missing_path) => warn!(
                     "File passed as argument or listed in Cargo.toml manifest does not exist: {}",
@@ -349,6 +558,31 @@ pub(crate) fn collect(
                 ),
                 Extraction::Source(path) => acc.push(CheckItem::Source(path)),
                 Extraction::Markdown(path) => acc.push(CheckItem::Markdown(path)),
+                Extraction::Text(path) => acc.push(CheckItem::Text(path)),
+                Extraction::ReStructuredText(path) => acc.push(CheckItem::ReStructuredText(path)),
+                Extraction::Directory(dir) => {
+                    for path in discover(&dir, config.parallel_discovery, config.no_ignore)?
+                        .into_iter()
+                        .filter(|path| filter.is_selected(path))
+                    {
+                        let item = match config.document_kind(&path) {
+                            Some(DocumentKind::Rust) => CheckItem::Source(path),
+                            Some(DocumentKind::Markdown) => CheckItem::Markdown(path),
+                            Some(DocumentKind::Text) => CheckItem::Text(path),
+                            Some(DocumentKind::ReStructuredText) => {
+                                CheckItem::ReStructuredText(path)
+                            }
+                            None => match path.extension().map(|x| x.to_str()).flatten() {
+                                Some("rs") => CheckItem::Source(path),
+                                Some("md") => CheckItem::Markdown(path),
+                                Some("rst") => CheckItem::ReStructuredText(path),
+                                Some("txt") => CheckItem::Text(path),
+                                _ => continue,
+                            },
+                        };
+                        acc.push(item);
+                    }
+                }
             }
             Ok(acc)
         })?;
@@ -360,12 +594,27 @@ pub(crate) fn collect(
         let mut dq = std::collections::VecDeque::<CheckItem>::with_capacity(64);
         dq.extend(items.into_iter());
         while let Some(item) = dq.pop_front() {
-            if let CheckItem::Source(path) = item {
-                let modules = extract_modules_from_file(&path)?;
-                if path_collection.insert(CheckItem::Source(path.to_owned())) {
-                    dq.extend(modules.into_iter().map(CheckItem::Source));
-                } else {
-                    warn!("Already visited module");
+            match item {
+                CheckItem::Source(path) => {
+                    let modules = extract_modules_from_file(&path)?;
+                    if path_collection.insert(CheckItem::Source(path.to_owned())) {
+                        dq.extend(
+                            modules
+                                .into_iter()
+                                .filter(|module| filter.is_selected(module))
+                                .map(CheckItem::Source),
+                        );
+                    } else {
+                        warn!("Already visited module");
+                    }
+                }
+                item @ CheckItem::Markdown(_)
+                | item @ CheckItem::ReStructuredText(_)
+                | item @ CheckItem::Text(_) => {
+                    let _ = path_collection.insert(item);
+                }
+                CheckItem::ManifestDescription(_) => {
+                    // @todo generate Documentation structs from non-file sources
                 }
             }
         }
@@ -380,8 +629,20 @@ pub(crate) fn collect(
                     match item {
                         CheckItem::Source(path) => {
                             let content = fs::read_to_string(&path)?;
-                            let stream = syn::parse_str(&content)?;
-                            acc.push(Documentation::from((path, stream)));
+                            acc.push(Documentation::load_from_str(
+                                &path,
+                                content.as_str(),
+                                config,
+                            )?);
+                        }
+                        CheckItem::Markdown(path) => {
+                            acc.push(load_document(&path, CommentVariant::CommonMark)?);
+                        }
+                        CheckItem::Text(path) => {
+                            acc.push(load_document(&path, CommentVariant::PlainText)?);
+                        }
+                        CheckItem::ReStructuredText(path) => {
+                            acc.push(load_document(&path, CommentVariant::ReStructuredText)?);
                         }
                         _ => unimplemented!("Did not impl this just yet"),
                     }
@@ -397,7 +658,16 @@ pub(crate) fn collect(
                 |mut acc, item| {
                     match item {
                         CheckItem::Source(path) => {
-                            acc.extend(traverse(path)?);
+                            acc.extend(traverse(path, config)?);
+                        }
+                        CheckItem::Markdown(path) => {
+                            acc.push(load_document(path, CommentVariant::CommonMark)?);
+                        }
+                        CheckItem::Text(path) => {
+                            acc.push(load_document(path, CommentVariant::PlainText)?);
+                        }
+                        CheckItem::ReStructuredText(path) => {
+                            acc.push(load_document(path, CommentVariant::ReStructuredText)?);
                         }
                         _ => {
                             // @todo generate Documentation structs from non-file sources
@@ -455,6 +725,158 @@ mod tests {
         manifest_dir().join("demo")
     }
 
+    #[test]
+    fn document_kind_by_glob() {
+        let path = demo_dir().join("docs/intro.md.tpl");
+        let mut config = Config::default();
+        config
+            .document_kinds
+            .insert("*.md.tpl".to_owned(), DocumentKind::Markdown);
+
+        let docs = collect(vec![path.clone()], false, &config).expect("Must collect");
+        let (found, literal_sets) = docs.iter().next().expect("Must contain the template");
+        assert_eq!(found, &path);
+        let plain = literal_sets[0].erase_markdown();
+        assert_eq!(
+            plain.as_str(),
+            "Intro\n\nA template with some emphasized prose."
+        );
+
+        // the prose maps back to the location within the file
+        let start = plain.as_str().find("emphasized").unwrap();
+        let spans = plain.linear_range_to_spans(start..start + 10);
+        let (literal, span) = spans.first().expect("Must map to a literal");
+        assert_eq!(span.start.line, 3);
+        assert_eq!(literal.variant.file_column(span.start.column), 25);
+
+        // without the mapping, the template is attempted to be parsed as rust source
+        let docs = collect(vec![path], false, &Config::default()).expect("Must collect");
+        assert!(docs.is_empty());
+    }
+
+    #[test]
+    fn discover_parallel() {
+        let dir = crate::testcase::scratch_dir("discover");
+        for a in 0..4 {
+            for b in 0..4 {
+                let nested = dir.join(format!("a{}/b{}/c/d/e", a, b));
+                fs::create_dir_all(&nested).unwrap();
+                fs::write(nested.join("lib.rs"), "//! Doc\n").unwrap();
+                fs::write(nested.join("README.md"), "# Readme\n").unwrap();
+                fs::write(dir.join(format!("a{}/b{}/skipped.rs", a, b)), "").unwrap();
+            }
+        }
+        fs::write(dir.join(".ignore"), "skipped.rs\n").unwrap();
+
+        let serial = discover(&dir, false, false).expect("Serial walk works");
+        let parallel = discover(&dir, true, false).expect("Parallel walk works");
+        assert_eq!(serial.len(), 4 * 4 * 2);
+        assert!(serial.iter().all(|path| !path.ends_with("skipped.rs")));
+        assert_eq!(serial, parallel);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn gitignored() {
+        let dir = crate::testcase::scratch_dir("gitignore");
+        // only a `.git` directory makes `.gitignore` files apply
+        fs::create_dir_all(dir.join(".git")).unwrap();
+        fs::create_dir_all(dir.join("target/doc")).unwrap();
+        fs::write(dir.join(".gitignore"), "/target\n").unwrap();
+        fs::write(dir.join("README.md"), "# Readme\n\nSome prose.\n").unwrap();
+        fs::write(
+            dir.join("target/doc/generated.md"),
+            "# Generated\n\nSome prose.\n",
+        )
+        .unwrap();
+
+        let found = |config: &Config| {
+            let docs = collect(vec![dir.clone()], true, config).expect("Must collect");
+            let mut found = docs
+                .iter()
+                .map(|(origin, _)| origin.clone())
+                .collect::<Vec<_>>();
+            found.sort();
+            found
+        };
+
+        assert_eq!(found(&Config::default()), vec![dir.join("README.md")]);
+
+        let mut config = Config::default();
+        config.no_ignore = true;
+        assert_eq!(
+            found(&config),
+            vec![dir.join("README.md"), dir.join("target/doc/generated.md")]
+        );
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn file_filter() {
+        let globs = |globs: &[&str]| {
+            globs
+                .iter()
+                .map(|glob| glob.to_string())
+                .collect::<Vec<_>>()
+        };
+        let cwd = PathBuf::from("/project");
+
+        let all = FileFilter::new(cwd.clone(), &[], &[]).unwrap();
+        assert!(all.is_selected(Path::new("/project/src/lib.rs")));
+
+        let filter = FileFilter::new(
+            cwd.clone(),
+            &globs(&["src/**/*.rs", "*.md"]),
+            &globs(&["src/generated/**", "CHANGELOG.md"]),
+        )
+        .unwrap();
+        assert!(filter.is_selected(Path::new("/project/src/lib.rs")));
+        assert!(filter.is_selected(Path::new("/project/src/nested/mod.rs")));
+        assert!(filter.is_selected(Path::new("/project/docs/intro.md")));
+        // not included
+        assert!(!filter.is_selected(Path::new("/project/benches/bench.rs")));
+        // included, but excluded again
+        assert!(!filter.is_selected(Path::new("/project/src/generated/api.rs")));
+        assert!(!filter.is_selected(Path::new("/project/CHANGELOG.md")));
+
+        // a separator is never matched by a single `*`
+        let shallow = FileFilter::new(cwd.clone(), &globs(&["src/*.rs"]), &[]).unwrap();
+        assert!(shallow.is_selected(Path::new("/project/src/lib.rs")));
+        assert!(!shallow.is_selected(Path::new("/project/src/nested/mod.rs")));
+
+        assert!(FileFilter::new(cwd, &globs(&["src/[.rs"]), &[]).is_err());
+    }
+
+    #[test]
+    fn collect_filtered() {
+        let dir = crate::testcase::scratch_dir("filter");
+        fs::create_dir_all(dir.join("generated")).unwrap();
+        fs::write(dir.join("README.md"), "# Readme\n\nSome prose.\n").unwrap();
+        fs::write(dir.join("NOTES.md"), "# Notes\n\nSome prose.\n").unwrap();
+        fs::write(dir.join("lib.rs"), "//! Doc\n").unwrap();
+        fs::write(dir.join("generated/api.rs"), "//! Doc\n").unwrap();
+
+        let mut config = Config::default();
+        config.include = vec!["*.rs".to_owned(), "README.md".to_owned()];
+        config.exclude = vec!["**/generated/**".to_owned()];
+        let docs = collect(vec![dir.clone()], true, &config).expect("Must collect");
+        let mut found = docs
+            .iter()
+            .map(|(origin, _)| origin.clone())
+            .collect::<Vec<_>>();
+        found.sort();
+        assert_eq!(found, vec![dir.join("README.md"), dir.join("lib.rs")]);
+
+        // explicitly given files are always checked
+        let docs =
+            collect(vec![dir.join("generated/api.rs")], false, &config).expect("Must collect");
+        assert_eq!(docs.iter().count(), 1);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn traverse_main_rs() {
         let manifest_path = demo_dir().join("src/main.rs");
@@ -493,4 +915,49 @@ mod tests {
 
         assert_eq!(found.len(), expect.len());
     }
+
+    #[test]
+    fn commit_message_file() {
+        let dir = crate::testcase::scratch_dir("commit-msg");
+        fs::write(dir.join("deny.txt"), "teh = the\n").unwrap();
+        let path = dir.join("COMMIT_EDITMSG");
+        fs::write(
+            &path,
+            r#"Fix teh cache invalidation
+
+The cache is now invalidated on every change.
+# Please enter the commit message for your changes. teh
+# ------------------------ >8 ------------------------
+# Do not modify or remove the line above.
+diff --git a/src/lib.rs b/src/lib.rs
++/// teh
+"#,
+        )
+        .unwrap();
+
+        let mut config = Config::default();
+        config.hunspell = None;
+        config.languagetool = None;
+        config.nlprule = None;
+        config.repetition = None;
+        config.wordlist = Some(crate::config::WordlistConfig {
+            allow: None,
+            deny: Some(dir.join("deny.txt")),
+        });
+
+        let docs = load_commit_message(&path).expect("Must load");
+        let set = crate::testcase::check(&docs, &config);
+        let (_path, suggestions) = crate::testcase::first(set.iter());
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].detector, crate::Detector::Wordlist);
+        assert_eq!(suggestions[0].span.start.line, 1);
+        assert_eq!(suggestions[0].replacements, vec!["the".to_owned()]);
+
+        // blocks the commit
+        assert!(crate::Action::Check(crate::OutputFormat::Human)
+            .run(set, &config, true)
+            .is_err());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
 }
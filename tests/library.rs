@@ -0,0 +1,51 @@
+//! Checks through the library API, without the binary.
+
+use cargo_spellcheck::{Config, Detector, Documentation};
+
+#[test]
+fn check_in_memory() {
+    let mut config = Config::default();
+    // no dictionaries are required for repeated words
+    config.hunspell = None;
+
+    const SOURCE: &str = "/// Checks the the docs.\nstruct X;\n";
+    let documentation =
+        Documentation::load_from_str("lib.rs", SOURCE, &config).expect("Must parse");
+    let suggestions =
+        cargo_spellcheck::check(&documentation, &config, |_path| Some(SOURCE.to_owned()))
+            .expect("Must check");
+    assert_eq!(suggestions.count(), 1);
+
+    let (path, suggestions) = suggestions.iter().next().expect("Must contain a file");
+    assert_eq!(path.to_str(), Some("lib.rs"));
+    let suggestion = &suggestions[0];
+    assert_eq!(suggestion.detector, Detector::Repetition);
+    assert_eq!(suggestion.span.start.line, 1);
+    // the repetition is removed as a whole
+    assert_eq!(suggestion.replacements, vec![String::new()]);
+}
+
+#[test]
+fn check_clean() {
+    let mut config = Config::default();
+    config.hunspell = None;
+    let documentation =
+        Documentation::load_from_str("lib.rs", "/// Clean.\nstruct X;\n", &config).unwrap();
+    let suggestions = cargo_spellcheck::check(&documentation, &config, |_path| None).unwrap();
+    assert_eq!(suggestions.count(), 0);
+}
+
+#[test]
+fn check_directives_in_memory() {
+    let mut config = Config::default();
+    config.hunspell = None;
+    const SOURCE: &str = "/// Checks the the docs.\nstruct X;\n";
+    let documentation = Documentation::load_from_str("lib.rs", SOURCE, &config).unwrap();
+    // the directives are taken from the given source, not from a file on disk
+    let suggestions = cargo_spellcheck::check(&documentation, &config, |_path| None).unwrap();
+    assert_eq!(suggestions.count(), 1);
+    let source = SOURCE.replace("docs.\n", "docs. spellcheck:ignore-line\n");
+    let suggestions =
+        cargo_spellcheck::check(&documentation, &config, |_path| Some(source.clone())).unwrap();
+    assert_eq!(suggestions.count(), 0);
+}